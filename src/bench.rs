@@ -0,0 +1,388 @@
+use std::{
+    fmt::Display,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use aoc2022::util::{InputSource, MissingInput};
+use color_eyre::eyre::{eyre, WrapErr};
+
+use crate::runner::{format_duration, get_day, Part, Report};
+
+pub const DEFAULT_SAMPLES: usize = 10;
+/// Where the medians of the previous benchmark are stored to compare the next one against
+pub const DEFAULT_BASELINE: &str = "target/bench/baseline.toml";
+/// Sampling stops after this time, so the slow days do not take forever
+const TIME_BUDGET: Duration = Duration::from_secs(5);
+/// Changes of the median within this fraction are considered noise
+const NOISE_THRESHOLD: f64 = 0.05;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Step {
+    Parse,
+    Part(Part),
+}
+
+impl Step {
+    const fn key(self) -> &'static str {
+        match self {
+            Self::Parse => "parse",
+            Self::Part(Part::One) => "part1",
+            Self::Part(Part::Two) => "part2",
+        }
+    }
+}
+
+impl Display for Step {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Parse => write!(f, "parse"),
+            Self::Part(part) => write!(f, "part {part}"),
+        }
+    }
+}
+
+/// Summary of the samples of a single step
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Statistics {
+    pub samples: usize,
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+    pub mean: Duration,
+    pub std_dev: Duration,
+}
+
+impl Statistics {
+    /// # Panics
+    /// If there are no samples
+    #[must_use]
+    pub fn new(samples: &mut [Duration]) -> Self {
+        assert!(!samples.is_empty(), "Cannot summarize zero samples");
+        samples.sort_unstable();
+        let n = samples.len();
+        let count = f64::from(u32::try_from(n).expect("Too many samples"));
+        let median = if n.is_multiple_of(2) {
+            (samples[n / 2 - 1] + samples[n / 2]) / 2
+        } else {
+            samples[n / 2]
+        };
+        let mean = samples.iter().map(Duration::as_secs_f64).sum::<f64>() / count;
+        let variance = samples
+            .iter()
+            .map(|sample| (sample.as_secs_f64() - mean).powi(2))
+            .sum::<f64>()
+            / count;
+        Self {
+            samples: n,
+            min: samples[0],
+            median,
+            max: samples[n - 1],
+            mean: Duration::from_secs_f64(mean),
+            std_dev: Duration::from_secs_f64(variance.sqrt()),
+        }
+    }
+}
+
+impl Display for Statistics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[{} {} {}] ± {} ({} samples)",
+            format_duration(self.min),
+            format_duration(self.median),
+            format_duration(self.max),
+            format_duration(self.std_dev),
+            self.samples
+        )
+    }
+}
+
+/// The relative change of the median compared to the baseline
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Change(pub f64);
+
+impl Change {
+    /// Baselines below the timer resolution are taken as 1 ns, so the change stays finite
+    #[must_use]
+    pub fn new(baseline: Duration, current: Duration) -> Self {
+        let baseline = baseline.max(Duration::from_nanos(1));
+        Self(current.as_secs_f64() / baseline.as_secs_f64() - 1.0)
+    }
+
+    #[must_use]
+    pub fn is_regression(self) -> bool {
+        self.0 > NOISE_THRESHOLD
+    }
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let verdict = if self.is_regression() {
+            "regressed"
+        } else if self.0 < -NOISE_THRESHOLD {
+            "improved"
+        } else {
+            "within noise"
+        };
+        write!(f, "{:+.2}% ({verdict})", self.0 * 100.0)
+    }
+}
+
+/// Runs a day repeatedly (after one warm-up run) until `samples` runs are done
+/// or the time budget is used up, and summarizes every step that was run.
+/// # Errors
+/// If the day is not registered or fails
+pub fn sample(
+    number: u8,
+    input: &str,
+    parts: &[Part],
+    samples: usize,
+) -> color_eyre::Result<Vec<(Step, Statistics)>> {
+    let day = get_day(number).ok_or_else(|| eyre!("Day {number} is not solved"))?;
+    day.run(input, parts)?;
+    let mut reports: Vec<Report> = Vec::with_capacity(samples);
+    let start = Instant::now();
+    while reports.len() < samples.max(1) && (reports.is_empty() || start.elapsed() < TIME_BUDGET) {
+        reports.push(day.run(input, parts)?);
+    }
+    let mut steps = vec![(
+        Step::Parse,
+        Statistics::new(
+            &mut reports
+                .iter()
+                .map(|report| report.parse)
+                .collect::<Vec<_>>(),
+        ),
+    )];
+    for &part in parts {
+        // unsolved parts finish immediately and are not worth reporting
+        if reports[0]
+            .answers
+            .iter()
+            .any(|answer| answer.part == part && answer.value.is_none())
+        {
+            continue;
+        }
+        let mut samples: Vec<Duration> = reports
+            .iter()
+            .filter_map(|report| report.elapsed(part))
+            .collect();
+        steps.push((Step::Part(part), Statistics::new(&mut samples)));
+    }
+    Ok(steps)
+}
+
+/// The medians of a previous run in nanoseconds, stored as `[dayNN] parse = …, part1 = …`
+#[derive(Debug, Default)]
+pub struct Baseline(toml::Table);
+
+impl Baseline {
+    /// An empty baseline if the file does not exist yet
+    /// # Errors
+    /// If the file exists but cannot be read or parsed
+    pub fn load(path: &Path) -> color_eyre::Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let table = std::fs::read_to_string(path)
+            .wrap_err_with(|| format!("Cannot read benchmark baseline '{}'", path.display()))?
+            .parse()
+            .wrap_err_with(|| format!("Cannot parse benchmark baseline '{}'", path.display()))?;
+        Ok(Self(table))
+    }
+
+    /// # Errors
+    /// If the file cannot be written
+    pub fn save(&self, path: &Path) -> color_eyre::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .wrap_err_with(|| format!("Cannot create directory '{}'", dir.display()))?;
+        }
+        std::fs::write(path, self.0.to_string())
+            .wrap_err_with(|| format!("Cannot write benchmark baseline '{}'", path.display()))
+    }
+
+    #[must_use]
+    pub fn get(&self, day: u8, step: Step) -> Option<Duration> {
+        let nanos = self
+            .0
+            .get(&format!("day{day:02}"))?
+            .get(step.key())?
+            .as_integer()?;
+        Some(Duration::from_nanos(u64::try_from(nanos).ok()?))
+    }
+
+    pub fn set(&mut self, day: u8, step: Step, median: Duration) {
+        let day = self
+            .0
+            .entry(format!("day{day:02}"))
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+        if let toml::Value::Table(day) = day {
+            let nanos = i64::try_from(median.as_nanos()).unwrap_or(i64::MAX);
+            day.insert(step.key().to_owned(), toml::Value::Integer(nanos));
+        }
+    }
+}
+
+/// Benchmarks a day, prints the statistics of every step and how the median changed compared to the
+/// baseline, and replaces its medians in the baseline. Returns the number of steps that regressed.
+fn bench_day(
+    number: u8,
+    parts: &[Part],
+    input: &InputSource,
+    samples: usize,
+    baseline: &mut Baseline,
+) -> color_eyre::Result<usize> {
+    let input = input.read(number)?;
+    let mut regressions = 0;
+    for (step, statistics) in sample(number, &input, parts, samples)? {
+        let name = format!("day{number:02} {step}");
+        match baseline.get(number, step) {
+            Some(median) => {
+                let change = Change::new(median, statistics.median);
+                regressions += usize::from(change.is_regression());
+                println!("{name:<14} {statistics}  change: {change}");
+            }
+            None => println!("{name:<14} {statistics}"),
+        }
+        baseline.set(number, step, statistics.median);
+    }
+    Ok(regressions)
+}
+
+/// Benchmarks the selected days, prints the statistics of every step and how the median changed
+/// compared to the baseline, and then replaces the baseline with the new medians of the days that ran.
+/// Days without an input file are skipped.
+/// # Errors
+/// If a step regressed, so CI can fail on it, or if an input cannot be read, a day fails or the
+/// baseline cannot be read or written
+pub fn run(
+    days: &[u8],
+    parts: &[Part],
+    input: &InputSource,
+    samples: usize,
+    baseline: &Path,
+) -> color_eyre::Result<()> {
+    let mut previous = Baseline::load(baseline)?;
+    let mut regressions = 0;
+    let mut failure = None;
+    for &number in days {
+        match bench_day(number, parts, input, samples, &mut previous) {
+            Ok(regressed) => regressions += regressed,
+            Err(err) if err.is::<MissingInput>() => {
+                println!("day{number:02} is skipped because its input is missing ({err})");
+            }
+            Err(err) => {
+                failure = Some(err);
+                break;
+            }
+        }
+    }
+    // keep what was measured, even if a day failed
+    previous.save(baseline)?;
+    if let Some(err) = failure {
+        return Err(err);
+    }
+    if regressions > 0 {
+        return Err(eyre!(
+            "{regressions} steps regressed by more than {:.0}%",
+            NOISE_THRESHOLD * 100.0
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+pub mod tests {
+    use aoc2022::util::read_example;
+
+    use super::*;
+
+    #[test]
+    fn it_summarizes_samples() {
+        let mut samples: Vec<Duration> = [4, 1, 3, 2]
+            .into_iter()
+            .map(Duration::from_millis)
+            .collect();
+        let statistics = Statistics::new(&mut samples);
+        assert_eq!(Duration::from_millis(1), statistics.min);
+        assert_eq!(Duration::from_micros(2500), statistics.median);
+        assert_eq!(Duration::from_millis(4), statistics.max);
+        assert_eq!(Duration::from_micros(2500), statistics.mean);
+        assert_eq!(1118, statistics.std_dev.as_micros());
+    }
+
+    #[test]
+    fn it_detects_regressions() {
+        let change = Change::new(Duration::from_millis(100), Duration::from_millis(110));
+        assert!(change.is_regression());
+        assert_eq!("+10.00% (regressed)", change.to_string());
+        assert_eq!(
+            "-2.00% (within noise)",
+            Change::new(Duration::from_millis(100), Duration::from_millis(98)).to_string()
+        );
+        assert_eq!(
+            Change(0.0),
+            Change::new(Duration::ZERO, Duration::from_nanos(1))
+        );
+        assert!(Change::new(Duration::ZERO, Duration::ZERO).0.is_finite());
+    }
+
+    #[test]
+    fn it_stores_baselines() {
+        let mut baseline = Baseline::default();
+        baseline.set(3, Step::Part(Part::Two), Duration::from_micros(1234));
+        let baseline = Baseline(baseline.0.to_string().parse().unwrap());
+        assert_eq!(
+            Some(Duration::from_micros(1234)),
+            baseline.get(3, Step::Part(Part::Two))
+        );
+        assert_eq!(None, baseline.get(3, Step::Parse));
+    }
+
+    #[test]
+    fn it_samples_solved_steps() {
+        let steps = sample(25, &read_example("day25.txt"), &[Part::One, Part::Two], 3).unwrap();
+        assert_eq!(
+            vec![Step::Parse, Step::Part(Part::One)],
+            steps.iter().map(|(step, _)| *step).collect::<Vec<_>>()
+        );
+        assert_eq!(3, steps[1].1.samples);
+    }
+
+    #[test]
+    fn it_skips_missing_inputs_and_keeps_the_measured_days() {
+        let dir = std::env::temp_dir().join(format!("aoc2022-bench-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::copy("examples/day25.txt", dir.join("day25.txt")).unwrap();
+        std::fs::write(dir.join("day02.txt"), "A Q").unwrap();
+        let (input, baseline) = (
+            InputSource::Directory(dir.clone()),
+            dir.join("baseline.toml"),
+        );
+        run(&[24, 25], &[Part::One], &input, 1, &baseline).unwrap();
+        assert!(Baseline::load(&baseline)
+            .unwrap()
+            .get(25, Step::Part(Part::One))
+            .is_some());
+        std::fs::remove_file(&baseline).unwrap();
+        assert!(run(&[25, 2], &[Part::One], &input, 1, &baseline).is_err());
+        assert!(Baseline::load(&baseline)
+            .unwrap()
+            .get(25, Step::Parse)
+            .is_some());
+
+        // a baseline no run can beat is a regression, which is still replaced
+        std::fs::write(&baseline, "[day25]\npart1 = 0\n").unwrap();
+        let err = run(&[25], &[Part::One], &input, 1, &baseline).unwrap_err();
+        assert_eq!("1 steps regressed by more than 5%", err.to_string());
+        assert!(
+            Baseline::load(&baseline)
+                .unwrap()
+                .get(25, Step::Part(Part::One))
+                > Some(Duration::ZERO)
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
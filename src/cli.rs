@@ -0,0 +1,408 @@
+use std::{collections::HashSet, path::PathBuf};
+
+use aoc2022::util::{input_dir, InputSource};
+use color_eyre::eyre::{self, eyre};
+
+use crate::{
+    bench::{DEFAULT_BASELINE, DEFAULT_SAMPLES},
+    runner::{Format, Part, DAYS},
+    verify::ANSWERS_FILE,
+};
+
+pub const USAGE: &str = "\
+Usage: aoc2022 [run] [--all | --day N | --days A-B[,C...]] [--part 1|2]
+                     [--input FILE | --input-dir DIR] [--time] [--format text|json] [--jobs N]
+       aoc2022 verify [SELECTION...] [--answers FILE] [--jobs N]
+       aoc2022 bench [SELECTION...] [--samples N] [--baseline FILE]
+       aoc2022 new-day N [--input-dir DIR]
+
+Without a selection, all days are run.
+'verify' compares the answers with the recorded ones and fails on mismatches.
+'bench' runs the days repeatedly and compares the timings with the previous benchmark.
+'new-day' generates src/dayNN.rs, registers it and adds empty example and input files.
+  --all            run every registered day
+  --day N          run day N (may be repeated)
+  --days A-B,C     run a range and/or list of days
+  --part P         only run part P (1 or 2)
+  --input FILE     read the input of the (single) selected day from FILE, '-' for stdin
+  --input-dir DIR  read the inputs from DIR/dayNN.txt
+                   (default: $AOC2022_INPUT_DIR or resources/)
+  --time           print how long parsing and each part took
+  --format F       print the answers as 'text' (default) or as a 'json' array of
+                   {day, part, answer, elapsed_ns, error}
+  --jobs N         solve up to N days (or blueprints, sensors, ...) in parallel;
+                   the answers are still printed in order (default: 1)
+  --answers FILE   the recorded answers to verify against
+                   (default: answers.toml in the input directory)
+  --samples N      the number of runs to benchmark (default: 10)
+  --baseline FILE  where the previous timings are stored
+                   (default: target/bench/baseline.toml)
+  -h, --help       print this help";
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    Run {
+        selection: Selection,
+        time: bool,
+        format: Format,
+        jobs: usize,
+    },
+    Verify {
+        selection: Selection,
+        answers: PathBuf,
+        jobs: usize,
+    },
+    Bench {
+        selection: Selection,
+        samples: usize,
+        baseline: PathBuf,
+    },
+    NewDay {
+        day: u8,
+        inputs: PathBuf,
+    },
+    Help,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Selection {
+    pub days: Vec<u8>,
+    pub parts: Vec<Part>,
+    pub input: InputSource,
+}
+
+impl Default for Selection {
+    fn default() -> Self {
+        Self {
+            days: DAYS.iter().map(|day| day.number).collect(),
+            parts: vec![Part::One, Part::Two],
+            input: InputSource::default(),
+        }
+    }
+}
+
+/// Parses the command line arguments (without the program name).
+/// # Errors
+/// If an argument is unknown or a value is missing or invalid
+pub fn parse_args<I>(args: I) -> eyre::Result<Command>
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    let mut args = args.into_iter().peekable();
+    let subcommand = match args.peek().map(AsRef::as_ref) {
+        Some("new-day") => {
+            args.next();
+            return parse_new_day(args);
+        }
+        Some(name @ ("run" | "verify" | "bench")) => {
+            let name = name.to_owned();
+            args.next();
+            name
+        }
+        _ => "run".to_owned(),
+    };
+    let mut time = false;
+    let mut format = Format::default();
+    let mut answers = None;
+    let mut jobs = 1;
+    let mut samples = DEFAULT_SAMPLES;
+    let mut baseline = PathBuf::from(DEFAULT_BASELINE);
+    let mut days: Vec<u8> = Vec::new();
+    let mut all = false;
+    let mut selection = Selection::default();
+    while let Some(arg) = args.next() {
+        let arg = arg.as_ref();
+        let mut value = || {
+            args.next()
+                .map(|v| v.as_ref().to_owned())
+                .ok_or_else(|| eyre!("Missing value for '{arg}'"))
+        };
+        match arg {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--all" => all = true,
+            "--day" | "-d" => days.push(parse_day(&value()?)?),
+            "--days" => days.extend(parse_days(&value()?)?),
+            "--part" | "-p" => {
+                let part: u8 = value()?.parse()?;
+                selection.parts = vec![Part::try_from(part)?];
+            }
+            "--input" | "-i" | "--input-dir" => {
+                selection.input = parse_input(&selection.input, arg, value()?)?;
+            }
+            "--time" if subcommand == "run" => time = true,
+            "--format" if subcommand == "run" => format = value()?.parse()?,
+            "--answers" if subcommand == "verify" => answers = Some(PathBuf::from(value()?)),
+            "--jobs" | "-j" if subcommand != "bench" => jobs = parse_positive(&value()?, "job")?,
+            "--samples" if subcommand == "bench" => samples = parse_positive(&value()?, "sample")?,
+            "--baseline" if subcommand == "bench" => baseline = PathBuf::from(value()?),
+            _ => return Err(eyre!("Unknown argument '{arg}'\n\n{USAGE}")),
+        }
+    }
+    if time && format == Format::Json {
+        return Err(eyre!(
+            "'--time' cannot be combined with '--format json', which already has the timings"
+        ));
+    }
+    if all && !days.is_empty() {
+        return Err(eyre!("'--all' cannot be combined with '--day' or '--days'"));
+    }
+    if !days.is_empty() {
+        let mut seen = HashSet::new();
+        days.retain(|&day| seen.insert(day));
+        selection.days = days;
+    }
+    if matches!(selection.input, InputSource::File(_) | InputSource::Stdin)
+        && selection.days.len() != 1
+    {
+        return Err(eyre!("'--input' requires exactly one day to be selected"));
+    }
+    Ok(match subcommand.as_str() {
+        "verify" => {
+            let answers = answers.unwrap_or_else(|| match &selection.input {
+                InputSource::Directory(dir) => dir.join(ANSWERS_FILE),
+                _ => input_dir().join(ANSWERS_FILE),
+            });
+            Command::Verify {
+                selection,
+                answers,
+                jobs,
+            }
+        }
+        "bench" => Command::Bench {
+            selection,
+            samples,
+            baseline,
+        },
+        _ => Command::Run {
+            selection,
+            time,
+            format,
+            jobs,
+        },
+    })
+}
+
+fn parse_input(current: &InputSource, arg: &str, path: String) -> eyre::Result<InputSource> {
+    if *current != InputSource::Default {
+        return Err(eyre!(
+            "Only one of '--input' and '--input-dir' can be given"
+        ));
+    }
+    Ok(match (arg, path.as_str()) {
+        ("--input-dir", _) => InputSource::Directory(PathBuf::from(path)),
+        (_, "-") => InputSource::Stdin,
+        _ => InputSource::File(PathBuf::from(path)),
+    })
+}
+
+fn parse_new_day(args: impl Iterator<Item = impl AsRef<str>>) -> eyre::Result<Command> {
+    let args: Vec<_> = args.map(|arg| arg.as_ref().to_owned()).collect();
+    let (day, inputs) = match args.as_slice() {
+        [day] => (day, input_dir()),
+        [day, option, dir] | [option, dir, day] if option == "--input-dir" => {
+            (day, PathBuf::from(dir))
+        }
+        _ => {
+            return Err(eyre!(
+                "'new-day' expects a day and optionally '--input-dir DIR'\n\n{USAGE}"
+            ))
+        }
+    };
+    let day: u8 = day
+        .parse()
+        .map_err(|err| eyre!("Cannot parse day '{day}': {err}"))?;
+    if !(1..=25).contains(&day) {
+        return Err(eyre!("There is no day {day}, only 1 to 25"));
+    }
+    Ok(Command::NewDay { day, inputs })
+}
+
+fn parse_positive(s: &str, what: &str) -> eyre::Result<usize> {
+    match s.parse()? {
+        0 => Err(eyre!("At least one {what} is needed")),
+        n => Ok(n),
+    }
+}
+
+fn parse_day(s: &str) -> eyre::Result<u8> {
+    let day: u8 = s
+        .trim()
+        .parse()
+        .map_err(|err| eyre!("Cannot parse day '{s}': {err}"))?;
+    if DAYS.iter().any(|d| d.number == day) {
+        Ok(day)
+    } else {
+        Err(eyre!("Day {day} is not solved"))
+    }
+}
+
+/// Parses a comma separated list of days and inclusive ranges, e.g. `1,3-9`.
+fn parse_days(s: &str) -> eyre::Result<Vec<u8>> {
+    let mut days = Vec::new();
+    for segment in s.split(',') {
+        if let Some((from, to)) = segment.split_once('-') {
+            let (from, to) = (parse_day(from)?, parse_day(to)?);
+            if from > to {
+                return Err(eyre!("Invalid range of days '{segment}'"));
+            }
+            days.extend(from..=to);
+        } else {
+            days.push(parse_day(segment)?);
+        }
+    }
+    Ok(days)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn it_runs_everything_by_default() {
+        let no_args: [&str; 0] = [];
+        let everything = Command::Run {
+            selection: Selection::default(),
+            time: false,
+            format: Format::Text,
+            jobs: 1,
+        };
+        assert_eq!(everything, parse_args(no_args).unwrap());
+        assert_eq!(everything, parse_args(["run", "--all"]).unwrap());
+    }
+
+    #[test]
+    fn it_selects_a_single_part() {
+        assert_eq!(
+            Command::Run {
+                selection: Selection {
+                    days: vec![16],
+                    parts: vec![Part::Two],
+                    input: InputSource::Default,
+                },
+                time: true,
+                format: Format::Text,
+                jobs: 1,
+            },
+            parse_args(["run", "--day", "16", "--part", "2", "--time"]).unwrap()
+        );
+    }
+
+    #[test]
+    fn it_selects_the_output_format() {
+        let Command::Run { format, .. } = parse_args(["--format", "json"]).unwrap() else {
+            panic!("Expected run command");
+        };
+        assert_eq!(Format::Json, format);
+        assert!(parse_args(["--format", "xml"]).is_err());
+        assert!(parse_args(["--format", "json", "--time"]).is_err());
+        assert!(parse_args(["verify", "--format", "json"]).is_err());
+    }
+
+    #[test]
+    fn it_selects_input_source() {
+        let Command::Run { selection, .. } = parse_args(["--day", "3", "--input", "-"]).unwrap()
+        else {
+            panic!("Expected run command");
+        };
+        assert_eq!(InputSource::Stdin, selection.input);
+        let Command::Run { selection, .. } = parse_args(["--input-dir", "inputs"]).unwrap() else {
+            panic!("Expected run command");
+        };
+        assert_eq!(
+            InputSource::Directory(PathBuf::from("inputs")),
+            selection.input
+        );
+        assert!(parse_args(["--input", "day03.txt"]).is_err());
+        assert!(parse_args(["--day", "3", "--input", "a", "--input-dir", "b"]).is_err());
+    }
+
+    #[test]
+    fn it_selects_ranges_of_days() {
+        let Command::Run { selection, .. } = parse_args(["--days", "3-5,9"]).unwrap() else {
+            panic!("Expected run command");
+        };
+        assert_eq!(vec![3, 4, 5, 9], selection.days);
+        assert_eq!(vec![Part::One, Part::Two], selection.parts);
+        let Command::Run { selection, .. } =
+            parse_args(["--day", "3", "--day", "4", "--days", "2-3"]).unwrap()
+        else {
+            panic!("Expected run command");
+        };
+        assert_eq!(vec![3, 4, 2], selection.days);
+    }
+
+    #[test]
+    fn it_rejects_invalid_arguments() {
+        assert!(parse_args(["--day", "26"]).is_err());
+        assert!(parse_args(["--days", "9-3"]).is_err());
+        assert!(parse_args(["--part", "3"]).is_err());
+        assert!(parse_args(["--day"]).is_err());
+        assert!(parse_args(["--all", "--day", "1"]).is_err());
+        assert!(parse_args(["--frobnicate"]).is_err());
+        assert!(parse_args(["--answers", "answers.toml"]).is_err());
+        assert!(parse_args(["verify", "--time"]).is_err());
+        assert!(parse_args(["bench", "--samples", "0"]).is_err());
+        assert!(parse_args(["--jobs", "0"]).is_err());
+        assert!(parse_args(["bench", "--jobs", "2"]).is_err());
+    }
+
+    #[test]
+    fn it_verifies_against_answers() {
+        assert_eq!(
+            Command::Verify {
+                selection: Selection {
+                    days: vec![1, 2],
+                    ..Selection::default()
+                },
+                answers: PathBuf::from("mine.toml"),
+                jobs: 4,
+            },
+            parse_args([
+                "verify",
+                "--days",
+                "1-2",
+                "--answers",
+                "mine.toml",
+                "--jobs",
+                "4"
+            ])
+            .unwrap()
+        );
+        let Command::Verify { answers, .. } =
+            parse_args(["verify", "--input-dir", "inputs"]).unwrap()
+        else {
+            panic!("Expected verify command");
+        };
+        assert_eq!(PathBuf::from("inputs/answers.toml"), answers);
+    }
+
+    #[test]
+    fn it_scaffolds_days() {
+        assert_eq!(
+            Command::NewDay {
+                day: 7,
+                inputs: PathBuf::from("inputs"),
+            },
+            parse_args(["new-day", "7", "--input-dir", "inputs"]).unwrap()
+        );
+        assert!(parse_args(["new-day", "26"]).is_err());
+        assert!(parse_args(["new-day"]).is_err());
+        assert!(parse_args(["new-day", "7", "--time"]).is_err());
+    }
+
+    #[test]
+    fn it_benchmarks() {
+        assert_eq!(
+            Command::Bench {
+                selection: Selection {
+                    days: vec![19],
+                    ..Selection::default()
+                },
+                samples: 3,
+                baseline: PathBuf::from(DEFAULT_BASELINE),
+            },
+            parse_args(["bench", "--day", "19", "--samples", "3"]).unwrap()
+        );
+    }
+}
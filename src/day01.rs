@@ -1,44 +1,61 @@
-use crate::util::read_input;
+use crate::{solution::Solution, util::parse};
 
-pub fn solve() {
-    let input = read_input("day01.txt");
-    println!("Day 01 part 1: {:?}", part1(&input));
-    println!("Day 01 part 2: {:?}", part2(&input));
-}
+pub struct Day01;
 
-fn get_elves(input: &str) -> Vec<usize> {
-    input
-        .replace("\r\n", "\n")
-        .split("\n\n")
-        .map(|elf| elf.lines().map(|line| line.parse::<usize>().unwrap()).sum())
-        .collect()
-}
+impl Solution for Day01 {
+    const DAY: u8 = 1;
+    type Input = Vec<usize>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> color_eyre::Result<Self::Input> {
+        get_elves(input)
+    }
 
-fn part1(input: &str) -> usize {
-    get_elves(input).into_iter().max().unwrap()
+    fn part1(elves: &Self::Input) -> color_eyre::Result<usize> {
+        elves
+            .iter()
+            .copied()
+            .max()
+            .ok_or_else(|| color_eyre::eyre::eyre!("There are no elves"))
+    }
+
+    fn part2(elves: &Self::Input) -> color_eyre::Result<usize> {
+        let mut elves = elves.clone();
+        elves.sort_unstable_by(|a, b| b.cmp(a)); // reverse sort
+        Ok(elves.into_iter().take(3).sum())
+    }
 }
 
-fn part2(input: &str) -> usize {
-    let mut elves = get_elves(input);
-    elves.sort_unstable_by(|a, b| b.cmp(a)); // reverse sort
-    elves.into_iter().take(3).sum()
+fn get_elves(input: &str) -> color_eyre::Result<Vec<usize>> {
+    let calories = parse::lines(input, |line| {
+        if line.is_empty() {
+            Ok(None)
+        } else {
+            line.parse::<usize>().map(Some)
+        }
+    })?;
+    Ok(calories
+        .split(Option::is_none)
+        .map(|elf| elf.iter().flatten().sum())
+        .collect())
 }
 
 #[cfg(test)]
 pub mod tests {
-    use crate::util::read_example;
+    use crate::{solution::Solution, util::read_example};
 
-    use super::{part1, part2};
+    use super::Day01;
 
     #[test]
     fn it_finds_the_elf_with_most_calories() {
         let input = read_example("day01.txt");
-        assert_eq!(24000, part1(&input));
+        assert_eq!(24000, Day01::solve_part1(&input).unwrap());
     }
 
     #[test]
     fn it_finds_the_top_3_elves() {
         let input = read_example("day01.txt");
-        assert_eq!(45000, part2(&input));
+        assert_eq!(45000, Day01::solve_part2(&input).unwrap());
     }
 }
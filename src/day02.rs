@@ -1,31 +1,63 @@
 use std::{cmp::Ordering, str::FromStr};
 
-use crate::util::read_input;
+use crate::{
+    solution::Solution,
+    util::{parse, ParseError},
+};
 
-pub fn solve(){
-    let input = read_input("day02.txt");
-    println!("Day 02 part 1: {}", play_part1(&input));
-    println!("Day 02 part 2: {}", play_part2(&input));
-}
+pub struct Day02;
+
+impl Solution for Day02 {
+    const DAY: u8 = 2;
+    type Input = Vec<Round>;
+    type Answer1 = usize;
+    type Answer2 = usize;
 
-pub fn play_part1(input: &str) -> usize {
-    let mut score = 0;
-    for line in input.lines() {
+    fn parse(input: &str) -> color_eyre::Result<Self::Input> {
+        Ok(parse::lines(input, str::parse)?)
+    }
+
+    fn part1(rounds: &Self::Input) -> color_eyre::Result<usize> {
         // the s in (s)elf is silent
-        let (other, elf): (Shape, Shape) = line.trim().split_once(' ').map(|(other, elf)| (other.parse().unwrap(), elf.parse().unwrap())).unwrap();
-        score += elf.play(other);
+        Ok(rounds
+            .iter()
+            .map(|round| round.shape.play(round.other))
+            .sum())
+    }
+
+    fn part2(rounds: &Self::Input) -> color_eyre::Result<usize> {
+        Ok(rounds
+            .iter()
+            .map(|round| round.other.target(round.target).play(round.other))
+            .sum())
     }
-    score
 }
 
-pub fn play_part2(input: &str) -> usize {
-    let mut score = 0;
-    for line in input.lines() {
-        // the s in (s)elf is silent
-        let (other, target): (Shape, Target) = line.trim().split_once(' ').map(|(other, target)| (other.parse().unwrap(), target.parse().unwrap())).unwrap();
-        score += other.target(target).play(other);
+/// The second column is either the shape to play (part 1) or the target outcome (part 2)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Round {
+    other: Shape,
+    shape: Shape,
+    target: Target,
+}
+
+impl FromStr for Round {
+    type Err = color_eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (other, elf) = s
+            .trim()
+            .split_once(' ')
+            .ok_or_else(|| ParseError::at(s, s.trim(), format!("Cannot parse round: '{s}'")))?;
+        let other = other.parse().map_err(|err| ParseError::at(s, other, err))?;
+        let shape = elf.parse().map_err(|err| ParseError::at(s, elf, err))?;
+        let target = elf.parse().map_err(|err| ParseError::at(s, elf, err))?;
+        Ok(Self {
+            other,
+            shape,
+            target,
+        })
     }
-    score
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -42,7 +74,7 @@ pub enum Target {
     Win,
 }
 
-impl FromStr for Target{
+impl FromStr for Target {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
@@ -50,13 +82,11 @@ impl FromStr for Target{
             "X" => Ok(Self::Lose),
             "Y" => Ok(Self::Draw),
             "Z" => Ok(Self::Win),
-            x => Err(format!("Illegal target: '{x}'"))
+            x => Err(format!("Illegal target: '{x}'")),
         }
     }
 }
 
-
-
 impl FromStr for Shape {
     type Err = String;
 
@@ -65,7 +95,7 @@ impl FromStr for Shape {
             "A" | "X" => Ok(Self::Rock),
             "B" | "Y" => Ok(Self::Paper),
             "C" | "Z" => Ok(Self::Scissor),
-            x => Err(format!("Illegal character: '{x}'"))
+            x => Err(format!("Illegal character: '{x}'")),
         }
     }
 }
@@ -73,9 +103,15 @@ impl FromStr for Shape {
 impl Ord for Shape {
     fn cmp(&self, other: &Self) -> Ordering {
         match (self, other) {
-            (Self::Rock, Self::Rock) | (Self::Paper, Self::Paper) | (Self::Scissor, Self::Scissor) => Ordering::Equal,
-            (Self::Rock, Self::Paper) | (Self::Paper, Self::Scissor) | (Self::Scissor, Self::Rock) => Ordering::Less,
-            (Self::Rock, Self::Scissor) |  (Self::Paper, Self::Rock) | (Self::Scissor, Self::Paper)=> Ordering::Greater,
+            (Self::Rock, Self::Rock)
+            | (Self::Paper, Self::Paper)
+            | (Self::Scissor, Self::Scissor) => Ordering::Equal,
+            (Self::Rock, Self::Paper)
+            | (Self::Paper, Self::Scissor)
+            | (Self::Scissor, Self::Rock) => Ordering::Less,
+            (Self::Rock, Self::Scissor)
+            | (Self::Paper, Self::Rock)
+            | (Self::Scissor, Self::Paper) => Ordering::Greater,
         }
     }
 }
@@ -87,7 +123,7 @@ impl PartialOrd for Shape {
 }
 
 impl Shape {
-
+    #[must_use]
     pub const fn value(self) -> usize {
         match self {
             Self::Rock => 1,
@@ -95,6 +131,7 @@ impl Shape {
             Self::Scissor => 3,
         }
     }
+    #[must_use]
     pub fn play(self, other: Self) -> usize {
         match self.cmp(&other) {
             Ordering::Less => self.value(),
@@ -103,21 +140,29 @@ impl Shape {
         }
     }
 
+    #[must_use]
     pub const fn target(self, target: Target) -> Self {
         match (target, self) {
-            (Target::Lose, Self::Paper) | (Target::Draw, Self::Rock) | (Target::Win, Self::Scissor) => Self::Rock,
-            (Target::Lose, Self::Scissor) | (Target::Draw, Self::Paper) | (Target::Win, Self::Rock) => Self::Paper,
-            (Target::Lose, Self::Rock) | (Target::Draw, Self::Scissor) | (Target::Win, Self::Paper) => Self::Scissor,
+            (Target::Lose, Self::Paper)
+            | (Target::Draw, Self::Rock)
+            | (Target::Win, Self::Scissor) => Self::Rock,
+            (Target::Lose, Self::Scissor)
+            | (Target::Draw, Self::Paper)
+            | (Target::Win, Self::Rock) => Self::Paper,
+            (Target::Lose, Self::Rock)
+            | (Target::Draw, Self::Scissor)
+            | (Target::Win, Self::Paper) => Self::Scissor,
         }
     }
 }
 
-
 #[cfg(test)]
 mod tests {
-    use crate::{day02::{Shape, play_part2}, util::read_example};
-
-    use super::play_part1;
+    use crate::{
+        day02::{Day02, Shape},
+        solution::Solution,
+        util::read_example,
+    };
 
     #[test]
     fn it_finds_winner() {
@@ -135,11 +180,11 @@ mod tests {
 
     #[test]
     fn it_scores_example_part1() {
-        assert_eq!(15, play_part1(&read_example("day02.txt")));
+        assert_eq!(15, Day02::solve_part1(&read_example("day02.txt")).unwrap());
     }
 
     #[test]
     fn it_scores_example_part2() {
-        assert_eq!(12, play_part2(&read_example("day02.txt")));
+        assert_eq!(12, Day02::solve_part2(&read_example("day02.txt")).unwrap());
     }
-}
\ No newline at end of file
+}
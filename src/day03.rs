@@ -1,6 +1,38 @@
 use std::{collections::HashSet, str::FromStr};
 
-use crate::util::read_input;
+use crate::{solution::Solution, util::parse};
+
+pub struct Day03;
+
+impl Solution for Day03 {
+    const DAY: u8 = 3;
+    type Input = Vec<Rucksack>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> color_eyre::Result<Self::Input> {
+        Ok(parse::lines(input, str::parse)?)
+    }
+
+    fn part1(rucksacks: &Self::Input) -> color_eyre::Result<usize> {
+        Ok(rucksacks
+            .iter()
+            .map(Rucksack::get_duplicates_priority)
+            .sum())
+    }
+
+    fn part2(rucksacks: &Self::Input) -> color_eyre::Result<usize> {
+        Ok(rucksacks
+            .chunks_exact(3)
+            .map(|bags| {
+                Rucksack::get_common_items(bags)
+                    .iter()
+                    .map(|x| priority(**x))
+                    .sum::<usize>()
+            })
+            .sum())
+    }
+}
 
 #[derive(Clone, Debug)]
 pub struct Rucksack {
@@ -19,11 +51,13 @@ impl FromStr for Rucksack {
 }
 
 impl Rucksack {
+    #[must_use]
     pub fn get_duplicates(&self) -> Vec<&char> {
         self.compartments[0]
             .intersection(&self.compartments[1])
             .collect()
     }
+    #[must_use]
     pub fn get_duplicates_priority(&self) -> usize {
         self.get_duplicates()
             .into_iter()
@@ -31,10 +65,12 @@ impl Rucksack {
             .sum()
     }
 
+    #[must_use]
     pub fn get_content(&self) -> HashSet<&char> {
         self.compartments[0].union(&self.compartments[1]).collect()
     }
 
+    #[must_use]
     pub fn get_common_items(bags: &[Self]) -> HashSet<&char> {
         let mut common: Option<HashSet<_>> = None;
         for o in bags {
@@ -55,49 +91,13 @@ const fn priority(c: char) -> usize {
     }
 }
 
-pub fn solve() -> color_eyre::Result<()> {
-    let input = read_input("day03.txt");
-    println!("Day 03 part 1: {}", part1(&input)?);
-    println!("Day 03 part 2: {}", part2(&input)?);
-    Ok(())
-}
-
-pub fn part1(input: &str) -> color_eyre::Result<usize> {
-    let mut sum = 0;
-    for line in input.lines() {
-        let rucksack: Rucksack = line.parse()?;
-        sum += rucksack.get_duplicates_priority();
-    }
-    Ok(sum)
-}
-
-pub fn part2(input: &str) -> color_eyre::Result<usize> {
-    let mut lines = input.lines();
-    let mut sum = 0;
-    while let Ok(bags) = lines.next_chunk::<3>() {
-        sum += Rucksack::get_common_items(
-            &bags
-                .iter()
-                .map(|b| Rucksack::from_str(b))
-                .collect::<color_eyre::Result<Vec<_>>>()?,
-        )
-        .iter()
-        .map(|x| priority(**x))
-        .sum::<usize>();
-    }
-    Ok(sum)
-}
-
 #[cfg(test)]
 pub mod tests {
     use std::str::FromStr;
 
-    use crate::{
-        day03::{part1, part2},
-        util::read_example,
-    };
+    use crate::{solution::Solution, util::read_example};
 
-    use super::Rucksack;
+    use super::{Day03, Rucksack};
 
     #[test]
     fn it_finds_duplicate() {
@@ -128,7 +128,7 @@ pub mod tests {
     #[test]
     fn it_solves_part1() -> color_eyre::Result<()> {
         let input = read_example("day03.txt");
-        assert_eq!(157, part1(&input)?);
+        assert_eq!(157, Day03::solve_part1(&input)?);
         Ok(())
     }
 
@@ -166,7 +166,7 @@ pub mod tests {
     #[test]
     fn it_solves_part2() -> color_eyre::Result<()> {
         let input = read_example("day03.txt");
-        assert_eq!(70, part2(&input)?);
+        assert_eq!(70, Day03::solve_part2(&input)?);
         Ok(())
     }
 }
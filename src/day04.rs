@@ -1,6 +1,30 @@
 use std::str::FromStr;
 
-use crate::util::read_input;
+use crate::{
+    solution::Solution,
+    util::{parse, ParseError},
+};
+
+pub struct Day04;
+
+impl Solution for Day04 {
+    const DAY: u8 = 4;
+    type Input = Vec<Pair>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> color_eyre::Result<Self::Input> {
+        Ok(parse::lines(input, Pair::from_str)?)
+    }
+
+    fn part1(pairs: &Self::Input) -> color_eyre::Result<usize> {
+        Ok(pairs.iter().filter(|pair| pair.fully_contains()).count())
+    }
+
+    fn part2(pairs: &Self::Input) -> color_eyre::Result<usize> {
+        Ok(pairs.iter().filter(|p| p.overlap()).count())
+    }
+}
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub struct Pair(Elf, Elf);
@@ -11,16 +35,22 @@ impl FromStr for Pair {
         let (x, y) = s
             .trim()
             .split_once(',')
-            .ok_or(color_eyre::eyre::eyre!("Cannot parse pair: '{s}'"))?;
-        Ok(Self(x.parse()?, y.parse()?))
+            .ok_or_else(|| ParseError::at(s, s.trim(), format!("Cannot parse pair: '{s}'")))?;
+        let elf = |elf: &str| {
+            elf.parse()
+                .map_err(|err| ParseError::at(s, elf, format!("{err:#}")))
+        };
+        Ok(Self(elf(x)?, elf(y)?))
     }
 }
 
 impl Pair {
+    #[must_use]
     pub const fn fully_contains(&self) -> bool {
         self.0.fully_contains(&self.1) || self.1.fully_contains(&self.0)
     }
 
+    #[must_use]
     pub const fn overlap(&self) -> bool {
         self.0.contains(self.1.start) || self.1.contains(self.0.start)
     }
@@ -49,42 +79,21 @@ impl FromStr for Elf {
 }
 
 impl Elf {
+    #[must_use]
     pub const fn fully_contains(&self, other: &Self) -> bool {
         self.start <= other.start && self.end >= other.end
     }
 
+    #[must_use]
     pub const fn contains(&self, value: usize) -> bool {
         self.start <= value && value <= self.end
     }
 }
 
-pub fn solve() -> color_eyre::Result<()> {
-    let input = read_input("day04.txt");
-    println!("Day 04 part 1: {}", part1(&input)?);
-    println!("Day 04 part 2: {}", part2(&input)?);
-    Ok(())
-}
-
-fn part1(input: &str) -> color_eyre::Result<usize> {
-    let pairs: Vec<Pair> = input
-        .lines()
-        .map(Pair::from_str)
-        .collect::<color_eyre::Result<Vec<_>>>()?;
-    Ok(pairs.iter().filter(|pair| pair.fully_contains()).count())
-}
-
-fn part2(input: &str) -> color_eyre::Result<usize> {
-    let pairs: Vec<Pair> = input
-        .lines()
-        .map(Pair::from_str)
-        .collect::<color_eyre::Result<Vec<_>>>()?;
-    Ok(pairs.iter().filter(|p| p.overlap()).count())
-}
-
 #[cfg(test)]
 pub mod tests {
 
-    use crate::util;
+    use crate::{solution::Solution, util};
 
     use super::*;
 
@@ -105,7 +114,7 @@ pub mod tests {
     #[test]
     fn it_finds_fully_contains() {
         let input = util::read_example("day04.txt");
-        assert_eq!(2, part1(&input).unwrap());
+        assert_eq!(2, Day04::solve_part1(&input).unwrap());
     }
 
     #[test]
@@ -118,6 +127,6 @@ pub mod tests {
     #[test]
     fn it_finds_all_overlaps() {
         let input = util::read_example("day04.txt");
-        assert_eq!(4, part2(&input).unwrap());
+        assert_eq!(4, Day04::solve_part2(&input).unwrap());
     }
 }
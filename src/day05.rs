@@ -2,12 +2,53 @@ use color_eyre::eyre;
 use std::fmt::Write;
 use std::{fmt::Display, str::FromStr};
 
-use crate::util::read_input;
-pub fn solve() -> color_eyre::Result<()> {
-    let input = read_input("day05.txt");
-    println!("Day 05 part 1: {}", part1(&input)?);
-    println!("Day 05 part 1: {}", part2(&input)?);
-    Ok(())
+use crate::{
+    solution::Solution,
+    util::{parse, ParseError},
+};
+
+pub struct Day05;
+
+impl Solution for Day05 {
+    const DAY: u8 = 5;
+    type Input = (Ship, Vec<Instruction>);
+    type Answer1 = String;
+    type Answer2 = String;
+
+    fn parse(input: &str) -> color_eyre::Result<Self::Input> {
+        let binding = input.replace("\r\n", "\n");
+        let (ship, instructions) = binding.split_once("\n\n").ok_or_else(|| {
+            color_eyre::eyre::eyre!("Cannot split between stacks and instructions")
+        })?;
+        let offset = ship.lines().count() + 1;
+        let parsed: Vec<Instruction> =
+            parse::lines(instructions, str::parse).map_err(|err| err.offset(offset))?;
+        let ship: Ship = ship.parse()?;
+        for (i, (line, instruction)) in instructions.lines().zip(&parsed).enumerate() {
+            // the stack numbers are the 4th and 6th word of "move 1 from 2 to 1"
+            for (number, word) in [(instruction.from, 3), (instruction.to, 5)] {
+                if !(1..=ship.stacks.len()).contains(&usize::from(number)) {
+                    let token = line.split_ascii_whitespace().nth(word).unwrap_or_default();
+                    let message = format!(
+                        "There is no stack {number}, only 1 to {}",
+                        ship.stacks.len()
+                    );
+                    return Err(ParseError::at(line, token, message)
+                        .offset(offset + i)
+                        .into());
+                }
+            }
+        }
+        Ok((ship, parsed))
+    }
+
+    fn part1((ship, instructions): &Self::Input) -> color_eyre::Result<String> {
+        both_parts(ship, instructions, Crane::CrateMover9000)
+    }
+
+    fn part2((ship, instructions): &Self::Input) -> color_eyre::Result<String> {
+        both_parts(ship, instructions, Crane::CrateMover9001)
+    }
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
@@ -36,6 +77,7 @@ impl Stack {
         self.0.pop()
     }
 
+    #[must_use]
     pub fn last(&self) -> Option<&Crate> {
         self.0.last()
     }
@@ -55,19 +97,18 @@ impl FromStr for Instruction {
         // pattern: "move 1 from 2 to 1"
         // positions are 1-based
         let mut segments = s.split_ascii_whitespace().skip(1).step_by(2);
-        Ok(Self {
-            quantity: segments
-                .next()
-                .ok_or(eyre::eyre!("Cannot get quantity"))?
-                .parse()?,
-            from: segments
+        let mut number = |name: &str| -> Result<u8, ParseError> {
+            let segment = segments
                 .next()
-                .ok_or(eyre::eyre!("Cannot get from"))?
-                .parse()?,
-            to: segments
-                .next()
-                .ok_or(eyre::eyre!("Cannot get to"))?
-                .parse()?,
+                .ok_or_else(|| ParseError::at(s, "", format!("Cannot get {name}")))?;
+            segment
+                .parse()
+                .map_err(|err| ParseError::at(s, segment, err))
+        };
+        Ok(Self {
+            quantity: number("quantity")?,
+            from: number("from")?,
+            to: number("to")?,
         })
     }
 }
@@ -89,12 +130,23 @@ impl FromStr for Ship {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut ship = Self::default();
         let mut is_first_row = true;
-        for line in s.lines().rev() {
+        let lines: Vec<&str> = s.lines().collect();
+        for (row, &line) in lines.iter().enumerate().rev() {
             for (idx, c) in line.chars().skip(1).step_by(4).enumerate() {
                 if is_first_row {
                     ship.stacks.push(Stack::default());
                 } else if c.is_alphabetic() {
-                    ship.stacks[idx].push(c.into());
+                    ship.stacks
+                        .get_mut(idx)
+                        .ok_or_else(|| {
+                            ParseError::new(
+                                row + 1,
+                                4 * idx + 2,
+                                line,
+                                "Crate outside of all stacks",
+                            )
+                        })?
+                        .push(c.into());
                 }
             }
             is_first_row = false;
@@ -118,7 +170,7 @@ impl Ship {
             }
             Crane::CrateMover9001 => {
                 for i in instructions {
-                    self.execute_9001(i);
+                    self.execute_9001(i)?;
                 }
             }
         }
@@ -136,8 +188,15 @@ impl Ship {
         Ok(())
     }
 
-    fn execute_9001(&mut self, instruction: &Instruction) {
+    fn execute_9001(&mut self, instruction: &Instruction) -> color_eyre::Result<()> {
         let source_len = self.stacks[instruction.from as usize - 1].0.len();
+        if source_len < usize::from(instruction.quantity) {
+            return Err(eyre::eyre!(
+                "Cannot move {} crates from stack {}, which holds {source_len}",
+                instruction.quantity,
+                instruction.from
+            ));
+        }
 
         let moved_stack: Vec<Crate> = self.stacks[instruction.from as usize - 1]
             .0
@@ -146,38 +205,33 @@ impl Ship {
         self.stacks[instruction.to as usize - 1]
             .0
             .extend(moved_stack);
+        Ok(())
     }
 
-    fn get_tops(&self) -> String {
-        self.stacks.iter().fold(String::new(), |mut output, s| {
-            let _ = write!(output, "{}", *s.last().unwrap());
-            output
-        })
+    fn get_tops(&self) -> color_eyre::Result<String> {
+        self.stacks
+            .iter()
+            .enumerate()
+            .try_fold(String::new(), |mut output, (i, s)| {
+                let top = s
+                    .last()
+                    .ok_or_else(|| eyre::eyre!("Stack {} is empty", i + 1))?;
+                let _ = write!(output, "{top}");
+                Ok(output)
+            })
     }
 }
 
-fn part1(input: &str) -> color_eyre::Result<String> {
-    _both_parts(input, Crane::CrateMover9000)
-}
-
-fn part2(input: &str) -> color_eyre::Result<String> {
-    _both_parts(input, Crane::CrateMover9001)
+fn both_parts(
+    ship: &Ship,
+    instructions: &[Instruction],
+    crane: Crane,
+) -> color_eyre::Result<String> {
+    let mut ship = ship.clone();
+    ship.execute_instructions(instructions, crane)?;
+    ship.get_tops()
 }
 
-fn _both_parts(input: &str, crane: Crane) -> color_eyre::Result<String> {
-    let binding = input.replace("\r\n", "\n");
-    let (ship, instructions) = binding
-        .split_once("\n\n")
-        .ok_or_else(|| color_eyre::eyre::eyre!("Cannot split between stacks and instructions"))?;
-
-    let mut ship: Ship = ship.parse().unwrap();
-    let instructions: Vec<Instruction> = instructions
-        .lines()
-        .map(str::parse)
-        .collect::<color_eyre::Result<Vec<Instruction>>>()?;
-    ship.execute_instructions(&instructions, crane)?;
-    Ok(ship.get_tops())
-}
 #[cfg(test)]
 pub mod tests {
     use crate::util::read_example;
@@ -231,15 +285,66 @@ pub mod tests {
         assert_eq!(expected, Instruction::from_str(input).unwrap());
     }
 
+    #[test]
+    fn it_locates_parse_errors() {
+        let input = read_example("day05.txt").replace("move 3 from 1", "move three from 1");
+        let err = Day05::parse(&input)
+            .unwrap_err()
+            .downcast::<ParseError>()
+            .unwrap();
+        assert_eq!(
+            (7, 6, "move three from 1 to 3"),
+            (err.line, err.column, err.text.as_str())
+        );
+
+        let err = Ship::from_str("        [A]\n[B] [C]\n 1   2 ")
+            .unwrap_err()
+            .downcast::<ParseError>()
+            .unwrap();
+        assert_eq!((1, 10), (err.line, err.column));
+
+        for (instruction, column) in [("move 1 from 0 to 1", 13), ("move 1 from 1 to 4", 18)] {
+            let input = read_example("day05.txt").replace("move 1 from 2 to 1", instruction);
+            let err = Day05::parse(&input)
+                .unwrap_err()
+                .downcast::<ParseError>()
+                .unwrap();
+            assert_eq!((6, column), (err.line, err.column), "{instruction}");
+        }
+    }
+
+    #[test]
+    fn it_rejects_moving_more_crates_than_a_stack_holds() {
+        let input = read_example("day05.txt").replace("move 1 from 2 to 1", "move 4 from 2 to 1");
+        assert_eq!(
+            "Cannot pop from stack 2",
+            Day05::solve_part1(&input).unwrap_err().to_string()
+        );
+        let err = Day05::solve_part2(&input).unwrap_err();
+        assert_eq!(
+            "Cannot move 4 crates from stack 2, which holds 3",
+            err.to_string()
+        );
+        let input = read_example("day05.txt").replace("move 1 from 1 to 2", "move 2 from 1 to 2");
+        assert_eq!(
+            "Stack 1 is empty",
+            Day05::solve_part1(&input).unwrap_err().to_string()
+        );
+        assert_eq!(
+            "Stack 1 is empty",
+            Day05::solve_part2(&input).unwrap_err().to_string()
+        );
+    }
+
     #[test]
     fn it_solves_example1() {
         let input = read_example("day05.txt");
-        assert_eq!("CMZ".to_owned(), part1(&input).unwrap());
+        assert_eq!("CMZ".to_owned(), Day05::solve_part1(&input).unwrap());
     }
 
     #[test]
     fn it_solves_example2() {
         let input = read_example("day05.txt");
-        assert_eq!("MCD".to_owned(), part2(&input).unwrap());
+        assert_eq!("MCD".to_owned(), Day05::solve_part2(&input).unwrap());
     }
 }
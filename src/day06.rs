@@ -1,12 +1,32 @@
 use std::collections::HashSet;
 
-use crate::util::read_input;
+use crate::{solution::Solution, util::ParseError};
 
-pub fn solve() -> color_eyre::Result<()>{
-    let input = read_input("day06.txt");
-    println!("Day 06 part 1: {}", find_marker(&input, 4)?);
-    println!("Day 06 part 2: {}", find_marker(&input, 14)?);
-    Ok(())
+pub struct Day06;
+
+impl Solution for Day06 {
+    const DAY: u8 = 6;
+    type Input = String;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> color_eyre::Result<Self::Input> {
+        let stream = input.trim();
+        if let Some((i, c)) = stream.char_indices().find(|(_, c)| !c.is_ascii_lowercase()) {
+            return Err(
+                ParseError::at(input, &stream[i..], format!("Unexpected character '{c}'")).into(),
+            );
+        }
+        Ok(stream.to_owned())
+    }
+
+    fn part1(input: &Self::Input) -> color_eyre::Result<usize> {
+        find_marker(input, 4)
+    }
+
+    fn part2(input: &Self::Input) -> color_eyre::Result<usize> {
+        find_marker(input, 14)
+    }
 }
 
 fn find_marker(input: &str, length: usize) -> color_eyre::Result<usize> {
@@ -29,16 +49,40 @@ pub mod tests {
         assert_eq!(7, find_marker("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 4).unwrap());
         assert_eq!(5, find_marker("bvwbjplbgvbhsrlpgdmjqwftvncz", 4).unwrap());
         assert_eq!(6, find_marker("nppdvjthqldpwncqszvftbrmjlhg", 4).unwrap());
-        assert_eq!(10, find_marker("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 4).unwrap());
-        assert_eq!(11, find_marker("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 4).unwrap());
+        assert_eq!(
+            10,
+            find_marker("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 4).unwrap()
+        );
+        assert_eq!(
+            11,
+            find_marker("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 4).unwrap()
+        );
+    }
+
+    #[test]
+    fn it_rejects_non_letters() {
+        let err = Day06::parse("mjqjp\nqmgbl")
+            .unwrap_err()
+            .downcast::<ParseError>()
+            .unwrap();
+        assert_eq!((1, 6), (err.line, err.column));
     }
 
     #[test]
     fn it_finds_long_marker() {
-        assert_eq!(19, find_marker("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 14).unwrap());
+        assert_eq!(
+            19,
+            find_marker("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 14).unwrap()
+        );
         assert_eq!(23, find_marker("bvwbjplbgvbhsrlpgdmjqwftvncz", 14).unwrap());
         assert_eq!(23, find_marker("nppdvjthqldpwncqszvftbrmjlhg", 14).unwrap());
-        assert_eq!(29, find_marker("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 14).unwrap());
-        assert_eq!(26, find_marker("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 14).unwrap());
+        assert_eq!(
+            29,
+            find_marker("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 14).unwrap()
+        );
+        assert_eq!(
+            26,
+            find_marker("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 14).unwrap()
+        );
     }
-}
\ No newline at end of file
+}
@@ -1,31 +1,42 @@
-use crate::util::read_input;
+use crate::{solution::Solution, util::ParseError};
+
 const FILE_SYSTEM_SIZE: usize = 70_000_000;
 const REQUIRED_FREE_SPACE: usize = 30_000_000;
 
-pub fn solve() -> color_eyre::Result<()> {
-    let input_raw = read_input("day07.txt");
-    println!("Day 07 part 1: {}", part1(&input_raw)?);
-    println!("Day 07 part 2: {}", part2(&input_raw)?);
-    Ok(())
-}
+pub struct Day07;
 
-fn part1(input_raw: &str) -> color_eyre::Result<usize> {
-    let mut root = Folder::new("/");
-    let input: Vec<&str> = input_raw.lines().collect();
-    root.parse(&input, &mut 0)?;
-    Ok(root.get_sizes_of_folders_smaller_than(100_000))
-}
+impl Solution for Day07 {
+    const DAY: u8 = 7;
+    type Input = Folder;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input_raw: &str) -> color_eyre::Result<Self::Input> {
+        let mut root = Folder::new("/");
+        let input: Vec<&str> = input_raw.lines().collect();
+        root.parse(&input, &mut 0)?;
+        Ok(root)
+    }
+
+    fn part1(root: &Self::Input) -> color_eyre::Result<usize> {
+        Ok(root.get_sizes_of_folders_smaller_than(100_000))
+    }
 
-fn part2(input_raw: &str) -> color_eyre::Result<usize> {
-    let mut root = Folder::new("/");
-    let input: Vec<&str> = input_raw.lines().collect();
-    root.parse(&input, &mut 0)?;
-    root.get_folders_larger_than(REQUIRED_FREE_SPACE - (FILE_SYSTEM_SIZE - root.size())).iter().min().ok_or_else(|| color_eyre::eyre::eyre!("no folder found")).copied()
+    fn part2(root: &Self::Input) -> color_eyre::Result<usize> {
+        root.get_folders_larger_than(REQUIRED_FREE_SPACE - (FILE_SYSTEM_SIZE - root.size()))
+            .iter()
+            .min()
+            .ok_or_else(|| color_eyre::eyre::eyre!("no folder found"))
+            .copied()
+    }
 }
 
 pub trait FileOrFolder {
     fn size(&self) -> usize;
     fn name(&self) -> &str;
+    /// Parses the terminal output from line `index` on, and moves `index` past what was parsed
+    /// # Errors
+    /// If a line is not a known command or its output, as a [`crate::util::ParseError`]
     fn parse(&mut self, input: &[&str], index: &mut usize) -> color_eyre::Result<()>;
     fn is_folder(&self) -> bool;
     fn get_sizes_of_folders_smaller_than(&self, max: usize) -> usize;
@@ -70,6 +81,7 @@ pub struct Folder {
 }
 
 impl Folder {
+    #[must_use]
     pub fn new(name: &str) -> Self {
         Self {
             name: name.to_string(),
@@ -108,13 +120,25 @@ impl FileOrFolder for Folder {
                             return Ok(());
                         }
                     }
-                    foldername => match self.children.iter_mut().find(|c| c.name() == foldername) {
-                        Some(folder) => {
-                            *index += 1;
-                            folder.parse(input, index)?;
-                        }
-                        None => unreachable!(),
-                    },
+                    foldername => {
+                        let Some(folder) = self
+                            .children
+                            .iter_mut()
+                            .find(|c| c.is_folder() && c.name() == foldername)
+                        else {
+                            let line = input[*index];
+                            let column = line.len() - foldername.len() + 1;
+                            return Err(ParseError::new(
+                                *index + 1,
+                                column,
+                                line,
+                                "Unknown folder",
+                            )
+                            .into());
+                        };
+                        *index += 1;
+                        folder.parse(input, index)?;
+                    }
                 },
                 "ls" => {
                     *index += 1;
@@ -124,23 +148,31 @@ impl FileOrFolder for Folder {
                                 self.children.push(Box::new(Self::new(dirname)));
                             }
                             Some((filesize, filename)) => {
+                                let size = filesize.parse().map_err(|err| {
+                                    ParseError::new(*index + 1, 1, input[*index], err)
+                                })?;
                                 self.children.push(Box::new(File {
                                     name: filename.to_string(),
-                                    size: filesize.parse()?,
+                                    size,
                                 }));
                             }
                             None => {
-                                return Err(color_eyre::eyre::eyre!(
-                                    "Invalid ls return: {}",
-                                    input[*index]
-                                ))
+                                return Err(ParseError::new(
+                                    *index + 1,
+                                    1,
+                                    input[*index],
+                                    "Invalid ls return",
+                                )
+                                .into())
                             }
                         }
                         *index += 1;
                     }
                 }
                 _ => {
-                    return Err(color_eyre::eyre::eyre!("Invalid command: {command}"));
+                    let line = input[*index];
+                    let column = line.len() - cmd.len() + 1;
+                    return Err(ParseError::new(*index + 1, column, line, "Invalid command").into());
                 }
             }
         }
@@ -189,6 +221,27 @@ pub mod tests {
         Ok(())
     }
 
+    #[test]
+    fn it_locates_parse_errors() {
+        let input = read_example("day07.txt");
+        let err = |input: &str| {
+            Day07::parse(input)
+                .err()
+                .unwrap()
+                .downcast::<ParseError>()
+                .unwrap()
+        };
+        assert_eq!(
+            ParseError::new(7, 6, "$ cd b.txt", "Unknown folder"),
+            err(&input.replace("cd a", "cd b.txt"))
+        );
+        assert_eq!(5, err(&input.replace("8504156", "85O4156")).line);
+        assert_eq!(
+            ParseError::new(2, 3, "$ dir", "Invalid command"),
+            err(&input.replacen("ls", "dir", 1))
+        );
+    }
+
     #[test]
     fn it_gets_sizes() -> color_eyre::Result<()> {
         let mut root = Folder::new("/");
@@ -215,9 +268,14 @@ pub mod tests {
         let input_raw = read_example("day07.txt");
         let input: Vec<&str> = input_raw.lines().collect();
         root.parse(&input, &mut 0)?;
-        assert_eq!(24_933_642, *root.get_folders_larger_than(REQUIRED_FREE_SPACE - (FILE_SYSTEM_SIZE - root.size())).iter().min().unwrap());
+        assert_eq!(
+            24_933_642,
+            *root
+                .get_folders_larger_than(REQUIRED_FREE_SPACE - (FILE_SYSTEM_SIZE - root.size()))
+                .iter()
+                .min()
+                .unwrap()
+        );
         Ok(())
     }
-
-
 }
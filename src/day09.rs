@@ -2,37 +2,38 @@ use std::{collections::HashSet, str::FromStr};
 
 use color_eyre::eyre;
 
-use crate::util::{Direction, read_input};
+use crate::{
+    solution::Solution,
+    util::{parse, Compass, Direction, ParseError, Vec2},
+};
 
-pub fn solve() -> color_eyre::Result<()>{
-    let input = read_input("day09.txt");
-    println!("Day 09 part 1: {}", part1(&input)?);
-    println!("Day 09 part 1: {}", part2(&input)?);
-    Ok(())
-}
+pub struct Day09;
 
-fn part1(input: &str) -> color_eyre::Result<usize> {
-    let mut area = Area::new(2);
-    let instructions: Vec<Instruction> = input
-            .lines()
-            .map(str::parse)
-            .collect::<color_eyre::Result<Vec<_>>>()?;
-        for instruction in &instructions {
-            area.apply(instruction);
-        }
-    Ok(area.tail_visited.len())
+impl Solution for Day09 {
+    const DAY: u8 = 9;
+    type Input = Vec<Instruction>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> color_eyre::Result<Self::Input> {
+        Ok(parse::lines(input, str::parse)?)
+    }
+
+    fn part1(instructions: &Self::Input) -> color_eyre::Result<usize> {
+        Ok(count_tail_positions(instructions, 2))
+    }
+
+    fn part2(instructions: &Self::Input) -> color_eyre::Result<usize> {
+        Ok(count_tail_positions(instructions, 10))
+    }
 }
 
-fn part2(input: &str) -> color_eyre::Result<usize> {
-    let mut area = Area::new(10);
-    let instructions: Vec<Instruction> = input
-            .lines()
-            .map(str::parse)
-            .collect::<color_eyre::Result<Vec<_>>>()?;
-        for instruction in &instructions {
-            area.apply(instruction);
-        }
-    Ok(area.tail_visited.len())
+fn count_tail_positions(instructions: &[Instruction], knots: usize) -> usize {
+    let mut area = Area::new(knots);
+    for instruction in instructions {
+        area.apply(instruction);
+    }
+    area.tail_visited.len()
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -47,9 +48,13 @@ impl FromStr for Instruction {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let (direction, distance) = s
             .split_once(' ')
-            .ok_or_else(|| eyre::eyre!("Cannot parse instruction '{s}"))?;
-        let direction = direction.parse()?;
-        let distance = distance.parse()?;
+            .ok_or_else(|| ParseError::at(s, s, format!("Cannot parse instruction '{s}'")))?;
+        let direction = direction
+            .parse()
+            .map_err(|err: eyre::Report| ParseError::at(s, direction, format!("{err:#}")))?;
+        let distance = distance
+            .parse()
+            .map_err(|err| ParseError::at(s, distance, err))?;
         Ok(Self {
             direction,
             distance,
@@ -57,11 +62,7 @@ impl FromStr for Instruction {
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Debug)]
-pub struct Point {
-    x: isize,
-    y: isize,
-}
+pub type Point = Vec2<isize>;
 
 #[derive(Debug)]
 pub struct Area {
@@ -70,6 +71,7 @@ pub struct Area {
 }
 
 impl Area {
+    #[must_use]
     pub fn new(knots: usize) -> Self {
         let mut visited = HashSet::new();
         visited.insert(Point::default());
@@ -86,36 +88,25 @@ impl Area {
     }
 
     fn move_head(&mut self, direction: Direction) {
-        match direction {
-            Direction::Up => self.knots[0].y += 1,
-            Direction::Right => self.knots[0].x += 1,
-            Direction::Down => self.knots[0].y -= 1,
-            Direction::Left => self.knots[0].x -= 1,
-        }
+        self.knots[0] += direction.into();
         self.follow_tail();
     }
 
     fn follow_tail(&mut self) {
         for i in 1..self.knots.len() {
-            if self.knots[i-1].x.abs_diff(self.knots[i].x) <= 1 && self.knots[i-1].y.abs_diff(self.knots[i].y) <= 1 {
+            if self.knots[i - 1].chebyshev_distance(self.knots[i]) <= 1 {
                 // They are touching
                 return;
             }
-    
-            if self.knots[i-1].x == self.knots[i].x {
-                // same column
-                self.knots[i].y += (self.knots[i-1].y - self.knots[i].y).signum();
-            } else if self.knots[i-1].y == self.knots[i].y {
-                // same row
-                self.knots[i].x += (self.knots[i-1].x - self.knots[i].x).signum();
-            } else {
-                // diagonal
-                self.knots[i].y += (self.knots[i-1].y - self.knots[i].y).signum();
-                self.knots[i].x += (self.knots[i-1].x - self.knots[i].x).signum();
-            }
+
+            // move straight or diagonally towards the previous knot
+            let delta = self.knots[i - 1] - self.knots[i];
+            let direction = Compass::towards(delta.into()).expect("The knots are not touching");
+            self.knots[i] += direction.into();
         }
-        
-        self.tail_visited.insert(*self.knots.last().expect("There must be at least 1 knot"));
+
+        self.tail_visited
+            .insert(*self.knots.last().expect("There must be at least 1 knot"));
     }
 }
 
@@ -127,14 +118,14 @@ pub mod tests {
     #[test]
     fn it_follows_length2() -> color_eyre::Result<()> {
         let input = read_example("day09.txt");
-        assert_eq!(13, part1(&input)?);
+        assert_eq!(13, Day09::solve_part1(&input)?);
         Ok(())
     }
 
     #[test]
     fn it_follows_length10() -> color_eyre::Result<()> {
-        assert_eq!(1, part2(&read_example("day09.txt"))?);
-        assert_eq!(36, part2(&read_example("day09_2.txt"))?);
+        assert_eq!(1, Day09::solve_part2(&read_example("day09.txt"))?);
+        assert_eq!(36, Day09::solve_part2(&read_example("day09_2.txt"))?);
         Ok(())
     }
 }
@@ -1,95 +1,198 @@
-use std::str::FromStr;
+use std::{collections::BTreeSet, fmt::Display, str::FromStr};
 
 use itertools::Itertools;
 
-use crate::util::read_input;
+use crate::{
+    solution::Solution,
+    util::{ocr, parse, Grid, ParseError},
+};
 
-const SIGNAL_OFFSET: usize = 20;
-const SIGNAL_INTERVAL: usize = 40;
-const SCREEN_WIDTH: usize = 40;
+pub mod asm;
+pub mod cpu;
 
-pub fn solve() -> color_eyre::Result<()> {
-    let input = read_input("day10.txt");
-    println!("Day 10 part 1: {}", part1(&input)?);
-    println!("Day 10 part 2:\n{}", part2(&input)?);
-    Ok(())
-}
+use cpu::{Cpu, Observer, Operation, Registers, State};
+
+pub struct Day10;
 
-fn part1(input: &str) -> color_eyre::Result<isize> {
-    let mut video_system = VideoSystem::default();
-    let instructions: Vec<Instruction> = input.lines().map(str::parse).collect::<color_eyre::Result<Vec<_>>>()?;
-    for instruction in &instructions {
-        video_system.apply(instruction);
+impl Solution for Day10 {
+    const DAY: u8 = 10;
+    type Input = Vec<Instruction>;
+    type Answer1 = isize;
+    type Answer2 = ocr::Text;
+
+    fn parse(input: &str) -> color_eyre::Result<Self::Input> {
+        Ok(parse::lines(input, str::parse)?)
     }
-    Ok(video_system.signal_strengths.iter().sum())
-}
 
-fn part2(input: &str) -> color_eyre::Result<String> {
-    let mut video_system = VideoSystem::default();
-    let instructions: Vec<Instruction> = input.lines().map(str::parse).collect::<color_eyre::Result<Vec<_>>>()?;
-    for instruction in &instructions {
-        video_system.apply(instruction);
+    fn part1(instructions: &Self::Input) -> color_eyre::Result<isize> {
+        Ok(VideoSystem::run(instructions).signal.strengths.iter().sum())
+    }
+
+    fn part2(instructions: &Self::Input) -> color_eyre::Result<ocr::Text> {
+        Ok(ocr::read(&VideoSystem::run(instructions).draw())?)
     }
-    Ok(video_system.draw())
 }
 
-#[derive(Debug, Clone)]
+/// The CPU with a [`SignalSampler`] and a [`Crt`] attached
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct VideoSystem {
-    signal_strengths: Vec<isize>,
-    cycle: usize,
-    register: isize,
-    screen: Vec<u64>
+    pub signal: SignalSampler,
+    pub crt: Crt,
 }
 
 impl Default for VideoSystem {
+    /// The device of the puzzle: a 40×6 screen, a sprite 3 pixels wide and the signal sampled
+    /// during cycles 20, 60, …, 220
     fn default() -> Self {
-        Self { signal_strengths: Vec::default(), cycle: Default::default(), register: 1, screen: Vec::default() }
+        Self::new(40, 6, 3, (20..=220).step_by(40))
     }
 }
 
 impl VideoSystem {
-    fn apply(&mut self, instruction: &Instruction) {
-        let old_cycle = self.cycle;
-        self.cycle += instruction.cost();
-        for cycle in (old_cycle+1)..=self.cycle {
-            if cycle % SIGNAL_INTERVAL == SIGNAL_OFFSET {
-                #[allow(clippy::cast_possible_wrap)]
-                self.signal_strengths.push(cycle as isize * self.register);
-            }
-
-            let screen_position = (cycle - 1) % SCREEN_WIDTH;
-            if screen_position == 0 {
-                self.screen.push(u64::MAX);
-            }
-            if self.register.abs_diff(screen_position.try_into().expect("Cannot fail since screen position is between 0 and 39 thanks to modulo")) > 1{
-                *self.screen.last_mut().expect("Screen row not initialized") &= !(1 << screen_position);
-            }
+    #[must_use]
+    pub fn new(
+        width: usize,
+        height: usize,
+        sprite_width: usize,
+        sampling: impl IntoIterator<Item = usize>,
+    ) -> Self {
+        Self {
+            signal: SignalSampler::new(sampling),
+            crt: Crt::new(width, height, sprite_width),
         }
-        if let Instruction::Addx(value) = instruction {
-            self.register += value;
+    }
+
+    /// Runs the instructions on the device of the puzzle
+    #[must_use]
+    pub fn run(instructions: &[Instruction]) -> Self {
+        Self::default().execute(instructions)
+    }
+
+    /// Runs the instructions on this device
+    #[must_use]
+    pub fn execute(self, instructions: &[Instruction]) -> Self {
+        let (signal, crt) = Cpu::new(instructions, (self.signal, self.crt)).run();
+        Self { signal, crt }
+    }
+
+    #[must_use]
+    pub fn draw(&self) -> String {
+        self.crt.draw()
+    }
+}
+
+/// Records the signal strength (cycle times `x`) during the given cycles
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignalSampler {
+    cycles: BTreeSet<usize>,
+    pub strengths: Vec<isize>,
+}
+
+impl SignalSampler {
+    #[must_use]
+    pub fn new(cycles: impl IntoIterator<Item = usize>) -> Self {
+        Self {
+            cycles: cycles.into_iter().collect(),
+            strengths: Vec::new(),
         }
-        
     }
+}
 
-    fn draw(&self) -> String {
-        self.screen.iter().map(|row| format!("{row:b}").replace('1', "#").replace('0', ".")[24..].chars().rev().collect::<String>()).join("\n")
+impl Observer for SignalSampler {
+    fn observe(&mut self, state: &State) {
+        if self.cycles.contains(&state.cycle) {
+            #[allow(clippy::cast_possible_wrap)]
+            self.strengths
+                .push(state.cycle as isize * state.registers.x);
+        }
     }
 }
 
-#[derive(Clone, Copy, Debug, Default)]
+/// Draws a pixel per cycle, row by row, which is lit if the sprite centered on `x` covers it.
+/// After the last pixel, it starts over in the top left corner.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Crt {
+    screen: Grid<bool>,
+    sprite_width: usize,
+}
+
+impl Crt {
+    #[must_use]
+    pub fn new(width: usize, height: usize, sprite_width: usize) -> Self {
+        Self {
+            screen: Grid::new(width, height, false),
+            sprite_width,
+        }
+    }
+
+    #[must_use]
+    pub const fn screen(&self) -> &Grid<bool> {
+        &self.screen
+    }
+
+    #[must_use]
+    pub fn draw(&self) -> String {
+        self.screen
+            .rows()
+            .map(|row| {
+                row.iter()
+                    .map(|&lit| if lit { '#' } else { '.' })
+                    .collect::<String>()
+            })
+            .join("\n")
+    }
+}
+
+impl Observer for Crt {
+    fn observe(&mut self, state: &State) {
+        let (width, height) = (self.screen.width(), self.screen.height());
+        if width * height == 0 {
+            return;
+        }
+        let position = (state.cycle - 1) % (width * height);
+        let (x, y) = (position % width, position / width);
+        self.screen[(x, y)] = sprite_covers(self.sprite_width, state.registers.x, x);
+    }
+}
+
+/// Whether the sprite centered on `x` covers the column
+#[must_use]
+pub fn sprite_covers(sprite_width: usize, x: isize, column: usize) -> bool {
+    // how far the column is from the left edge of the sprite
+    #[allow(clippy::cast_possible_wrap)]
+    let offset = column as isize - x + (sprite_width.saturating_sub(1) / 2) as isize;
+    usize::try_from(offset).is_ok_and(|offset| offset < sprite_width)
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub enum Instruction {
     Addx(isize),
     #[default]
-    Noop
+    Noop,
 }
 
-impl Instruction {
-    pub const fn cost(&self) -> usize {
+impl Operation for Instruction {
+    fn cycles(&self) -> usize {
         match self {
             Self::Addx(_) => 2,
             Self::Noop => 1,
         }
     }
+
+    fn execute(&self, registers: &mut Registers) {
+        if let Self::Addx(value) = self {
+            registers.x += value;
+        }
+    }
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Addx(value) => write!(f, "addx {value}"),
+            Self::Noop => write!(f, "noop"),
+        }
+    }
 }
 
 impl FromStr for Instruction {
@@ -99,9 +202,11 @@ impl FromStr for Instruction {
         if s == "noop" {
             Ok(Self::Noop)
         } else if let Some((_, value)) = s.split_once(' ') {
-            Ok(Self::Addx(value.parse()?))
+            Ok(Self::Addx(
+                value.parse().map_err(|err| ParseError::at(s, value, err))?,
+            ))
         } else {
-            Err(color_eyre::eyre::eyre!("Cannot parse instruction '{s}"))
+            Err(ParseError::at(s, s, format!("Cannot parse instruction '{s}'")).into())
         }
     }
 }
@@ -114,28 +219,67 @@ pub mod tests {
     #[test]
     fn it_applies_instructions() {
         let input = read_example("day10.txt");
-        let mut video_system = VideoSystem::default();
-        let instructions: Vec<Instruction> = input.lines().map(str::parse).collect::<color_eyre::Result<Vec<_>>>().unwrap();
-        video_system.apply(&instructions[0]);
-        assert_eq!(1, video_system.register);
-        assert_eq!(1, video_system.cycle);
-        video_system.apply(&instructions[1]);
-        assert_eq!(4, video_system.register);
-        assert_eq!(3, video_system.cycle);
-        video_system.apply(&instructions[2]);
-        assert_eq!(-1, video_system.register);
-        assert_eq!(5, video_system.cycle);
+        let instructions: Vec<Instruction> = input
+            .lines()
+            .map(str::parse)
+            .collect::<color_eyre::Result<Vec<_>>>()
+            .unwrap();
+        let mut cpu = Cpu::new(&instructions, ());
+        cpu.step_instruction();
+        assert_eq!(1, cpu.inspect().registers.x);
+        assert_eq!(2, cpu.inspect().cycle);
+        cpu.step_instruction();
+        assert_eq!(4, cpu.inspect().registers.x);
+        assert_eq!(4, cpu.inspect().cycle);
+        cpu.step_instruction();
+        assert_eq!(-1, cpu.inspect().registers.x);
+        assert_eq!(6, cpu.inspect().cycle);
+        assert!(cpu.is_halted());
+    }
+
+    #[test]
+    fn it_samples_the_signal() {
+        let instructions = Day10::parse(&read_example("day10_2.txt")).unwrap();
+        let mut cpu = Cpu::new(&instructions, VideoSystem::default().signal);
+        cpu.add_breakpoint(cpu::Breakpoint::Cycle(60));
+        cpu.resume();
+        assert_eq!(19, cpu.inspect().registers.x);
+        assert_eq!(vec![420, 1140, 1800, 2940, 2880, 3960], cpu.run().strengths);
     }
 
     #[test]
     fn it_solves_part1() {
         let input = read_example("day10_2.txt");
-        assert_eq!(13_140, part1(&input).unwrap());
+        assert_eq!(13_140, Day10::solve_part1(&input).unwrap());
+    }
+
+    #[test]
+    fn it_uses_any_geometry() {
+        let video_system = VideoSystem::new(100, 2, 5, [3, 150]).execute(&[Instruction::Noop; 150]);
+        let row = format!("####{}", ".".repeat(96));
+        assert_eq!(format!("{row}\n{row}"), video_system.draw());
+        assert_eq!(vec![3, 150], video_system.signal.strengths);
+        let video_system = VideoSystem::new(4, 1, 1, []).execute(&[
+            Instruction::Addx(2),
+            Instruction::Noop,
+            Instruction::Noop,
+        ]);
+        assert_eq!(".#.#", video_system.draw());
     }
 
     #[test]
     fn it_draws_example() {
-        let input = read_example("day10_2.txt");
-        assert_eq!(read_example("day10_expected.txt").replace("\r\n", "\n"),part2(&input).unwrap());
+        let instructions = Day10::parse(&read_example("day10_2.txt")).unwrap();
+        assert_eq!(
+            read_example("day10_expected.txt").replace("\r\n", "\n"),
+            VideoSystem::run(&instructions).draw()
+        );
+        // the example draws stripes instead of letters
+        let err = Day10::part2(&instructions).unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("Unrecognized glyphs at positions 0, 1, 2"),
+            "{err}"
+        );
     }
-}
\ No newline at end of file
+}
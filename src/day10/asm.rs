@@ -0,0 +1,381 @@
+//! Assembling programs for the handheld device, and writing programs that draw a given image
+//!
+//! Besides `addx V` and `noop`, the assembler understands
+//! - comments from `;` to the end of the line
+//! - labels (`name:`) naming the index of the next instruction, e.g. for [`super::cpu::Breakpoint`]
+//! - `wait N`, expanding to `N` times `noop`
+//! - `setx V`, an `addx` setting `x` to `V` (which is known, as programs cannot jump)
+//! - macros defined by `.macro name param...` up to `.endm`, with `{param}` replaced by the arguments
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Write,
+};
+
+use color_eyre::eyre::eyre;
+
+use super::{sprite_covers, Instruction};
+use crate::util::ParseError;
+
+/// How deep macros may expand into other macros, which stops recursive macros
+const MAX_MACRO_DEPTH: usize = 16;
+
+/// An assembled program
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Program {
+    pub instructions: Vec<Instruction>,
+    labels: HashMap<String, usize>,
+}
+
+impl Program {
+    /// The index of the instruction following the label
+    #[must_use]
+    pub fn label(&self, name: &str) -> Option<usize> {
+        self.labels.get(name).copied()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Macro {
+    params: Vec<String>,
+    body: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+struct Assembler {
+    program: Program,
+    /// `x` after the instructions assembled so far
+    x: isize,
+    macros: HashMap<String, Macro>,
+}
+
+/// Assembles a program, see the [module](self) for the syntax
+/// # Errors
+/// If a line cannot be assembled, located at the line (or the macro invocation) that failed
+pub fn assemble(source: &str) -> Result<Program, ParseError> {
+    let mut assembler = Assembler {
+        x: 1,
+        ..Assembler::default()
+    };
+    let mut definition: Option<(usize, &str, String, Macro)> = None;
+    for (i, text) in source.lines().enumerate() {
+        let line = text.split(';').next().unwrap_or_default();
+        let error = |token: &str, message: &str| ParseError::at(text, token, message).offset(i);
+        let mut tokens = line.split_whitespace();
+        let Some(first) = tokens.next() else { continue };
+        if let Some((start, start_text, name, mut body)) = definition.take() {
+            if first == ".endm" {
+                assembler.macros.insert(name, body);
+            } else {
+                body.body.push(line.to_owned());
+                definition = Some((start, start_text, name, body));
+            }
+            continue;
+        }
+        match first {
+            ".macro" => {
+                let name = tokens
+                    .next()
+                    .ok_or_else(|| error(first, "Missing name of the macro"))?;
+                if is_builtin(name) || !is_identifier(name) {
+                    return Err(error(name, "Invalid name for a macro"));
+                }
+                let params = tokens.map(str::to_owned).collect();
+                definition = Some((
+                    i,
+                    text,
+                    name.to_owned(),
+                    Macro {
+                        params,
+                        body: Vec::new(),
+                    },
+                ));
+            }
+            ".endm" => return Err(error(first, "'.endm' without '.macro'")),
+            _ => assembler
+                .line(line, 0)
+                .map_err(|(token, message)| error(token, &message))?,
+        }
+    }
+    if let Some((i, text, name, _)) = definition {
+        return Err(ParseError::new(
+            i + 1,
+            1,
+            text,
+            format!("Macro '{name}' lacks '.endm'"),
+        ));
+    }
+    Ok(assembler.program)
+}
+
+/// What went wrong, and the token of the line it went wrong at
+type LineError<'a> = (&'a str, String);
+
+impl Assembler {
+    fn line<'a>(&mut self, line: &'a str, depth: usize) -> Result<(), LineError<'a>> {
+        let mut tokens = line.split_whitespace().peekable();
+        if let Some(label) = tokens.peek().and_then(|token| token.strip_suffix(':')) {
+            let token = tokens.next().unwrap_or_default();
+            if !is_identifier(label) {
+                return Err((token, format!("Invalid label '{label}'")));
+            }
+            let index = self.program.instructions.len();
+            if self
+                .program
+                .labels
+                .insert(label.to_owned(), index)
+                .is_some()
+            {
+                return Err((token, format!("Label '{label}' is defined twice")));
+            }
+        }
+        let Some(mnemonic) = tokens.next() else {
+            return Ok(());
+        };
+        let args: Vec<&str> = tokens.collect();
+        let arg = |index: usize| -> Result<isize, LineError<'a>> {
+            let arg = args[index];
+            arg.parse()
+                .map_err(|err| (arg, format!("Invalid number '{arg}': {err}")))
+        };
+        let expected = match mnemonic {
+            "noop" => 0,
+            "addx" | "wait" | "setx" => 1,
+            name => self.macros.get(name).map_or(usize::MAX, |m| m.params.len()),
+        };
+        if expected == usize::MAX {
+            return Err((mnemonic, format!("Unknown instruction '{mnemonic}'")));
+        }
+        if args.len() != expected {
+            return Err((
+                mnemonic,
+                format!(
+                    "'{mnemonic}' takes {expected} arguments, got {}",
+                    args.len()
+                ),
+            ));
+        }
+        match mnemonic {
+            "noop" => self.emit(Instruction::Noop),
+            "addx" => self.emit(Instruction::Addx(arg(0)?)),
+            "setx" => self.emit(Instruction::Addx(arg(0)? - self.x)),
+            "wait" => {
+                let count = usize::try_from(arg(0)?).map_err(|_| {
+                    (
+                        args[0],
+                        "Cannot wait a negative number of cycles".to_owned(),
+                    )
+                })?;
+                for _ in 0..count {
+                    self.emit(Instruction::Noop);
+                }
+            }
+            name => self
+                .expand(name, &args, depth)
+                .map_err(|message| (mnemonic, message))?,
+        }
+        Ok(())
+    }
+
+    fn expand(&mut self, name: &str, args: &[&str], depth: usize) -> Result<(), String> {
+        if depth >= MAX_MACRO_DEPTH {
+            return Err(format!(
+                "Macro '{name}' expands too deeply (is it recursive?)"
+            ));
+        }
+        let Macro { params, body } = self.macros[name].clone();
+        for line in body {
+            let line = params.iter().zip(args).fold(line, |line, (param, arg)| {
+                line.replace(&format!("{{{param}}}"), arg)
+            });
+            self.line(&line, depth + 1)
+                .map_err(|(_, message)| format!("In macro '{name}': {message}"))?;
+        }
+        Ok(())
+    }
+
+    fn emit(&mut self, instruction: Instruction) {
+        if let Instruction::Addx(value) = instruction {
+            self.x += value;
+        }
+        self.program.instructions.push(instruction);
+    }
+}
+
+fn is_builtin(name: &str) -> bool {
+    matches!(name, "noop" | "addx" | "wait" | "setx")
+}
+
+fn is_identifier(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// The instructions, one per line, as [`assemble`] reads them
+#[must_use]
+pub fn disassemble(instructions: &[Instruction]) -> String {
+    instructions
+        .iter()
+        .fold(String::new(), |mut source, instruction| {
+            writeln!(source, "{instruction}").unwrap();
+            source
+        })
+}
+
+/// A program drawing `image` (rows of `#` and `.`, as wide as the screen) with a sprite
+/// `sprite_width` pixels wide, running for exactly one cycle per pixel
+/// # Errors
+/// If the image cannot be drawn, as `x` starts at 1 and only changes every other cycle
+pub fn synthesize(image: &str, sprite_width: usize) -> color_eyre::Result<Vec<Instruction>> {
+    let rows: Vec<&str> = image.lines().collect();
+    let width = rows.first().map_or(0, |row| row.len());
+    if rows.iter().any(|row| row.len() != width) {
+        return Err(eyre!("The rows of the image differ in width"));
+    }
+    let pixels: Vec<bool> = rows
+        .iter()
+        .flat_map(|row| row.chars().map(|c| c == '#'))
+        .collect();
+    let fits =
+        |pixel: usize, x: isize| sprite_covers(sprite_width, x, pixel % width) == pixels[pixel];
+    let margin = isize::try_from(width + sprite_width).unwrap_or(isize::MAX);
+    // `reached[p]` maps every `x` that can be set when pixel `p` is drawn to how it was set
+    let mut reached: Vec<BTreeMap<isize, (isize, Instruction)>> =
+        vec![BTreeMap::new(); pixels.len() + 1];
+    reached[0].insert(1, (1, Instruction::Noop));
+    for pixel in 0..pixels.len() {
+        let candidates: Vec<isize> = reached[pixel]
+            .keys()
+            .copied()
+            .filter(|&x| fits(pixel, x))
+            .collect();
+        for x in candidates {
+            reached[pixel + 1]
+                .entry(x)
+                .or_insert((x, Instruction::Noop));
+            if pixel + 1 < pixels.len() && fits(pixel + 1, x) {
+                for next in -margin..=margin {
+                    reached[pixel + 2]
+                        .entry(next)
+                        .or_insert((x, Instruction::Addx(next - x)));
+                }
+            }
+        }
+    }
+    let Some(&(mut x)) = reached[pixels.len()].keys().next() else {
+        let drawn = (0..pixels.len())
+            .rev()
+            .find(|&pixel| reached[pixel].keys().any(|&x| fits(pixel, x)));
+        let pixel = drawn.map_or(0, |pixel| pixel + 1);
+        return Err(eyre!(
+            "Pixel ({}, {}) cannot be drawn with a sprite {sprite_width} pixels wide",
+            pixel % width,
+            pixel / width
+        ));
+    };
+    let mut pixel = pixels.len();
+    let mut instructions = Vec::new();
+    while pixel > 0 {
+        let (previous, instruction) = reached[pixel][&x];
+        pixel -= match instruction {
+            Instruction::Addx(_) => 2,
+            Instruction::Noop => 1,
+        };
+        instructions.push(instruction);
+        x = previous;
+    }
+    instructions.reverse();
+    Ok(instructions)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{day10::VideoSystem, util::ocr};
+
+    const SOURCE: &str = "\
+; blinks a pixel
+.macro blink at times
+    setx {at}
+    wait {times}
+.endm
+
+start:  addx 3   ; x is 4 now
+        blink 10 2
+end:
+        noop";
+
+    #[test]
+    fn it_assembles() {
+        let program = assemble(SOURCE).unwrap();
+        assert_eq!(
+            vec![
+                Instruction::Addx(3),
+                Instruction::Addx(6),
+                Instruction::Noop,
+                Instruction::Noop,
+                Instruction::Noop
+            ],
+            program.instructions
+        );
+        assert_eq!(
+            (Some(0), Some(4), None),
+            (
+                program.label("start"),
+                program.label("end"),
+                program.label("blink")
+            )
+        );
+        assert_eq!(
+            program.instructions,
+            assemble(&disassemble(&program.instructions))
+                .unwrap()
+                .instructions
+        );
+    }
+
+    #[test]
+    fn it_locates_errors() {
+        let err = assemble("noop\n  addx one").unwrap_err();
+        assert_eq!((2, 8), (err.line, err.column));
+        let err = assemble("a: noop\na: noop").unwrap_err();
+        assert_eq!(
+            (2, 1, "Label 'a' is defined twice"),
+            (err.line, err.column, err.message.as_str())
+        );
+        let err = assemble(".macro twice\n  jump\n.endm\nnoop\ntwice").unwrap_err();
+        assert_eq!(
+            (5, 1, "In macro 'twice': Unknown instruction 'jump'"),
+            (err.line, err.column, err.message.as_str())
+        );
+        assert!(assemble(".macro loop\nloop\n.endm\nloop")
+            .unwrap_err()
+            .message
+            .contains("too deeply"));
+        assert!(assemble(".macro open\nnoop").is_err());
+        assert!(assemble("wait 1 2").is_err());
+    }
+
+    #[test]
+    fn it_synthesizes_programs() {
+        let image = ocr::SMALL.render("EFHKLPRU").unwrap();
+        let program = synthesize(&image, 3).unwrap();
+        let video_system = VideoSystem::default().execute(&program);
+        assert_eq!(image, video_system.draw());
+        assert_eq!("EFHKLPRU", ocr::read(&video_system.draw()).unwrap().text);
+
+        let image = ".#....##..##.\n.....#...##..";
+        let program = synthesize(image, 1).unwrap();
+        assert_eq!(
+            image,
+            VideoSystem::new(13, 2, 1, []).execute(&program).draw()
+        );
+        // the sprite starts on the first pixel
+        let err = synthesize(&ocr::SMALL.render("A").unwrap(), 3).unwrap_err();
+        assert_eq!(
+            "Pixel (0, 0) cannot be drawn with a sprite 3 pixels wide",
+            err.to_string()
+        );
+        assert!(synthesize(".###.", 1).is_err());
+    }
+}
@@ -0,0 +1,258 @@
+//! The CPU of the handheld device: runs a program cycle by cycle, shows every cycle to an
+//! [`Observer`] (like the CRT) and can be stepped through like in a debugger
+
+/// The registers of the CPU
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Registers {
+    pub x: isize,
+}
+
+impl Default for Registers {
+    fn default() -> Self {
+        Self { x: 1 }
+    }
+}
+
+/// An instruction the CPU can run, implemented by instruction sets to add opcodes
+pub trait Operation {
+    /// How many cycles the instruction takes
+    fn cycles(&self) -> usize;
+
+    /// Changes the registers at the end of the last cycle of the instruction
+    fn execute(&self, registers: &mut Registers);
+}
+
+/// What the CPU is doing during a cycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct State {
+    /// The cycle, starting at 1
+    pub cycle: usize,
+    /// The index of the instruction being run
+    pub pc: usize,
+    pub registers: Registers,
+}
+
+/// Sees the state during every cycle
+pub trait Observer {
+    fn observe(&mut self, state: &State);
+}
+
+impl Observer for () {
+    fn observe(&mut self, _state: &State) {}
+}
+
+impl<A: Observer, B: Observer> Observer for (A, B) {
+    fn observe(&mut self, state: &State) {
+        self.0.observe(state);
+        self.1.observe(state);
+    }
+}
+
+/// Where [`Cpu::resume`] stops, before the cycle or instruction starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Breakpoint {
+    Cycle(usize),
+    Instruction(usize),
+}
+
+/// Why [`Cpu::resume`] stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stop {
+    Breakpoint(Breakpoint),
+    Halted,
+}
+
+#[derive(Debug, Clone)]
+pub struct Cpu<'p, I, O> {
+    program: &'p [I],
+    observer: O,
+    state: State,
+    /// The cycles of the current instruction that are done
+    busy: usize,
+    breakpoints: Vec<Breakpoint>,
+}
+
+impl<'p, I: Operation, O: Observer> Cpu<'p, I, O> {
+    #[must_use]
+    pub fn new(program: &'p [I], observer: O) -> Self {
+        Self {
+            program,
+            observer,
+            state: State {
+                cycle: 1,
+                pc: 0,
+                registers: Registers::default(),
+            },
+            busy: 0,
+            breakpoints: Vec::new(),
+        }
+    }
+
+    /// The state during the next cycle
+    #[must_use]
+    pub const fn inspect(&self) -> State {
+        self.state
+    }
+
+    #[must_use]
+    pub const fn observer(&self) -> &O {
+        &self.observer
+    }
+
+    #[must_use]
+    pub fn into_observer(self) -> O {
+        self.observer
+    }
+
+    #[must_use]
+    pub const fn is_halted(&self) -> bool {
+        self.state.pc >= self.program.len()
+    }
+
+    /// Runs a single cycle and returns the state it ran in, or `None` if the program is done
+    pub fn step(&mut self) -> Option<State> {
+        let instruction = self.program.get(self.state.pc)?;
+        let state = self.state;
+        self.observer.observe(&state);
+        self.busy += 1;
+        self.state.cycle += 1;
+        if self.busy >= instruction.cycles() {
+            instruction.execute(&mut self.state.registers);
+            self.state.pc += 1;
+            self.busy = 0;
+        }
+        Some(state)
+    }
+
+    /// Runs the rest of the current instruction and returns the states of its cycles
+    pub fn step_instruction(&mut self) -> Vec<State> {
+        let pc = self.state.pc;
+        let mut states = Vec::new();
+        while self.state.pc == pc {
+            let Some(state) = self.step() else { break };
+            states.push(state);
+        }
+        states
+    }
+
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) {
+        self.breakpoints.push(breakpoint);
+    }
+
+    pub fn remove_breakpoint(&mut self, breakpoint: Breakpoint) {
+        self.breakpoints.retain(|&b| b != breakpoint);
+    }
+
+    /// Runs at least one cycle, until a breakpoint is reached or the program is done
+    pub fn resume(&mut self) -> Stop {
+        while self.step().is_some() {
+            if let Some(&breakpoint) = self.breakpoints.iter().find(|&&b| self.is_at(b)) {
+                return Stop::Breakpoint(breakpoint);
+            }
+        }
+        Stop::Halted
+    }
+
+    const fn is_at(&self, breakpoint: Breakpoint) -> bool {
+        match breakpoint {
+            Breakpoint::Cycle(cycle) => self.state.cycle == cycle,
+            Breakpoint::Instruction(pc) => self.state.pc == pc && self.busy == 0,
+        }
+    }
+
+    /// Runs the whole program, ignoring breakpoints
+    #[must_use]
+    pub fn run(mut self) -> O {
+        while self.step().is_some() {}
+        self.observer
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    /// A made-up instruction set, with a three cycle multiplication
+    enum Extended {
+        Noop,
+        Mulx(isize),
+    }
+
+    impl Operation for Extended {
+        fn cycles(&self) -> usize {
+            match self {
+                Self::Noop => 1,
+                Self::Mulx(_) => 3,
+            }
+        }
+
+        fn execute(&self, registers: &mut Registers) {
+            if let Self::Mulx(factor) = self {
+                registers.x *= factor;
+            }
+        }
+    }
+
+    /// Remembers `x` during every cycle
+    #[derive(Default)]
+    struct Trace(Vec<isize>);
+
+    impl Observer for Trace {
+        fn observe(&mut self, state: &State) {
+            self.0.push(state.registers.x);
+        }
+    }
+
+    const PROGRAM: [Extended; 4] = [
+        Extended::Mulx(3),
+        Extended::Noop,
+        Extended::Mulx(-2),
+        Extended::Noop,
+    ];
+
+    #[test]
+    fn it_runs_custom_instructions() {
+        let trace = Cpu::new(&PROGRAM, Trace::default()).run();
+        assert_eq!(vec![1, 1, 1, 3, 3, 3, 3, -6], trace.0);
+        let (first, second) = Cpu::new(&PROGRAM, (Trace::default(), Trace::default())).run();
+        assert_eq!(first.0, second.0);
+    }
+
+    #[test]
+    fn it_steps() {
+        let mut cpu = Cpu::new(&PROGRAM, ());
+        assert_eq!(1, cpu.step().unwrap().cycle);
+        let states = cpu.step_instruction();
+        assert_eq!(
+            vec![2, 3],
+            states.iter().map(|state| state.cycle).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            State {
+                cycle: 4,
+                pc: 1,
+                registers: Registers { x: 3 }
+            },
+            cpu.inspect()
+        );
+        assert_eq!(1, cpu.step_instruction().len());
+        assert_eq!(3, cpu.step_instruction().len());
+        assert_eq!(1, cpu.step_instruction().len());
+        assert!(cpu.is_halted());
+        assert_eq!(None, cpu.step());
+    }
+
+    #[test]
+    fn it_stops_at_breakpoints() {
+        let mut cpu = Cpu::new(&PROGRAM, Trace::default());
+        cpu.add_breakpoint(Breakpoint::Instruction(2));
+        cpu.add_breakpoint(Breakpoint::Cycle(7));
+        assert_eq!(Stop::Breakpoint(Breakpoint::Instruction(2)), cpu.resume());
+        assert_eq!(5, cpu.inspect().cycle);
+        assert_eq!(Stop::Breakpoint(Breakpoint::Cycle(7)), cpu.resume());
+        assert_eq!(3, cpu.inspect().registers.x);
+        cpu.remove_breakpoint(Breakpoint::Instruction(2));
+        assert_eq!(Stop::Halted, cpu.resume());
+        assert_eq!(8, cpu.observer().0.len());
+    }
+}
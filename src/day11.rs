@@ -2,72 +2,104 @@ use itertools::Itertools;
 use nom::{
     branch::alt,
     bytes::complete::tag,
-    character::complete::{digit1, multispace0},
-    combinator::{all_consuming, map_res, value},
-    multi::separated_list1,
-    sequence::{preceded, tuple},
-    Finish, IResult,
+    character::complete::{char, digit1, multispace0, multispace1, one_of, space0},
+    combinator::{map, map_res, value},
+    multi::{fold_many0, separated_list1},
+    sequence::{delimited, pair, preceded, tuple},
+    IResult,
 };
 use std::collections::VecDeque;
 
-use crate::util::read_input;
+use color_eyre::eyre::eyre;
 
+use crate::{
+    solution::Solution,
+    util::{parse, ParseError},
+};
 
-pub fn solve() {
-    println!("Day 11 part 1: {}", part1(&read_input("day11.txt")));
-    println!("Day 11 part 2: {}", part2(&read_input("day11.txt")));
-}
+pub struct Day11;
 
-fn part2(input: &str) -> u64 {
-    let mut monkeys: Vec<Monkey> = parse_monkeys(input);
-    for _ in 0..10_000 {
-        for i in 0..monkeys.len() {
-            monkeys.as_mut_slice().monkey_business(i, 1);
-        }
+impl Solution for Day11 {
+    const DAY: u8 = 11;
+    type Input = Vec<Monkey>;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> color_eyre::Result<Self::Input> {
+        parse_monkeys(input)
     }
 
-    monkeys
-        .iter()
-        .map(|m| m.inspection_counter)
-        .sorted()
-        .rev()
-        .take(2)
-        .product()
+    fn part1(monkeys: &Self::Input) -> color_eyre::Result<u64> {
+        monkey_business_level(monkeys.clone(), 20, 3)
+    }
+
+    fn part2(monkeys: &Self::Input) -> color_eyre::Result<u64> {
+        monkey_business_level(monkeys.clone(), 10_000, 1)
+    }
 }
 
-fn part1(input: &str) -> u64 {
-    let mut monkeys: Vec<Monkey> = parse_monkeys(input);
-    for _ in 0..20 {
+fn monkey_business_level(
+    mut monkeys: Vec<Monkey>,
+    rounds: usize,
+    relief_divisor: u64,
+) -> color_eyre::Result<u64> {
+    for _ in 0..rounds {
         for i in 0..monkeys.len() {
-            monkeys.as_mut_slice().monkey_business(i, 3);
+            monkeys.as_mut_slice().monkey_business(i, relief_divisor)?;
         }
     }
 
-    monkeys
+    Ok(monkeys
         .iter()
         .map(|m| m.inspection_counter)
         .sorted()
         .rev()
         .take(2)
-        .product()
+        .product())
 }
 
-fn parse_monkeys(input: &str) -> Vec<Monkey> {
-    input
-    .trim()
-    .replace("\r\n", "\n")
-    .split("\n\n")
-    .map(|lines| all_consuming(monkey_parser)(lines).finish().unwrap().1)
-    .collect()
+fn parse_monkeys(input: &str) -> color_eyre::Result<Vec<Monkey>> {
+    let monkeys = parse::nom(input, |input| {
+        delimited(
+            multispace0,
+            separated_list1(multispace1, monkey_parser),
+            multispace0,
+        )(input)
+    })?;
+    for (i, monkey) in monkeys.iter().enumerate() {
+        for (target, branch) in [
+            (monkey.target_true, "If true: "),
+            (monkey.target_false, "If false: "),
+        ] {
+            if target >= monkeys.len() {
+                let (position, _) = input
+                    .match_indices(branch)
+                    .nth(i)
+                    .unwrap_or((input.len(), ""));
+                return Err(ParseError::at(
+                    input,
+                    &input[position..],
+                    format!("There is no monkey {target}"),
+                )
+                .into());
+            }
+        }
+    }
+    Ok(monkeys)
 }
 
 trait Monkeys {
-    fn monkey_business(&mut self, monkey: usize, relief_divisor: u64);
+    /// # Errors
+    /// If a worry level becomes negative or overflows
+    fn monkey_business(&mut self, monkey: usize, relief_divisor: u64) -> color_eyre::Result<()>;
 }
 
 impl Monkeys for &mut [Monkey] {
-    fn monkey_business(&mut self, monkey: usize, relief_divisor: u64) {
-        let lcm: u64 = self.iter().map(|monkey| monkey.test_divisor).product();
+    fn monkey_business(&mut self, monkey: usize, relief_divisor: u64) -> color_eyre::Result<()> {
+        let lcm: i128 = self
+            .iter()
+            .map(|monkey| i128::from(monkey.test_divisor))
+            .product();
         // println!("Monkey {monkey}:");
         let monkey_cloned = &mut self[monkey].clone();
         self[monkey].items.clear();
@@ -75,10 +107,24 @@ impl Monkeys for &mut [Monkey] {
             self[monkey].inspection_counter += 1;
             // println!("  Monkey inspects an item with a worry level of {item}.");
             monkey_cloned.inspection_counter += 1;
-            item = monkey_cloned.operation.apply(item);
-            item %= lcm;
+            let level = monkey_cloned
+                .operation
+                .evaluate(i128::from(item))
+                .ok_or_else(|| eyre!("Monkey {monkey} makes the worry level {item} overflow"))?;
             // println!("    Worry level is adjusted to {item}.");
-            item /= relief_divisor;
+            // without relief the levels grow without bound, but staying the same modulo every
+            // test divisor keeps the tests (and the levels the operations make of them) the same.
+            // Only the residue is known then, so a negative intermediate level is no error.
+            let level = if relief_divisor == 1 {
+                level.rem_euclid(lcm)
+            } else if level < 0 {
+                return Err(eyre!(
+                    "Monkey {monkey} makes the worry level {item} negative ({level})"
+                ));
+            } else {
+                level / i128::from(relief_divisor)
+            };
+            item = u64::try_from(level).map_err(|_| eyre!("The worry level {level} overflows"))?;
             // println!("    Monkey gets bored with item. Worry level is divided by {RELIEF_DIVISOR} to {item}.");
             if item % monkey_cloned.test_divisor == 0 {
                 // println!(
@@ -102,13 +148,14 @@ impl Monkeys for &mut [Monkey] {
                 // );
             }
         }
+        Ok(())
     }
 }
 
-#[derive(Clone, PartialEq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub struct Monkey {
     pub items: VecDeque<u64>,
-    operation: Operation,
+    operation: Expression,
     test_divisor: u64,
     target_false: usize,
     target_true: usize,
@@ -122,68 +169,90 @@ fn items_parser(input: &str) -> IResult<&str, Vec<u64>> {
     )(input)
 }
 
-#[derive(Clone, PartialEq, Debug)]
-struct Operation {
-    operator: Operator,
-    operand: Operand,
+/// How a monkey changes the worry level `old` of an item
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Expression {
+    Old,
+    Constant(i128),
+    Add(Box<Self>, Box<Self>),
+    Sub(Box<Self>, Box<Self>),
+    Mul(Box<Self>, Box<Self>),
 }
 
-impl Operation {
-    pub const fn apply(&self, value: u64) -> u64 {
-        match (self.operator, self.operand) {
-            (Operator::Mul, Operand::Old) => value * value,
-            (Operator::Mul, Operand::Value(s)) => value * s,
-            (Operator::Add, Operand::Old) => value + value,
-            (Operator::Add, Operand::Value(s)) => value + s,
+impl Expression {
+    /// The new worry level, which may be negative, or `None` if it overflows
+    #[must_use]
+    pub fn evaluate(&self, old: i128) -> Option<i128> {
+        match self {
+            Self::Old => Some(old),
+            Self::Constant(constant) => Some(*constant),
+            Self::Add(a, b) => a.evaluate(old)?.checked_add(b.evaluate(old)?),
+            Self::Sub(a, b) => a.evaluate(old)?.checked_sub(b.evaluate(old)?),
+            Self::Mul(a, b) => a.evaluate(old)?.checked_mul(b.evaluate(old)?),
         }
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Debug)]
-enum Operator {
-    Mul,
-    Add,
+fn operation_parser(input: &str) -> IResult<&str, Expression> {
+    preceded(
+        tuple((multispace0, tag("Operation: new ="), space0)),
+        expression_parser,
+    )(input)
 }
-#[derive(Clone, Copy, PartialEq, Debug)]
-enum Operand {
-    Old,
-    Value(u64),
+
+/// Sums and differences of terms
+fn expression_parser(input: &str) -> IResult<&str, Expression> {
+    let (input, first) = term_parser(input)?;
+    fold_many0(
+        pair(delimited(space0, one_of("+-"), space0), term_parser),
+        move || first.clone(),
+        |left, (operator, right)| match operator {
+            '+' => Expression::Add(Box::new(left), Box::new(right)),
+            _ => Expression::Sub(Box::new(left), Box::new(right)),
+        },
+    )(input)
 }
 
-fn operation_parser(input: &str) -> IResult<&str, Operation> {
-    let operand = map_res(digit1, |s: &str| s.parse().map(Operand::Value));
-    let (remaining, (operator, operand)) = preceded(
-        tuple((multispace0, tag("Operation: new = old"))),
-        tuple((
-            alt((
-                value(Operator::Add, tag(" + ")),
-                value(Operator::Mul, tag(" * ")),
-            )),
-            alt((value(Operand::Old, tag("old")), operand)),
-        )),
-    )(input)?;
-    let f: Operation = Operation { operator, operand };
-    IResult::Ok((remaining, f))
+/// Products of factors
+fn term_parser(input: &str) -> IResult<&str, Expression> {
+    let (input, first) = factor_parser(input)?;
+    fold_many0(
+        preceded(delimited(space0, char('*'), space0), factor_parser),
+        move || first.clone(),
+        |left, right| Expression::Mul(Box::new(left), Box::new(right)),
+    )(input)
+}
+
+fn factor_parser(input: &str) -> IResult<&str, Expression> {
+    alt((
+        value(Expression::Old, tag("old")),
+        map(nom::character::complete::u64, |constant| {
+            Expression::Constant(constant.into())
+        }),
+        delimited(
+            pair(char('('), space0),
+            expression_parser,
+            pair(space0, char(')')),
+        ),
+    ))(input)
 }
 
-fn test_parser(input: &str) -> IResult<&str, (u64, u64, u64)> {
+fn test_parser(input: &str) -> IResult<&str, (u64, usize, usize)> {
+    let target = || map_res(nom::character::complete::u64, usize::try_from);
     let (remaining, test) = preceded(
         tuple((multispace0, tag("Test: divisible by "))),
         nom::character::complete::u64,
     )(input)?;
     let (remaining, true_branch) = preceded(
         tuple((multispace0, tag("If true: throw to monkey "))),
-        nom::character::complete::u64,
+        target(),
     )(remaining)?;
 
     let (remaining, false_branch) = preceded(
         tuple((multispace0, tag("If false: throw to monkey "))),
-        nom::character::complete::u64,
+        target(),
     )(remaining)?;
-    IResult::Ok((
-        remaining,
-        (test, true_branch, false_branch),
-    ))
+    IResult::Ok((remaining, (test, true_branch, false_branch)))
 }
 
 fn monkey_parser(i: &str) -> IResult<&str, Monkey> {
@@ -197,8 +266,8 @@ fn monkey_parser(i: &str) -> IResult<&str, Monkey> {
             items: starting_items.into(),
             operation,
             test_divisor: test,
-            target_false: false_path.try_into().unwrap(),
-            target_true: true_path.try_into().unwrap(),
+            target_false: false_path,
+            target_true: true_path,
             inspection_counter: 0,
         },
     ))
@@ -223,9 +292,36 @@ pub mod tests {
         let input = "  Operation: new = old * 19";
         let (remaining, f) = operation_parser(input).unwrap();
         assert_eq!("", remaining);
-        assert_eq!(0, f.apply(0));
-        assert_eq!(19, f.apply(1));
-        assert_eq!(38, f.apply(2));
+        assert_eq!(Some(0), f.evaluate(0));
+        assert_eq!(Some(19), f.evaluate(1));
+        assert_eq!(Some(38), f.evaluate(2));
+    }
+
+    #[test]
+    fn it_evaluates_expressions() {
+        let expression = |input| operation_parser(input).unwrap().1;
+        assert_eq!(
+            Some(25),
+            expression("Operation: new = old * old").evaluate(5)
+        );
+        assert_eq!(
+            Some(17),
+            expression("Operation: new = 2 + old * 3").evaluate(5)
+        );
+        assert_eq!(
+            Some(14),
+            expression("Operation: new = (old - 3) * (old+2)").evaluate(5)
+        );
+        assert_eq!(
+            Some(1),
+            expression("Operation: new = old - 2 - 3 + 1").evaluate(5)
+        );
+        assert_eq!(Some(-4), expression("Operation: new = old - 9").evaluate(5));
+        assert_eq!(
+            None,
+            expression("Operation: new = old * old * old").evaluate(i128::from(u64::MAX))
+        );
+        assert!(operation_parser("Operation: new = (old + 1").is_err());
     }
 
     #[test]
@@ -250,10 +346,10 @@ pub mod tests {
         assert_eq!("", remaining);
         let expected = Monkey {
             items: vec![79, 98].into(),
-            operation: Operation {
-                operator: Operator::Mul,
-                operand: Operand::Value(19),
-            },
+            operation: Expression::Mul(
+                Box::new(Expression::Old),
+                Box::new(Expression::Constant(19)),
+            ),
             test_divisor: 23,
             target_false: 3,
             target_true: 2,
@@ -262,15 +358,58 @@ pub mod tests {
         assert_eq!(expected, monkey);
     }
 
+    #[test]
+    fn it_locates_parse_errors() {
+        let input = read_example("day11.txt");
+        let err = |input: &str| {
+            Day11::parse(input)
+                .unwrap_err()
+                .downcast::<ParseError>()
+                .unwrap()
+        };
+        let operator = err(&input.replacen("old * 19", "old / 19", 1));
+        assert_eq!((3, 24), (operator.line, operator.column));
+        let target = err(&input.replacen("monkey 3", "monkey 7", 1));
+        assert_eq!(
+            (6, 5, "There is no monkey 7"),
+            (target.line, target.column, target.message.as_str())
+        );
+    }
+
     #[test]
     fn it_solves_part1() {
         let input = read_example("day11.txt");
-        assert_eq!(10605, part1(&input));
+        assert_eq!(10605, Day11::solve_part1(&input).unwrap());
+    }
+
+    #[test]
+    fn it_rejects_negative_worry_levels() {
+        let input = read_example("day11.txt").replacen("old * 19", "old - 90", 1);
+        let err = Day11::solve_part1(&input).unwrap_err();
+        assert_eq!(
+            "Monkey 0 makes the worry level 79 negative (-11)",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn it_solves_part1_with_subtraction() {
+        let input = read_example("day11.txt").replacen("old * 19", "old * 20 - old", 1);
+        assert_eq!(10605, Day11::solve_part1(&input).unwrap());
+        let input = read_example("day11.txt").replacen("old * 19", "old * 19 - 4", 1);
+        assert_eq!(10706, Day11::solve_part1(&input).unwrap());
     }
 
     #[test]
     fn it_solves_part2() {
         let input = read_example("day11.txt");
-        assert_eq!(2_713_310_158, part2(&input));
+        assert_eq!(2_713_310_158, Day11::solve_part2(&input).unwrap());
+    }
+
+    #[test]
+    fn it_solves_part2_with_subtraction() {
+        // the levels are only known modulo the test divisors, where `old * old - 100` can be negative
+        let input = read_example("day11.txt").replacen("old * 19", "old * old - 100", 1);
+        assert_eq!(2_499_800_000, Day11::solve_part2(&input).unwrap());
     }
 }
@@ -1,149 +1,81 @@
 use std::{collections::HashMap, str::FromStr};
 
-use crate::util::read_input;
+use crate::{
+    solution::Solution,
+    util::{search, Grid},
+};
 
-pub fn solve() -> color_eyre::Result<()> {
-    let input = read_input("day12.txt");
-    println!("Day 12 part 1: {}", part1(&input)?);
-    println!("Day 12 part 2: {}", part2(&input)?);
-    Ok(())
-}
+pub struct Day12;
 
-pub fn part1(input: &str) -> color_eyre::Result<usize> {
-    let graph = Graph::from_str(input)?;
-    let start = get_distinct_node(input, 'S')
-        .ok_or_else(|| color_eyre::eyre::eyre!("Cannot find start node"))?;
-    let (_, prev) = graph
-        .dijkstra(start)
-        .ok_or_else(|| color_eyre::eyre::eyre!("Error applying dijkstra"))?;
-    let mut best = usize::MAX;
-    for (index, value) in input.replace(['\r', '\n'], "").char_indices().filter(|(_, c)| c == &'E' ) {
-        let mut target = &Node { index, value };
-        let mut c = 0;
-        while let Some(predecessor) = prev.get(target) {
-            target = predecessor;
-            c += 1;
-        }
-        // Not sure why the != 0 is needed, but like this, it gives me the correct result. Probably an offbyone somewhere?
-        if best > c  && c != 0 {
-            best = c;
-        }
+impl Solution for Day12 {
+    const DAY: u8 = 12;
+    type Input = Graph;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> color_eyre::Result<Self::Input> {
+        input.parse()
     }
 
-    Ok(best)
-}
+    fn part1(graph: &Self::Input) -> color_eyre::Result<usize> {
+        fewest_steps(graph, |c| c == 'S')
+    }
 
-pub fn part2(input: &str) -> color_eyre::Result<usize> {
-    let reversed_heightmap = input
-        .chars()
-        .map(|c| match c {
-            'a' => Some('E'),
-            c if c.is_ascii_lowercase() => {
-                char::from_u32('a' as u32 + ('z' as u32) - (c as u32))
-            }
-            'E' => Some('S'),
-            'S' => Some('E'),
-            a => Some(a),
-        })
-        .collect::<Option<String>>()
-        .ok_or_else(|| color_eyre::eyre::eyre!("Cannot parse input"))?;
-        //println!("{}", &reversed_heightmap);
-        part1(&reversed_heightmap)
+    fn part2(graph: &Self::Input) -> color_eyre::Result<usize> {
+        fewest_steps(graph, |c| c == 'S' || c == 'a')
+    }
 }
 
-fn get_distinct_node(input: &str, node: char) -> Option<Node> {
-    let index = input.replace(['\r', '\n'], "").find(node)?;
-    Some(Node { index, value: node })
+/// The fewest steps from any node matching `is_start` to the best signal
+fn fewest_steps(graph: &Graph, is_start: impl Fn(char) -> bool) -> color_eyre::Result<usize> {
+    let starts = graph
+        .edges
+        .keys()
+        .filter(|node| is_start(node.value))
+        .copied();
+    search::bfs(starts, |node| graph.edges[&node].iter().copied())
+        .find(|(node, _)| node.value == 'E')
+        .map(|(_, steps)| steps)
+        .ok_or_else(|| color_eyre::eyre::eyre!("Cannot reach the best signal"))
 }
 
 #[derive(Debug, Default)]
 pub struct Graph {
-    edges: HashMap<Node, Vec<Edge>>,
-}
-
-impl Graph {
-    fn dijkstra(&self, start: Node) -> Option<(HashMap<Node, isize>, HashMap<Node, Node>)> {
-        let mut dist: HashMap<Node, isize> = HashMap::default();
-        let mut prev: HashMap<Node, Node> = HashMap::default();
-        let mut q: Vec<Node> = self.edges.keys().copied().collect();
-        dist.insert(start, 0);
-        while !q.is_empty() {
-            // find vertex with minimum distance
-            let (idx, u) = q
-                .iter()
-                .enumerate()
-                .min_by_key(|(_, v)| dist.get(v).unwrap_or(&isize::MAX))?;
-
-            let dist_to_u = match dist.get(u) {
-                Some(d) => *d,
-                None => return Some((dist, prev)),
-            };
-            let u = q.remove(idx);
-            for edge in self.edges.get(&u)?.iter().filter(|e| q.contains(&e.target)) {
-                let alt = dist_to_u + edge.cost;
-                if alt < *dist.get(&edge.target).unwrap_or(&isize::MAX) {
-                    dist.insert(edge.target, alt);
-                    prev.insert(edge.target, u);
-                }
-            }
-        }
-        Some((dist, prev))
-    }
+    edges: HashMap<Node, Vec<Node>>,
 }
 
 impl FromStr for Graph {
     type Err = color_eyre::Report;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let chars: Vec<Vec<char>> = s
-            .trim()
-            .lines()
-            .map(|line| line.chars().collect::<Vec<char>>())
-            .collect();
-        let mut graph = Self::default();
-        let width = chars[0].len();
+        let chars = Grid::parse(s.trim_end(), |c| match c {
+            'a'..='z' | 'S' | 'E' => Ok(c),
+            _ => Err(color_eyre::eyre::eyre!("'{c}' is not an elevation")),
+        })?;
         let get_value = |c: char| match c {
             'S' => 'a',
             'E' => 'z',
             e => e,
         };
-        let add_edge = |(value, nx, ny, edges): (char, usize, usize, &mut Vec<Edge>)| {
-            let neighbour = get_value(chars[ny][nx]);
-            let difference = (neighbour as i16) - (value as i16); // we can go downwards as far as we want, but upwards only 1 step
-            if difference < 2 {
-                edges.push(Edge {
-                    cost: 26 - difference as isize,
-                    target: Node {
-                        index: nx + ny * width,
-                        value: chars[ny][nx],
-                    },
-                });
-            }
-        };
-
-        for y in 0..chars.len() {
-            for x in 0..width {
-                let value = get_value(chars[y][x]);
-                let node = Node {
-                    index: x + y * width,
-                    value: chars[y][x],
-                };
-                let mut edges: Vec<Edge> = Vec::default();
-                if x > 0 {
-                    add_edge((value, x - 1, y, &mut edges));
-                }
-                if x < width - 1 {
-                    add_edge((value, x + 1, y, &mut edges));
-                }
-                if y > 0 {
-                    add_edge((value, x, y - 1, &mut edges));
-                }
-                if y < chars.len() - 1 {
-                    add_edge((value, x, y + 1, &mut edges));
-                }
-
-                graph.edges.insert(node, edges);
-            }
+        let mut graph = Self::default();
+        for (x, y) in chars.positions() {
+            let value = get_value(chars[(x, y)]);
+            let node = Node {
+                index: chars.index_of(x, y),
+                value: chars[(x, y)],
+            };
+            let edges = chars
+                .neighbours(x, y)
+                .filter_map(|(nx, ny)| {
+                    let neighbour = get_value(chars[(nx, ny)]);
+                    let difference = (neighbour as i16) - (value as i16); // we can go downwards as far as we want, but upwards only 1 step
+                    (difference < 2).then(|| Node {
+                        index: chars.index_of(nx, ny),
+                        value: chars[(nx, ny)],
+                    })
+                })
+                .collect();
+            graph.edges.insert(node, edges);
         }
         Ok(graph)
     }
@@ -155,28 +87,10 @@ pub struct Node {
     value: char,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
-pub struct Edge {
-    cost: isize,
-    target: Node,
-}
-
-impl Ord for Edge {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.cost.cmp(&other.cost)
-    }
-}
-
-impl PartialOrd for Edge {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
 #[cfg(test)]
 pub mod tests {
     use super::*;
-    use crate::util::read_example;
+    use crate::util::{read_example, ParseError};
     #[test]
     fn it_parses_graph() {
         let input = read_example("day12.txt");
@@ -185,25 +99,22 @@ pub mod tests {
             2,
             graph
                 .edges
-                .get(&get_distinct_node(&input, 'S').unwrap())
+                .get(&Node {
+                    index: 0,
+                    value: 'S'
+                })
                 .unwrap()
                 .len()
         );
         assert_eq!(
             &vec![
-                Edge {
-                    cost: 27,
-                    target: Node {
-                        index: 1,
-                        value: 'a'
-                    }
+                Node {
+                    index: 10,
+                    value: 'c'
                 },
-                Edge {
-                    cost: 25,
-                    target: Node {
-                        index: 10,
-                        value: 'c'
-                    }
+                Node {
+                    index: 1,
+                    value: 'a'
                 }
             ],
             graph
@@ -216,17 +127,30 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn it_rejects_unknown_elevations() {
+        let input = read_example("day12.txt").replacen('c', "C", 1);
+        let err = Graph::from_str(&input)
+            .unwrap_err()
+            .downcast::<ParseError>()
+            .unwrap();
+        assert_eq!(
+            ParseError::new(2, 3, "abCryxxl", "'C' is not an elevation"),
+            err
+        );
+    }
+
     #[test]
     fn it_finds_path() {
         let input = read_example("day12.txt");
 
-        assert_eq!(31, part1(&input).unwrap());
+        assert_eq!(31, Day12::solve_part1(&input).unwrap());
     }
 
     #[test]
     fn it_finds_reverse_path() -> color_eyre::Result<()> {
         let input = read_example("day12.txt");
-        assert_eq!(29, part2(&input)?);
+        assert_eq!(29, Day12::solve_part2(&input)?);
         Ok(())
     }
 }
@@ -3,56 +3,59 @@ use std::cmp::Ordering;
 use nom::{
     branch::alt,
     bytes::complete::tag,
-    combinator::{all_consuming, map, opt},
+    combinator::{cut, map, opt},
     multi::{many1, separated_list0, separated_list1},
     sequence::{delimited, pair, preceded, tuple},
-    Finish, IResult,
+    IResult,
 };
 
-use crate::util::read_input;
+use crate::{solution::Solution, util::parse};
 
-pub fn solve() {
-    let input = read_input("day13.txt");
-    println!("Day 13 part 1: {}", part1(&input));
-    println!("Day 13 part 2: {}", part2(&input));
-}
+pub struct Day13;
 
-pub fn part1(input: &str) -> usize {
-    let pairs = all_consuming(Packet::parse_input)(input.trim())
-        .finish()
-        .unwrap()
-        .1;
-    pairs
-        .iter()
-        .enumerate()
-        .map(|(index, (a, b))| if a < b { index + 1 } else { 0 })
-        .sum()
-}
+impl Solution for Day13 {
+    const DAY: u8 = 13;
+    type Input = Vec<(Packet, Packet)>;
+    type Answer1 = usize;
+    type Answer2 = usize;
 
-pub fn part2(input: &str) -> usize {
-    let pairs: Vec<_> = all_consuming(Packet::parse_input)(input.trim())
-        .finish()
-        .unwrap()
-        .1;
-    let mut packets: Vec<_> = pairs.iter().map(|(a, _)| a).cloned().collect();
-    let second_part: Vec<_> = pairs.iter().map(|(_, a)| a).cloned().collect();
-    let markers = Packet::parse_pair(
-        "[[2]]
+    fn parse(input: &str) -> color_eyre::Result<Self::Input> {
+        Ok(parse::nom(input.trim_end(), Packet::parse_input)?)
+    }
+
+    fn part1(pairs: &Self::Input) -> color_eyre::Result<usize> {
+        Ok(pairs
+            .iter()
+            .enumerate()
+            .map(|(index, (a, b))| if a < b { index + 1 } else { 0 })
+            .sum())
+    }
+
+    fn part2(pairs: &Self::Input) -> color_eyre::Result<usize> {
+        let mut packets: Vec<_> = pairs.iter().map(|(a, _)| a).cloned().collect();
+        let second_part: Vec<_> = pairs.iter().map(|(_, a)| a).cloned().collect();
+        let markers = Packet::parse_pair(
+            "[[2]]
 [[6]]",
-    )
-    .unwrap()
-    .1;
-    packets.extend(second_part);
-    packets.push(markers.0.clone());
-    packets.push(markers.1.clone());
-    packets.sort_unstable();
-    (packets.binary_search(&markers.0).unwrap() + 1)
-        * (packets.binary_search(&markers.1).unwrap() + 1)
+        )
+        .map_err(|err| color_eyre::eyre::eyre!("Cannot parse markers: {err}"))?
+        .1;
+        packets.extend(second_part);
+        packets.push(markers.0.clone());
+        packets.push(markers.1.clone());
+        packets.sort_unstable();
+        let position = |marker| {
+            packets
+                .binary_search(marker)
+                .map_err(|_| color_eyre::eyre::eyre!("Cannot find marker"))
+        };
+        Ok((position(&markers.0)? + 1) * (position(&markers.1)? + 1))
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Packet {
-    List(Vec<Packet>),
+    List(Vec<Self>),
     Number(u8),
 }
 
@@ -96,6 +99,8 @@ impl PartialOrd for Packet {
 }
 
 impl Packet {
+    /// # Errors
+    /// If the input does not start with a packet
     pub fn parse(input: &str) -> IResult<&str, Self> {
         fn parse_list(input: &str) -> IResult<&str, Packet> {
             delimited(
@@ -115,6 +120,8 @@ impl Packet {
         parse_list(input)
     }
 
+    /// # Errors
+    /// If the input does not start with two packets on consecutive lines
     pub fn parse_pair(input: &str) -> IResult<&str, (Self, Self)> {
         pair(
             Self::parse,
@@ -122,8 +129,15 @@ impl Packet {
         )(input)
     }
 
+    /// Pairs separated by blank lines, without trailing line breaks
+    /// # Errors
+    /// If a pair is malformed
     pub fn parse_input(input: &str) -> IResult<&str, Vec<(Self, Self)>> {
-        separated_list1(many1(tuple((opt(tag("\r")), tag("\n")))), Self::parse_pair)(input)
+        // cut, so that a broken pair is reported instead of everything after the last good one
+        separated_list1(
+            many1(tuple((opt(tag("\r")), tag("\n")))),
+            cut(Self::parse_pair),
+        )(input)
     }
 }
 
@@ -208,15 +222,25 @@ pub mod tests {
         let list = all_consuming(Packet::parse_pair)(input).finish().unwrap().1;
         assert!(list.0 < list.1);
     }
+    #[test]
+    fn it_locates_parse_errors() {
+        let input = read_example("day13.txt").replacen("[[1],4]", "[[1],4", 1);
+        let err = Day13::parse(&input)
+            .unwrap_err()
+            .downcast::<crate::util::ParseError>()
+            .unwrap();
+        assert_eq!((5, 7), (err.line, err.column));
+    }
+
     #[test]
     fn it_solves_part1() {
         let input = read_example("day13.txt");
-        assert_eq!(13, part1(&input));
+        assert_eq!(13, Day13::solve_part1(&input).unwrap());
     }
 
     #[test]
     fn it_solves_part2() {
         let input = read_example("day13.txt");
-        assert_eq!(140, part2(&input));
+        assert_eq!(140, Day13::solve_part2(&input).unwrap());
     }
 }
@@ -1,41 +1,57 @@
 use std::fmt::Display;
 
-use itertools::{Itertools, MinMaxResult};
+use itertools::Itertools;
 use nom::{
     bytes::complete::tag,
-    combinator::{all_consuming, map, opt},
+    combinator::{cut, map, opt},
     multi::{many1, separated_list1},
     sequence::{separated_pair, tuple},
-    Finish, IResult,
+    IResult,
 };
 
-use crate::util::read_input;
+use crate::{
+    solution::Solution,
+    util::{parse, Grid, Vec2},
+};
+
+pub struct Day14;
+
+impl Solution for Day14 {
+    const DAY: u8 = 14;
+    type Input = Vec<Vec<Coordinate>>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> color_eyre::Result<Self::Input> {
+        Cave::parse_rocks(input)
+    }
+
+    fn part1(rocks: &Self::Input) -> color_eyre::Result<usize> {
+        let mut cave = Cave::new(rocks.clone(), (500, 0), None);
+        Ok(cave.count_resting_sand())
+    }
 
-pub fn solve() {
-    let input = read_input("day14.txt");
-    let mut cave = Cave::parse(&input, (500, 0), None);
-    println!("Day 14 part 1: {}", cave.count_resting_sand());
-    let mut cave = Cave::parse(&input, (500, 0), Some(2));
-    println!("Day 14 part 2: {}", cave.count_resting_sand());
+    fn part2(rocks: &Self::Input) -> color_eyre::Result<usize> {
+        let mut cave = Cave::new(rocks.clone(), (500, 0), Some(2));
+        Ok(cave.count_resting_sand())
+    }
 }
 
 pub struct Cave {
-    // index = x * height + y
-    // That means it's the opposite direction since we are normally doing column operations
-    grid: Vec<Cell>,
-    height: usize,
-    width: usize,
+    grid: Grid<Cell>,
     drop_position: Coordinate,
 }
 
 impl Cave {
     pub fn set_cell(&mut self, coordinate: impl Into<Coordinate>, cell: Cell) {
-        let coord = self.get_index(coordinate);
-        self.grid[coord] = cell;
+        let Coordinate { x, y } = coordinate.into();
+        self.grid[(x, y)] = cell;
     }
 
-    pub fn get_cell(&self, coordinate: impl Into<Coordinate>) -> Cell {
-        self.grid[self.get_index(coordinate)]
+    /// `None` outside of the cave, where sand falls into the abyss
+    pub fn get_cell(&self, coordinate: impl Into<Coordinate>) -> Option<Cell> {
+        let Coordinate { x, y } = coordinate.into();
+        self.grid.get(x, y).copied()
     }
 
     pub fn count_resting_sand(&mut self) -> usize {
@@ -50,36 +66,30 @@ impl Cave {
         self.drop_sand(self.drop_position)
     }
 
+    /// Lets a unit of sand fall until it comes to rest, or `None` if it falls out of the cave
     fn drop_sand(&mut self, coordinate: impl Into<Coordinate>) -> Option<Coordinate> {
-        if self.get_cell(self.drop_position) == Cell::Sand {
+        if self.get_cell(self.drop_position) == Some(Cell::Sand) {
             return None;
         }
         let mut coordinate: Coordinate = coordinate.into();
-        if coordinate.x >= self.width {
-            return None;
-        }
-        while Cell::Air == self.get_cell(coordinate) {
-            coordinate.y += 1;
-        }
-        // back up 1
-        coordinate.y -= 1;
-
-        // try left and right first. If they are already filled, stay in the centre.
-        if Cell::Air == self.get_cell((coordinate.x.checked_sub(1)?, coordinate.y + 1)) {
-            self.drop_sand((coordinate.x.checked_sub(1)?, coordinate.y + 1))
-        } else if Cell::Air == self.get_cell((coordinate.x + 1, coordinate.y + 1)) {
-            self.drop_sand((coordinate.x + 1, coordinate.y + 1))
-        } else {
+        'falling: loop {
+            // straight down first, then down to the left and then down to the right
+            for x in [
+                Some(coordinate.x),
+                coordinate.x.checked_sub(1),
+                Some(coordinate.x + 1),
+            ] {
+                let below: Coordinate = (x?, coordinate.y + 1).into();
+                if self.get_cell(below)? == Cell::Air {
+                    coordinate = below;
+                    continue 'falling;
+                }
+            }
             self.set_cell(coordinate, Cell::Sand);
-            Some(coordinate)
+            return Some(coordinate);
         }
     }
 
-    pub fn get_index(&self, coordinate: impl Into<Coordinate>) -> usize {
-        let coordinate: Coordinate = coordinate.into();
-        coordinate.x * self.height + coordinate.y
-    }
-
     fn _parse_coordinate(input: &str) -> IResult<&str, Coordinate> {
         map(
             separated_pair(
@@ -92,13 +102,19 @@ impl Cave {
     }
 
     fn _parse_line(input: &str) -> IResult<&str, Vec<Coordinate>> {
-        separated_list1(tag(" -> "), Self::_parse_coordinate)(input)
+        separated_list1(tag(" -> "), cut(Self::_parse_coordinate))(input)
     }
     fn _parse_input(input: &str) -> IResult<&str, Vec<Vec<Coordinate>>> {
-        separated_list1(many1(tuple((opt(tag("\r")), tag("\n")))), Self::_parse_line)(input)
+        separated_list1(
+            many1(tuple((opt(tag("\r")), tag("\n")))),
+            cut(Self::_parse_line),
+        )(input)
     }
 
     fn draw_rock_line(&mut self, rocks: &[Coordinate]) {
+        if let [rock] = rocks {
+            self.set_cell(*rock, Cell::Rock);
+        }
         for (from, to) in rocks.iter().tuple_windows() {
             for y in from.y.min(to.y)..=from.y.max(to.y) {
                 for x in from.x.min(to.x)..=from.x.max(to.x) {
@@ -108,24 +124,52 @@ impl Cave {
         }
     }
 
+    /// # Errors
+    /// If the input is not a list of rock formations
+    pub fn parse_rocks(input: &str) -> color_eyre::Result<Vec<Vec<Coordinate>>> {
+        Ok(parse::nom(input.trim_end(), Self::_parse_input)?)
+    }
+
+    /// # Errors
+    /// If the input is malformed
     pub fn parse(
         input: &str,
         drop_position: impl Into<Coordinate>,
         floor_offset: Option<usize>,
+    ) -> color_eyre::Result<Self> {
+        Ok(Self::new(
+            Self::parse_rocks(input)?,
+            drop_position,
+            floor_offset,
+        ))
+    }
+
+    /// The cave around the rocks and the drop position, and with `floor_offset` a floor that far
+    /// below the lowest rock, wide enough that no sand can fall past its ends
+    #[must_use]
+    pub fn new(
+        mut lines: Vec<Vec<Coordinate>>,
+        drop_position: impl Into<Coordinate>,
+        floor_offset: Option<usize>,
     ) -> Self {
         let mut drop_position: Coordinate = drop_position.into();
-        let mut lines = all_consuming(Self::_parse_input)(input.trim())
-            .finish()
-            .unwrap()
-            .1;
-        let MinMaxResult::MinMax(min, max) = lines.iter().flatten().map(|x| x.x).minmax() else {
-            panic!()
-        };
-        let minmax_x = (min.min(drop_position.x), max.max(drop_position.x));
-        let MinMaxResult::MinMax(min, max) = lines.iter().flatten().map(|y| y.y).minmax() else {
-            panic!()
+        // the drop position is part of the cave, so there is always a bounding box
+        let coordinates = || {
+            lines
+                .iter()
+                .flatten()
+                .chain(std::iter::once(&drop_position))
         };
-        let mut minmax_y = (min.min(drop_position.y), max.max(drop_position.y));
+        let minmax_x = coordinates()
+            .map(|c| c.x)
+            .minmax()
+            .into_option()
+            .unwrap_or_default();
+        let mut minmax_y = coordinates()
+            .map(|c| c.y)
+            .minmax()
+            .into_option()
+            .unwrap_or_default();
 
         let mut width = minmax_x.1 - minmax_x.0 + 1;
         let mut height = minmax_y.1 - minmax_y.0 + 1;
@@ -133,24 +177,18 @@ impl Cave {
         if let Some(floor_offset) = floor_offset {
             minmax_y.1 += floor_offset;
             height += floor_offset;
-            add_x_offset = ((2 * height + 2) - width) / 2;
+            // sand spreads at most one column per row, so the floor has to reach `height - 1`
+            // columns to both sides of the drop position
+            let spread = height - 1;
+            add_x_offset = ((2 * height + 2).saturating_sub(width) / 2)
+                .max(spread.saturating_sub(drop_position.x - minmax_x.0))
+                .max(spread.saturating_sub(minmax_x.1 - drop_position.x));
             width += add_x_offset * 2;
-            lines.push(vec![
-                (minmax_x.0 - add_x_offset, minmax_y.1).into(),
-                (minmax_x.1 + add_x_offset, minmax_y.1).into(),
-            ]);
         }
 
         drop_position.x = drop_position.x + add_x_offset - minmax_x.0;
         drop_position.y -= minmax_y.0;
-        let grid: Vec<Cell> = vec![Cell::Air; width * height];
 
-        let mut cave = Self {
-            grid,
-            height,
-            width,
-            drop_position,
-        };
         // shift input over to be properly zeroed
         for line in &mut lines {
             for c in line {
@@ -158,6 +196,13 @@ impl Cave {
                 c.y -= minmax_y.0;
             }
         }
+        if floor_offset.is_some() {
+            lines.push(vec![(0, height - 1).into(), (width - 1, height - 1).into()]);
+        }
+        let mut cave = Self {
+            grid: Grid::new(width, height, Cell::Air),
+            drop_position,
+        };
         for formation in lines {
             cave.draw_rock_line(&formation);
         }
@@ -167,12 +212,12 @@ impl Cave {
 
 impl Display for Cave {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for y in 0..self.height {
-            for x in 0..self.width {
+        for (y, row) in self.grid.rows().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
                 if self.drop_position == (x, y).into() {
                     write!(f, "+")?;
                 } else {
-                    write!(f, "{}", self.get_cell((x, y)))?;
+                    write!(f, "{cell}")?;
                 }
             }
             writeln!(f)?;
@@ -181,23 +226,7 @@ impl Display for Cave {
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
-pub struct Coordinate {
-    pub x: usize,
-    pub y: usize,
-}
-
-impl From<(usize, usize)> for Coordinate {
-    fn from((x, y): (usize, usize)) -> Self {
-        Self { x, y }
-    }
-}
-
-impl Display for Coordinate {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "({},{})", self.x, self.y)
-    }
-}
+pub type Coordinate = Vec2<usize>;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Cell {
@@ -218,14 +247,40 @@ impl Display for Cell {
 
 #[cfg(test)]
 pub mod tests {
-    use crate::util::read_example;
+    use crate::util::{read_example, ParseError};
 
     use super::*;
+
+    #[test]
+    fn it_locates_parse_errors() {
+        let input = read_example("day14.txt").replace("502,9", "50x,9");
+        let err = Day14::parse(&input)
+            .unwrap_err()
+            .downcast::<ParseError>()
+            .unwrap();
+        assert_eq!(
+            (2, 21, "503,4 -> 502,4 -> 50x,9 -> 494,9"),
+            (err.line, err.column, err.text.as_str())
+        );
+    }
+
+    #[test]
+    fn it_builds_caves_around_any_rocks() {
+        for (input, part1, part2) in [
+            ("508,56", 0, 3363),
+            ("0,5 -> 2,5", 0, 49),
+            ("400,3 -> 600,3", 9, 9),
+        ] {
+            assert_eq!(part1, Day14::solve_part1(input).unwrap(), "{input}");
+            assert_eq!(part2, Day14::solve_part2(input).unwrap(), "{input}");
+        }
+    }
+
     #[test]
     fn it_parses_rocks() {
         let input = read_example("day14.txt");
-        let cave = Cave::parse(&input, (500, 0), None);
-        let expected = r#"
+        let cave = Cave::parse(&input, (500, 0), None).unwrap();
+        let expected = r"
 ......+...
 ..........
 ..........
@@ -235,7 +290,7 @@ pub mod tests {
 ..###...#.
 ........#.
 ........#.
-#########."#
+#########."
             .trim();
         assert_eq!(expected, cave.to_string().trim());
     }
@@ -243,7 +298,7 @@ pub mod tests {
     #[test]
     fn it_drops_single_sand() {
         let input = read_example("day14.txt");
-        let mut cave = Cave::parse(&input, (500, 0), None);
+        let mut cave = Cave::parse(&input, (500, 0), None).unwrap();
         let position = cave.tick();
         assert_eq!(Some((6, 8).into()), position);
     }
@@ -251,7 +306,7 @@ pub mod tests {
     #[test]
     fn it_drops_two_sands() {
         let input = read_example("day14.txt");
-        let mut cave = Cave::parse(&input, (500, 0), None);
+        let mut cave = Cave::parse(&input, (500, 0), None).unwrap();
         cave.tick();
         let position = cave.tick();
         assert_eq!(Some((5, 8).into()), position);
@@ -260,7 +315,7 @@ pub mod tests {
     #[test]
     fn it_drops_saaand() {
         let input = read_example("day14.txt");
-        let mut cave = Cave::parse(&input, (500, 0), None);
+        let mut cave = Cave::parse(&input, (500, 0), None).unwrap();
         for _ in 0..22 {
             cave.tick();
         }
@@ -283,7 +338,7 @@ pub mod tests {
     #[test]
     fn it_drops_saaaaaaaaaaaaaaand() {
         let input = read_example("day14.txt");
-        let mut cave = Cave::parse(&input, (500, 0), None);
+        let mut cave = Cave::parse(&input, (500, 0), None).unwrap();
         for _ in 0..24 {
             assert!(cave.tick().is_some());
         }
@@ -293,14 +348,14 @@ pub mod tests {
     #[test]
     fn it_solves_part1() {
         let input = read_example("day14.txt");
-        let mut cave = Cave::parse(&input, (500, 0), None);
+        let mut cave = Cave::parse(&input, (500, 0), None).unwrap();
         assert_eq!(24, cave.count_resting_sand());
     }
 
     #[test]
     fn it_adds_floor() {
         let input = read_example("day14.txt");
-        let cave = Cave::parse(&input, (500, 0), Some(2));
+        let cave = Cave::parse(&input, (500, 0), Some(2)).unwrap();
         let expected = r"
 ..............+...........
 ..........................
@@ -323,9 +378,7 @@ pub mod tests {
     #[test]
     fn it_drops_on_floor() {
         let input = read_example("day14.txt");
-        let mut cave = Cave::parse(&input, (500, 0), Some(2));
-        let res = cave.count_resting_sand();
-        println!("{}", &cave);
-        assert_eq!(93, res);
+        let mut cave = Cave::parse(&input, (500, 0), Some(2)).unwrap();
+        assert_eq!(93, cave.count_resting_sand());
     }
 }
@@ -1,24 +1,37 @@
-use std::{
-    cmp::Ordering,
-    collections::HashSet,
-    fmt::Display,
-    ops::{AddAssign, Range},
-};
+use std::{cmp::Ordering, collections::HashSet, fmt::Display, ops::Range};
 
 use nom::{
     bytes::complete::tag,
-    combinator::{all_consuming, map},
+    combinator::map,
     sequence::{pair, preceded, separated_pair},
-    Finish, IResult,
+    IResult,
+};
+
+use crate::{
+    solution::Solution,
+    util::{parallel, parse, ParseError, Vec2},
 };
 
-use crate::util::read_input;
+pub struct Day15;
 
-pub fn solve() {
-    let input = read_input("day15.txt");
-    let sensors: Vec<Sensor> = input.lines().map(Sensor::parse).collect();
-    println!("Day 15 part 1: {}", coverage(2_000_000, &sensors));
-    println!("Day 15 part 2: {}", uncovered_spot2(4_000_000, &sensors));
+impl Solution for Day15 {
+    const DAY: u8 = 15;
+    type Input = Vec<Sensor>;
+    type Answer1 = usize;
+    type Answer2 = isize;
+
+    fn parse(input: &str) -> color_eyre::Result<Self::Input> {
+        Ok(parse::located_lines(input, Sensor::parse)?)
+    }
+
+    fn part1(sensors: &Self::Input) -> color_eyre::Result<usize> {
+        Ok(coverage(2_000_000, sensors))
+    }
+
+    fn part2(sensors: &Self::Input) -> color_eyre::Result<isize> {
+        uncovered_spot2(4_000_000, sensors)
+            .ok_or_else(|| color_eyre::eyre::eyre!("Every spot is covered"))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -28,32 +41,39 @@ pub struct Sensor {
 }
 
 impl Sensor {
-    pub const fn range(&self) -> usize {
-        self.position.manhattan_distance(&self.beacon)
+    #[must_use]
+    pub fn range(&self) -> usize {
+        self.position.manhattan_distance(self.beacon).unsigned_abs()
     }
     #[allow(clippy::cast_possible_wrap)]
     #[allow(clippy::range_plus_one)]
-    pub const fn range_at(&self, y: isize) -> Range<isize> {
+    #[must_use]
+    pub fn range_at(&self, y: isize) -> Range<isize> {
         let y_offset = self.position.y.abs_diff(y);
-        let Some(range_at_y) = self.range().checked_sub(y_offset) else {return 0..1;};
+        let Some(range_at_y) = self.range().checked_sub(y_offset) else {
+            return 0..1;
+        };
 
         (self.position.x - range_at_y as isize)..(self.position.x + range_at_y as isize + 1)
     }
 
-    pub fn parse(input: &str) -> Self {
-        all_consuming(map(
-            pair(
-                preceded(tag("Sensor at "), Point::parse_point),
-                preceded(tag(": closest beacon is at "), Point::parse_point),
+    /// # Errors
+    /// If the line does not describe a sensor
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        parse::nom(
+            input,
+            map(
+                pair(
+                    preceded(tag("Sensor at "), parse_point),
+                    preceded(tag(": closest beacon is at "), parse_point),
+                ),
+                |(position, beacon)| Self { position, beacon },
             ),
-            |(position, beacon)| Self { position, beacon },
-        ))(input)
-        .finish()
-        .unwrap()
-        .1
+        )
     }
 }
 
+#[must_use]
 pub fn coverage(y: isize, sensors: &[Sensor]) -> usize {
     let beacon_positions: HashSet<isize> = sensors
         .iter()
@@ -64,107 +84,75 @@ pub fn coverage(y: isize, sensors: &[Sensor]) -> usize {
     coverage.difference(&beacon_positions).count()
 }
 
-pub fn uncovered_spot2(max: isize, sensors: &[Sensor]) -> isize {
+/// The tuning frequency of the only spot within `0..=max` that no sensor covers, which has to be
+/// right outside of a sensor's range. The sensors' perimeters are walked in parallel.
+/// # Panics
+/// If a sensor's range does not fit into an `isize`
+#[must_use]
+pub fn uncovered_spot2(max: isize, sensors: &[Sensor]) -> Option<isize> {
     fn is_covered(point: &Point, sensors: &[Sensor]) -> bool {
         sensors
             .iter()
-            .any(|s| s.position.manhattan_distance(point) <= s.range())
+            .any(|s| s.position.manhattan_distance(*point).unsigned_abs() <= s.range())
     }
 
     const fn is_in_range(point: &Point, max: isize) -> bool {
         point.x >= 0 && point.x <= max && point.y >= 0 && point.y <= max
     }
 
-    for sensor in sensors {
-        let starting_point = Point {
-            x: sensor.position.x,
-            y: sensor.position.y
-                - std::convert::TryInto::<isize>::try_into(sensor.range()).unwrap()
-                - 1,
-        };
+    parallel::find_map(sensors, |sensor| {
+        let starting_point = Point::new(
+            sensor.position.x,
+            sensor.position.y - isize::try_from(sensor.range()).unwrap() - 1,
+        );
         let mut point = starting_point;
-        let mut direction = Point { x: 1, y: 1 };
+        let mut direction = Point::new(1, 1);
         loop {
             point += direction;
             if !is_covered(&point, sensors) && is_in_range(&point, max) {
-                return point.x * 4_000_000 + point.y;
-            };
+                return Some(point.x * 4_000_000 + point.y);
+            }
             if point == starting_point {
-                break;
+                return None;
             }
             match (
                 point.x.cmp(&sensor.position.x),
                 point.y.cmp(&sensor.position.y),
             ) {
-                (Ordering::Greater, Ordering::Equal) => {
-                    // We are at the right tip of the circumference
-                    direction = Point { x: -1, y: 1 };
-                }
-                (Ordering::Equal, Ordering::Greater) => {
-                    // We are at the bottom tip of the circumference
-                    direction = Point { x: -1, y: -1 };
-                }
-                (Ordering::Less, Ordering::Equal) => {
-                    // We are at the left tip of the circumference
-                    direction = Point { x: 1, y: -1 };
+                // We are at the right, bottom or left tip of the circumference
+                (Ordering::Greater | Ordering::Less, Ordering::Equal)
+                | (Ordering::Equal, Ordering::Greater) => {
+                    direction = direction.rotate_right();
                 }
                 _ => {}
             }
         }
-    }
-    todo!()
+    })
 }
 
 impl Display for Sensor {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "Sensor at {}: closest beacon is at {}",
-            self.position, self.beacon
+            "Sensor at x={}, y={}: closest beacon is at x={}, y={}",
+            self.position.x, self.position.y, self.beacon.x, self.beacon.y
         )
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
-pub struct Point {
-    pub x: isize,
-    pub y: isize,
-}
-
-impl Point {
-    pub fn parse_point(input: &str) -> IResult<&str, Self> {
-        map(
-            separated_pair(
-                preceded(tag("x="), nom::character::complete::i32),
-                tag(", "),
-                preceded(tag("y="), nom::character::complete::i32),
-            ),
-            |(x, y)| (x as isize, y as isize).into(),
-        )(input)
-    }
-
-    pub const fn manhattan_distance(&self, other: &Self) -> usize {
-        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
-    }
-}
-
-impl From<(isize, isize)> for Point {
-    fn from((x, y): (isize, isize)) -> Self {
-        Self { x, y }
-    }
-}
-
-impl Display for Point {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "x={}, y={}", self.x, self.y)
-    }
-}
-
-impl AddAssign for Point {
-    fn add_assign(&mut self, rhs: Self) {
-        self.x += rhs.x;
-        self.y += rhs.y;
-    }
+pub type Point = Vec2<isize>;
+
+/// # Errors
+/// If the input does not start with `x=…, y=…`
+pub fn parse_point(input: &str) -> IResult<&str, Point> {
+    map(
+        separated_pair(
+            preceded(tag("x="), nom::character::complete::i32),
+            tag(", "),
+            preceded(tag("y="), nom::character::complete::i32),
+        ),
+        |(x, y)| (x as isize, y as isize).into(),
+    )(input)
 }
 
 #[cfg(test)]
@@ -185,21 +173,31 @@ pub mod tests {
     #[test]
     fn it_parses_sensor() {
         let input = "Sensor at x=2, y=18: closest beacon is at x=-2, y=15";
-        let sensor = Sensor::parse(input);
+        let sensor = Sensor::parse(input).unwrap();
         assert_eq!(input, sensor.to_string());
     }
 
+    #[test]
+    fn it_locates_parse_errors() {
+        let input = read_example("day15.txt").replacen("x=13, y=2:", "x=13 y=2:", 1);
+        let err = Day15::parse(&input)
+            .unwrap_err()
+            .downcast::<ParseError>()
+            .unwrap();
+        assert_eq!((3, 15), (err.line, err.column));
+    }
+
     #[test]
     fn it_counts_coverage() {
         let input = read_example("day15.txt");
-        let sensors: Vec<Sensor> = input.lines().map(Sensor::parse).collect();
+        let sensors = Day15::parse(&input).unwrap();
         assert_eq!(26, coverage(10, &sensors));
     }
 
     #[test]
     fn it_finds_uncovered_spot() {
         let input = read_example("day15.txt");
-        let sensors: Vec<Sensor> = input.lines().map(Sensor::parse).collect();
-        assert_eq!(56_000_011, uncovered_spot2(20, &sensors));
+        let sensors = Day15::parse(&input).unwrap();
+        assert_eq!(Some(56_000_011), uncovered_spot2(20, &sensors));
     }
 }
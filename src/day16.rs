@@ -3,38 +3,55 @@ use std::{
     fmt::{Debug, Display},
 };
 
-use itertools::Itertools;
 use nom::{
     branch::alt,
     bytes::complete::tag,
-    combinator::{all_consuming, map},
+    combinator::map,
     multi::separated_list1,
     sequence::{pair, preceded, tuple},
-    Finish, IResult,
+    IResult,
 };
 
-use crate::util::read_input;
-
-pub fn solve() {
-    let input = read_input("day16.txt");
-    let vulcano = Vulcano::parse(&input);
-    println!(
-        "Day 16 part 1: {}",
-        vulcano.get_best_pressure_release(1, 30)
-    );
-    // println!(
-    //     "Day 16 part 2: {}",
-    //     vulcano.get_best_pressure_release(2, 26)
-    // );
+use crate::{
+    solution::Solution,
+    util::{
+        parse,
+        search::{self, AllPairs},
+        ParseError,
+    },
+};
+
+pub struct Day16;
+
+impl Solution for Day16 {
+    const DAY: u8 = 16;
+    type Input = Vulcano;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> color_eyre::Result<Self::Input> {
+        Ok(Vulcano::parse(input)?)
+    }
+
+    fn part1(vulcano: &Self::Input) -> color_eyre::Result<usize> {
+        Ok(vulcano.get_best_pressure_release(1, 30))
+    }
+
+    fn part2(vulcano: &Self::Input) -> color_eyre::Result<usize> {
+        Ok(vulcano.get_best_pressure_release(2, 26))
+    }
 }
 
+/// The valves with a flow rate are combined as a bitmask with an entry for every subset,
+/// and two actors try every split of each subset, so this many already take a while
+pub const MAX_ACTIVE_VALVES: usize = 16;
+
 #[derive(Debug)]
 pub struct Vulcano {
     tunnels: Vec<Tunnel>,
     active_valves: Vec<Name>,
     flow_rates: HashMap<Name, usize>,
     valve_names: Vec<Name>,
-    all_valves: usize,
 }
 ///
 /// Idea: first get the shortest distance from/to every valve
@@ -42,220 +59,144 @@ pub struct Vulcano {
 /// So basically, first do a minimizing Dijkstra
 /// And then a maximising one
 impl Vulcano {
-    fn parse(input: &str) -> Self {
+    fn parse(input: &str) -> Result<Self, ParseError> {
         //let mut valves = Vec::with_capacity(input.lines().count());
         let mut tunnels: Vec<Tunnel> = vec![];
         let mut active_valves = Vec::with_capacity(input.lines().count());
         let mut flow_rates: HashMap<Name, usize> = HashMap::new();
         let mut valve_names = Vec::with_capacity(input.lines().count());
-        for line in input.trim().lines() {
-            let (valve, targets) = Valve::parse(line);
+        for (i, line) in input.trim_end().lines().enumerate() {
+            let (valve, targets) = Valve::parse(line).map_err(|err| err.offset(i))?;
             //valves.push(valve);
             if valve.flow_rate > 0 {
+                if active_valves.len() == MAX_ACTIVE_VALVES {
+                    let rate = line
+                        .find("rate=")
+                        .map_or("", |at| &line[at + "rate=".len()..]);
+                    let message =
+                        format!("At most {MAX_ACTIVE_VALVES} valves can have a flow rate");
+                    return Err(ParseError::at(line, rate, message).offset(i));
+                }
                 active_valves.push(valve.name);
             }
             flow_rates.insert(valve.name, valve.flow_rate.into());
             valve_names.push(valve.name);
             for target in &targets {
-                tunnels.push(Tunnel {
-                    from: valve.name,
-                    to: *target,
-                });
+                tunnels.push(Tunnel::new(valve.name, *target));
             }
         }
-        let all_valves = flow_rates.values().sum();
-
-        Self {
+        Ok(Self {
             tunnels,
             active_valves,
             flow_rates,
             valve_names,
-            all_valves,
-        }
+        })
     }
 
-    fn get_distances(&self) -> HashMap<Tunnel, (usize, Name)> {
-        let mut distances: HashMap<Tunnel, (usize, Name)> = HashMap::default();
-        for tunnel in &self.tunnels {
-            distances.insert(*tunnel, (1, tunnel.to));
-        }
-        for valve in &self.valve_names {
-            distances.insert(
-                Tunnel {
-                    from: *valve,
-                    to: *valve,
-                },
-                (0, *valve),
-            );
+    fn get_distances(&self) -> AllPairs<Name, usize> {
+        search::all_pairs(self.valve_names.iter().copied(), |valve| {
+            self.tunnels
+                .iter()
+                .filter(move |tunnel| tunnel.from == valve)
+                .map(|tunnel| (tunnel.to, 1))
+        })
+    }
+
+    /// The most pressure `num_actors` actors starting at `AA` can release in `remaining_time` minutes.
+    ///
+    /// First finds, for every set of valves, the best a single actor can do opening (a subset of) it.
+    /// As actors never need to open the same valve, the best of several actors is then the best
+    /// combination of disjoint sets.
+    fn get_best_pressure_release(&self, num_actors: usize, remaining_time: usize) -> usize {
+        if num_actors == 0 {
+            return 0;
         }
-        for k in 0..self.valve_names.len() {
-            for i in 0..self.valve_names.len() {
-                for j in 0..self.valve_names.len() {
-                    let ik = *distances
-                        .get(&Tunnel {
-                            from: self.valve_names[i],
-                            to: self.valve_names[k],
-                        })
-                        .unwrap_or(&(usize::MAX, (' ', ' ').into()));
-                    let kj = *distances
-                        .get(&Tunnel {
-                            from: self.valve_names[k],
-                            to: self.valve_names[j],
-                        })
-                        .unwrap_or(&(usize::MAX, (' ', ' ').into()));
-                    let ij = distances
-                        .entry(Tunnel {
-                            from: self.valve_names[i],
-                            to: self.valve_names[j],
-                        })
-                        .or_insert((usize::MAX, (' ', ' ').into()));
-                    if ij.0 > ik.0.saturating_add(kj.0) {
-                        ij.0 = ik.0 + kj.0;
-                        ij.1 = ik.1;
-                    }
-                }
-            }
+        let best = self.get_best_pressure_per_valve_set(remaining_time);
+        let full = best.len() - 1;
+        let mut combined = best.clone();
+        for _ in 1..num_actors {
+            combined = (0..=full)
+                .map(|mask| {
+                    submasks(mask)
+                        .map(|subset| combined[subset] + best[mask ^ subset])
+                        .max()
+                        .unwrap_or_default()
+                })
+                .collect();
         }
-        distances
+        combined[full]
     }
 
-    fn get_best_pressure_release(&self, num_actors: usize, remaining_time: usize) -> usize {
-        let mut best = 0;
+    /// The most pressure a single actor can release by opening only valves of a set,
+    /// indexed by a bitmask over `active_valves`
+    fn get_best_pressure_per_valve_set(&self, remaining_time: usize) -> Vec<usize> {
         let dist = self.get_distances();
-        let start: Name = *self
-            .valve_names
-            .iter()
-            .find(|v| v == &&('A', 'A').into())
-            .unwrap();
-        self._get_best_pressure_release_with_elephant(
-            &dist,
-            &vec![
-                Actor {
-                    next_action: remaining_time,
-                    position: start,
-                };
-                num_actors
-            ],
-            &mut HashMap::default(),
-            remaining_time,
-            &mut best,
-        );
-        best
-    }
-    
-    fn _get_best_pressure_release_with_elephant(
-        &self,
-        dist: &HashMap<Tunnel, (usize, Name)>,
-        actors: &[Actor],
-        opened_valves: &mut HashMap<Name, usize>,
-        remaining_time: usize,
-        best_flow: &mut usize,
-    ) {
-        // dbg!(&actors);
-        if actors.len() > 2 {
-            unimplemented!()
-        }
-        let sum = opened_valves
+        let distance = |from: Name, to: Name| dist.distance(from, to).unwrap_or(usize::MAX);
+        let start: Name = ('A', 'A').into();
+        let from_start: Vec<usize> = self
+            .active_valves
             .iter()
-            .map(|(valve, ticks)| self.flow_rates.get(valve).unwrap() * ticks)
-            .sum();
-        if remaining_time == 0 {
-            if sum > *best_flow {
-                *best_flow = sum;
-                // dbg!(&opened_valves);
-                // dbg!(best_flow);
-            }
-            return;
-        }
-        // Can we even reach the best value still?
-        if sum + self.all_valves * (remaining_time - 1) < *best_flow {
-            return;
-        }
-        let new_valves: Vec<Name> = actors
+            .map(|&to| distance(start, to))
+            .collect();
+        let between: Vec<Vec<usize>> = self
+            .active_valves
             .iter()
-            .filter(|actor| {
-                actor.next_action == remaining_time
-                    && self.flow_rates.get(&actor.position).unwrap() > &0
+            .map(|&from| {
+                self.active_valves
+                    .iter()
+                    .map(|&to| distance(from, to))
+                    .collect()
             })
-            .map(|actor| actor.position)
             .collect();
-        for v in &new_valves {
-            opened_valves.insert(*v, remaining_time);
-        }
-        let more_than_one = actors.len() > 1;
-        for actors_new in actors
+        let flow_rates: Vec<usize> = self
+            .active_valves
             .iter()
-            .map(|actor| {
-                if actor.next_action == remaining_time {
-                    self._get_actions(dist, actor.position, opened_valves, remaining_time)
-                } else {
-                    vec![*actor]
-                }
-            })
-            .multi_cartesian_product()
-            .filter(|actors_new| !(more_than_one
-                && actors_new.iter().map(|actor| actor.position).all_equal()
-                && self.flow_rates.get(&actors_new[0].position).unwrap() != &0))
-        {
-            // dbg!(&actors_new);
-            let next_iteration = actors_new
-                .iter()
-                .map(|actor| actor.next_action)
-                .max()
-                .unwrap();
-            // dbg!(&next_iteration);
-            self._get_best_pressure_release_with_elephant(
-                dist,
-                &actors_new,
-                opened_valves,
-                next_iteration,
-                best_flow,
-            );
-        }
-        // }
-
-        for v in &new_valves {
-            opened_valves.remove(v);
-        }
-    }
+            .map(|valve| self.flow_rates[valve])
+            .collect();
 
-    fn _get_actions(
-        &self,
-        dist: &HashMap<Tunnel, (usize, Name)>,
-        current_position: Name,
-        opened_valves: &HashMap<Name, usize>,
-        remaining_time: usize,
-    ) -> Vec<Actor> {
-        let mut remaining_valves: Vec<_> = self
-            .active_valves
+        let mut best = vec![0; 1 << self.active_valves.len()];
+        let mut stack: Vec<(usize, usize, usize, usize)> = from_start
             .iter()
-            .filter(|v| {
-                !opened_valves.contains_key(v)
-                    && dist.get(&Tunnel::new(current_position, **v)).unwrap().0 < remaining_time
-                // ???
-            })
-            .map(|v| Actor {
-                next_action: remaining_time.saturating_sub(
-                    1_usize + dist.get(&Tunnel::new(current_position, *v)).unwrap().0,
-                ),
-                position: *v,
+            .enumerate()
+            .filter(|(_, &d)| d < remaining_time)
+            .map(|(valve, d)| {
+                let remaining = remaining_time - d - 1;
+                (valve, 1 << valve, remaining, remaining * flow_rates[valve])
             })
             .collect();
-        remaining_valves.push(Actor {
-            position: *self
-                .valve_names
-                .iter()
-                .find(|v| v == &&('A', 'A').into())
-                .unwrap(),
-            next_action: 0,
-        });
-        remaining_valves.sort_unstable_by_key(|v| {
-            usize::MAX - (self.flow_rates.get(&v.position).unwrap() * v.next_action)
-        });
-        remaining_valves
+        while let Some((valve, opened, remaining, pressure)) = stack.pop() {
+            best[opened] = best[opened].max(pressure);
+            for (next, &d) in between[valve].iter().enumerate() {
+                if opened & (1 << next) == 0 && d < remaining {
+                    let remaining = remaining - d - 1;
+                    stack.push((
+                        next,
+                        opened | 1 << next,
+                        remaining,
+                        pressure + remaining * flow_rates[next],
+                    ));
+                }
+            }
+        }
+        // A set is at least as good as any of its subsets
+        for valve in 0..self.active_valves.len() {
+            for mask in 0..best.len() {
+                if mask & (1 << valve) != 0 {
+                    best[mask] = best[mask].max(best[mask ^ (1 << valve)]);
+                }
+            }
+        }
+        best
     }
 }
 
+/// All subsets of a bitmask, including the mask itself and the empty set
+fn submasks(mask: usize) -> impl Iterator<Item = usize> {
+    std::iter::successors(Some(mask), move |&subset| {
+        (subset != 0).then(|| (subset - 1) & mask)
+    })
+}
+
 #[derive(Clone, Hash, PartialEq, Eq, Copy)]
 pub struct Name([char; 2]);
 
@@ -315,7 +256,7 @@ impl Tunnel {
 }
 
 impl Valve {
-    fn parse(input: &str) -> (Self, Vec<Name>) {
+    fn parse(input: &str) -> Result<(Self, Vec<Name>), ParseError> {
         fn _name_parser(input: &str) -> IResult<&str, Name> {
             map(
                 pair(
@@ -340,32 +281,56 @@ impl Valve {
             ))(input)
         }
 
-        let (name, flow_rate, tunnels) = all_consuming(_parse)(input).finish().unwrap().1;
-        (
+        let (name, flow_rate, tunnels) = parse::nom(input, _parse)?;
+        Ok((
             Self {
                 name,
                 flow_rate: u16::from(flow_rate),
             },
             tunnels,
-        )
+        ))
     }
 }
 
-#[derive(Debug, Copy, Clone)]
-pub struct Actor {
-    next_action: usize,
-    position: Name,
-}
-
 #[cfg(test)]
 pub mod tests {
     use crate::util::read_example;
 
     use super::*;
+    #[test]
+    fn it_locates_parse_errors() {
+        let input = read_example("day16.txt").replace("rate=13", "rate=-13");
+        let err = Day16::parse(&input)
+            .unwrap_err()
+            .downcast::<ParseError>()
+            .unwrap();
+        assert_eq!((2, 24), (err.line, err.column));
+    }
+
+    #[test]
+    fn it_limits_the_valves_with_a_flow_rate() {
+        let name = |i: usize| format!("V{}", char::from(b'A' + u8::try_from(i).unwrap()));
+        let valves: Vec<String> = (0..=MAX_ACTIVE_VALVES)
+            .map(|i| {
+                format!(
+                    "Valve {} has flow rate=1; tunnel leads to valve {}",
+                    name(i),
+                    name(i.saturating_sub(1))
+                )
+            })
+            .collect();
+        let err = Day16::parse(&valves.join("\n"))
+            .unwrap_err()
+            .downcast::<ParseError>()
+            .unwrap();
+        assert_eq!((MAX_ACTIVE_VALVES + 1, 24), (err.line, err.column));
+        assert!(Day16::parse(&valves[..MAX_ACTIVE_VALVES].join("\n")).is_ok());
+    }
+
     #[test]
     fn it_parses_valve() {
         let input = "Valve AA has flow rate=0; tunnels lead to valves DD, II, BB";
-        let (valve, tunnels) = Valve::parse(input);
+        let (valve, tunnels) = Valve::parse(input).unwrap();
         assert_eq!(
             Valve {
                 name: ('A', 'A').into(),
@@ -386,57 +351,79 @@ pub mod tests {
     #[test]
     fn it_parses_vulcano() {
         let input = read_example("day16.txt");
-        let vulcano = Vulcano::parse(&input);
+        let vulcano = Vulcano::parse(&input).unwrap();
         assert_eq!(10, vulcano.valve_names.len());
     }
 
     #[test]
     fn it_finds_shortest_paths() {
         let input = read_example("day16.txt");
-        let vulcano = Vulcano::parse(&input);
+        let vulcano = Vulcano::parse(&input).unwrap();
         let distances = vulcano.get_distances();
         assert_eq!(
-            distances
-                .get(&Tunnel::new(('J', 'J'), ('E', 'E')))
-                .unwrap()
-                .0,
-            4
+            Some(4),
+            distances.distance(('J', 'J').into(), ('E', 'E').into())
         );
         assert_eq!(
-            distances
-                .get(&Tunnel::new(('J', 'J'), ('H', 'H')))
-                .unwrap()
-                .0,
-            7
+            Some(7),
+            distances.distance(('J', 'J').into(), ('H', 'H').into())
         );
     }
 
     #[test]
     fn it_finds_optimal_pressure_release() {
         let input = read_example("day16.txt");
-        let vulcano = Vulcano::parse(&input);
+        let vulcano = Vulcano::parse(&input).unwrap();
         assert_eq!(1651, vulcano.get_best_pressure_release(1, 30));
     }
 
     #[test]
     fn it_gets_next_step_for_shortest_path() {
         let input = read_example("day16.txt");
-        let vulcano = Vulcano::parse(&input);
+        let vulcano = Vulcano::parse(&input).unwrap();
         let distances = vulcano.get_distances();
         assert_eq!(
-            distances.get(&Tunnel::new(('J', 'J'), ('E', 'E'))).unwrap(),
-            &(4, ('I', 'I').into())
+            Some(('I', 'I').into()),
+            distances.next_hop(('J', 'J').into(), ('E', 'E').into())
         );
         assert_eq!(
-            distances.get(&Tunnel::new(('J', 'J'), ('H', 'H'))).unwrap(),
-            &(7, ('I', 'I').into())
+            Some(('I', 'I').into()),
+            distances.next_hop(('J', 'J').into(), ('H', 'H').into())
+        );
+        assert_eq!(
+            8,
+            distances
+                .path(('J', 'J').into(), ('H', 'H').into())
+                .unwrap()
+                .len()
         );
     }
 
     #[test]
     fn it_finds_optimal_pressure_release_with_elephant() {
         let input = read_example("day16.txt");
-        let vulcano = Vulcano::parse(&input);
+        let vulcano = Vulcano::parse(&input).unwrap();
         assert_eq!(1707, vulcano.get_best_pressure_release(2, 26));
     }
+
+    #[test]
+    fn it_finds_optimal_pressure_release_with_more_actors() {
+        let input = read_example("day16.txt");
+        let vulcano = Vulcano::parse(&input).unwrap();
+        assert_eq!(0, vulcano.get_best_pressure_release(0, 26));
+        let releases: Vec<usize> = (1..=6)
+            .map(|actors| vulcano.get_best_pressure_release(actors, 26))
+            .collect();
+        assert!(releases.windows(2).all(|w| w[0] <= w[1]), "{releases:?}");
+        // with an actor per valve, every valve is opened as soon as it can be reached
+        assert_eq!(releases[5], vulcano.get_best_pressure_release(8, 26));
+    }
+
+    #[test]
+    fn it_enumerates_submasks() {
+        assert_eq!(
+            vec![0b101, 0b100, 0b001, 0],
+            submasks(0b101).collect::<Vec<_>>()
+        );
+    }
 }
@@ -4,16 +4,32 @@ use std::{
     str::FromStr,
 };
 
-use itertools::Itertools;
+use crate::{
+    solution::Solution,
+    util::{cycle, Direction, ParseError},
+};
+
+pub struct Day17;
+
+impl Solution for Day17 {
+    const DAY: u8 = 17;
+    type Input = Jet;
+    type Answer1 = usize;
+    type Answer2 = usize;
 
-use crate::util::{read_input, Direction};
+    fn parse(input: &str) -> color_eyre::Result<Self::Input> {
+        Jet::from_str(input)
+    }
 
-pub fn solve() -> color_eyre::Result<()> {
-    let input = read_input("day17.txt");
-    let mut chamber = Chamber::new(Jet::from_str(&input)?);
-    chamber.drop_rocks(2022);
-    println!("Day 17 part 1: {}", chamber.get_highest_occupied_row());
-    Ok(())
+    fn part1(jet: &Self::Input) -> color_eyre::Result<usize> {
+        let mut chamber = Chamber::new(jet.clone());
+        chamber.drop_rocks(2022);
+        Ok(chamber.get_highest_occupied_row())
+    }
+
+    fn part2(jet: &Self::Input) -> color_eyre::Result<usize> {
+        Chamber::new(jet.clone()).drop_many_rocks(1_000_000_000_000)
+    }
 }
 
 #[derive(Clone, PartialEq, Eq, Default)]
@@ -28,7 +44,7 @@ impl Rock {
         }
     }
 
-    fn height(&self) -> usize {
+    const fn height(&self) -> usize {
         self.shape.len()
     }
 
@@ -142,12 +158,24 @@ impl FromStr for Jet {
     type Err = color_eyre::Report;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let jets = s.trim();
+        let directions = jets
+            .char_indices()
+            .map(|(i, c)| match c {
+                '<' => Ok(Direction::Left),
+                '>' => Ok(Direction::Right),
+                _ => Err(ParseError::at(
+                    s,
+                    &jets[i..],
+                    format!("Expected '<' or '>', got '{c}'"),
+                )),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if directions.is_empty() {
+            return Err(color_eyre::eyre::eyre!("There are no jets"));
+        }
         Ok(Self {
-            directions: s
-                .trim()
-                .chars()
-                .map(|c| format!("{c}").parse())
-                .collect::<color_eyre::Result<Vec<_>>>()?,
+            directions,
             index: 0,
         })
     }
@@ -172,6 +200,22 @@ impl Iterator for Rocks {
     }
 }
 
+/// Columns deeper than this below the top of the tower count as equally deep in a [`State`].
+/// Without a limit, a column that never fills (like the right one if every jet pushes left)
+/// would get deeper with every rock, and the states would never repeat.
+const MAX_SURFACE_DEPTH: usize = 64;
+/// [`Chamber::drop_many_rocks`] gives up if the states have not repeated after this many rocks
+const MAX_ROCKS_TO_CYCLE: usize = 100_000;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct State {
+    rock: usize,
+    jet: usize,
+    /// The depth of the highest rock in every column, from the top of the tower,
+    /// up to [`MAX_SURFACE_DEPTH`]
+    surface: [usize; 7],
+}
+
 #[derive(Default, Debug)]
 pub struct Chamber {
     rocks: Rocks,
@@ -199,77 +243,48 @@ impl Chamber {
         }
     }
 
-    #[allow(unused)]
-    fn drop_many_rocks(&mut self, amount: usize) {
-        let original_rocks = self.rocks.clone();
-        let cycle_length = self.rocks.rocks.len() * self.jet.directions.len();
-        let mut i = 0;
-        let mut previous_cycle_start = 1;
-        let mut the_one_before_that = 1;
-        loop {
-            if i == amount {
-                return;
+    /// The height of the tower after dropping `amount` rocks.
+    ///
+    /// Once the falling rock, the position in the jet pattern and the shape of the surface repeat,
+    /// so does everything after it. The height is then extrapolated from the cycle
+    /// instead of dropping every rock, so the chamber only holds the rocks dropped until then.
+    /// # Errors
+    /// If there is no cycle within [`MAX_ROCKS_TO_CYCLE`] rocks
+    fn drop_many_rocks(&mut self, amount: usize) -> color_eyre::Result<usize> {
+        let mut detector = cycle::Detector::new();
+        let mut heights = vec![self.get_highest_occupied_row()];
+        for dropped in 1..=amount {
+            if dropped > MAX_ROCKS_TO_CYCLE {
+                return Err(color_eyre::eyre::eyre!(
+                    "The tower does not repeat within {MAX_ROCKS_TO_CYCLE} rocks"
+                ));
             }
-            // if i >= cycle_length && amount - i > cycle_length {
-            //     if i == cycle_length {
-            //         let new_rock: Vec<Row> = self.grid[1..]
-            //             .iter()
-            //             .take_while(|row| !row.is_empty())
-            //             .map(|row| Row::from(row.0 & 0b1111_1110))
-            //             .collect();
-            //         self.rocks.rocks = vec![Rock::new(new_rock)];
-            //         //println!("{:?}", self.rocks.rocks[0]);
-            //         self.rocks.index = 0;
-
-            //     }
-            //     previous_cycle_start = self.get_highest_occupied_row() + 1;
-            //     self.spawn();
-            //     while self.current.is_some() {
-            //         self.gravity();
-            //     }
-            //     i += cycle_length;
-                
-            //     if i % 1_000_000 == 0 {
-            //         println!("{i}");
-            //     }
-            // } else {
-                if self.rocks.rocks.len() == 1 {
-                    self.rocks = original_rocks.clone();
-                }
-                self.drop_rocks(1);
-                i += 1;
-                if i % cycle_length == 0 && i > 0 && i < amount - cycle_length {
-                    // println!("Check for cycles in round {i} out of {amount}");
-                    let new_rows: Vec<_> = self.grid[previous_cycle_start..=self.get_highest_occupied_row()].to_vec();
-                    if self.grid[1..previous_cycle_start].ends_with(&new_rows) {
-                        println!("Found cycle after {i} rounds. From: {previous_cycle_start} to including {}", self.get_highest_occupied_row());
-                        println!("{:?}", self.grid[previous_cycle_start]);
-                        println!("{:?}", self.grid[self.get_highest_occupied_row()]);
-                        
-                        while self.get_highest_occupied_row() > self.grid.len() - 1{
-                            self.grid.pop();
-                        }
-                        while i < amount - cycle_length {
-                            self.grid.append(&mut new_rows.clone());
-                            i += cycle_length;
-                        }
-                    }
-                    if the_one_before_that > 1 && previous_cycle_start > 1 {
-                        let needle = &self.grid[the_one_before_that..=previous_cycle_start];
-                        // if let Some(u) = self.grid[1..the_one_before_that].windows(needle.len()).position(|window| window == needle) {
-                        if self.grid[..(the_one_before_that-11)].ends_with(needle) {
-                            // dbg!(u);
-                            dbg!(needle);
-                            std::fs::write("cycle.txt", format!("{:?}", needle.iter().rev().collect_vec())).unwrap();
-                            std::fs::write("chamber.txt", format!("{self}")).unwrap();
-                            return;
-                        }
-                    }
-                    // dbg!(previous_cycle_start, self.get_highest_occupied_row());
-                    the_one_before_that = previous_cycle_start;
-                    previous_cycle_start = self.get_highest_occupied_row();
-                }
-            // }
+            self.drop_rocks(1);
+            heights.push(self.get_highest_occupied_row());
+            if let Some(cycle) = detector.observe(dropped, self.state()) {
+                return Ok(cycle.extrapolate(&heights, amount));
+            }
+        }
+        Ok(heights[amount])
+    }
+
+    /// What decides where the next rocks will land: the next rock and jet, and how deep every column is
+    fn state(&self) -> State {
+        let top = self.get_highest_occupied_row();
+        let mut surface = [0; 7];
+        for (column, depth) in surface.iter_mut().enumerate() {
+            let mask = 0b1000_0000 >> column;
+            *depth = self.grid[..=top]
+                .iter()
+                .rev()
+                .take(MAX_SURFACE_DEPTH)
+                .position(|row| row.0 & mask != 0)
+                .unwrap_or(MAX_SURFACE_DEPTH);
+        }
+        State {
+            rock: self.rocks.index,
+            jet: self.jet.index,
+            surface,
         }
     }
 
@@ -302,8 +317,7 @@ impl Chamber {
             .enumerate()
             .rev()
             .find(|(_, x)| !x.is_empty())
-            .unwrap_or((0, &Row::default()))
-            .0
+            .map_or(0, |(i, _)| i)
     }
 
     fn jet(&mut self) {
@@ -352,10 +366,12 @@ impl Display for Chamber {
         // }
         for (i, row) in self.grid.iter().enumerate().skip(1).rev() {
             let mut static_row = format!("{row:?}");
-            if let Some((rock, y)) = self.current.as_ref() && i >= *y && i < *y + rock.height() {
-                for (char_index, c) in format!("{:?}", rock.shape[i - *y]).char_indices() {
-                    if c == '#' && char_index < 7 {
-                        static_row.replace_range(char_index..=char_index, "@");
+            if let Some((rock, y)) = self.current.as_ref() {
+                if i >= *y && i < *y + rock.height() {
+                    for (char_index, c) in format!("{:?}", rock.shape[i - *y]).char_indices() {
+                        if c == '#' && char_index < 7 {
+                            static_row.replace_range(char_index..=char_index, "@");
+                        }
                     }
                 }
             }
@@ -372,6 +388,20 @@ pub mod tests {
 
     use super::*;
 
+    #[test]
+    fn it_locates_parse_errors() {
+        let err = Jet::from_str(">>><x<>")
+            .unwrap_err()
+            .downcast::<ParseError>()
+            .unwrap();
+        assert_eq!(
+            ParseError::new(1, 5, ">>><x<>", "Expected '<' or '>', got 'x'"),
+            err
+        );
+        assert!(Jet::from_str(">>^<").is_err());
+        assert!(Jet::from_str("\n").is_err());
+    }
+
     #[test]
     fn it_displays_rock() {
         let rock = Rock::new(vec![0b1111_0000]);
@@ -455,17 +485,65 @@ pub mod tests {
     fn it_drops_rocks() {
         let mut chamber = Chamber::new(Jet::from_str(&read_example("day17.txt")).unwrap());
         chamber.drop_rocks(2022);
-        
+
         assert_eq!(3068, chamber.get_highest_occupied_row());
     }
 
-    #[ignore = "Doesn't work yet"]
     #[test]
     fn it_drops_more_rocks() {
-        let mut chamber = Chamber::new(Jet::from_str(&read_example("day17.txt")).unwrap());
-        chamber.drop_many_rocks(20220);
-        std::fs::write("test.txt",format!("{chamber}")).unwrap();
-        assert_eq!(30624, chamber.get_highest_occupied_row());
+        let jet = Jet::from_str(&read_example("day17.txt")).unwrap();
+        for amount in [0, 1, 2022, 5000, 20220] {
+            let mut chamber = Chamber::new(jet.clone());
+            chamber.drop_rocks(amount);
+            assert_eq!(
+                chamber.get_highest_occupied_row(),
+                Chamber::new(jet.clone()).drop_many_rocks(amount).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn it_drops_a_trillion_rocks() {
+        let jet = Jet::from_str(&read_example("day17.txt")).unwrap();
+        assert_eq!(
+            1_514_285_714_288,
+            Chamber::new(jet)
+                .drop_many_rocks(1_000_000_000_000)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn it_drops_a_trillion_rocks_with_columns_that_never_fill() {
+        for jets in ["<", "<<>", ">"] {
+            let jet = Jet::from_str(jets).unwrap();
+            let mut chamber = Chamber::new(jet.clone());
+            chamber.drop_rocks(5000);
+            assert_eq!(
+                chamber.get_highest_occupied_row(),
+                Chamber::new(jet.clone()).drop_many_rocks(5000).unwrap()
+            );
+            assert!(
+                Chamber::new(jet).drop_many_rocks(1_000_000_000_000).is_ok(),
+                "{jets}"
+            );
+        }
+        // every jet pushes left, so the five rocks stack up the same way over and over
+        let heights: Vec<usize> = [1000, 1005]
+            .into_iter()
+            .map(|amount| {
+                let mut chamber = Chamber::new(Jet::from_str("<").unwrap());
+                chamber.drop_rocks(amount);
+                chamber.get_highest_occupied_row()
+            })
+            .collect();
+        let expected = heights[0] + (1_000_000_000_000 - 1000) / 5 * (heights[1] - heights[0]);
+        assert_eq!(
+            expected,
+            Chamber::new(Jet::from_str("<").unwrap())
+                .drop_many_rocks(1_000_000_000_000)
+                .unwrap()
+        );
     }
 
     #[test]
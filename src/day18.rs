@@ -1,63 +1,55 @@
-use std::{
-    collections::{HashSet, VecDeque},
-    str::FromStr,
-};
+use std::collections::{HashSet, VecDeque};
 
 use itertools::Itertools;
 
-use crate::util::read_input;
+use crate::{
+    solution::Solution,
+    util::{parse, ParseError, Vec3},
+};
 
-pub fn solve() {
-    let input = read_input("day18.txt");
-    let volcano = Volcano::new(&input);
-    println!("Day 18 part 1: {}", volcano.surface_area());
-    println!("Day 18 part 2: {}", volcano.exterior_surface_area());
-}
+pub struct Day18;
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
-pub struct Point3 {
-    x: u8,
-    y: u8,
-    z: u8,
-}
+impl Solution for Day18 {
+    const DAY: u8 = 18;
+    type Input = Volcano;
+    type Answer1 = usize;
+    type Answer2 = usize;
 
-impl FromStr for Point3 {
-    type Err = color_eyre::Report;
+    fn parse(input: &str) -> color_eyre::Result<Self::Input> {
+        Volcano::parse(input)
+    }
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let v = s
-            .trim()
-            .split(',')
-            .map(|v| {
-                v.parse()
-                    .map_err(|err| color_eyre::eyre::eyre!("Cannot parse number: {err}"))
-            })
-            .collect::<color_eyre::Result<Vec<_>>>()?;
-        Ok(Self {
-            x: v[0],
-            y: v[1],
-            z: v[2],
-        })
+    fn part1(volcano: &Self::Input) -> color_eyre::Result<usize> {
+        Ok(volcano.surface_area())
     }
-}
-impl Point3 {
-    pub const fn manhattan_distance(self, other: Self) -> u32 {
-        self.x.abs_diff(other.x) as u32
-            + self.y.abs_diff(other.y) as u32
-            + self.z.abs_diff(other.z) as u32
+
+    fn part2(volcano: &Self::Input) -> color_eyre::Result<usize> {
+        Ok(volcano.exterior_surface_area())
     }
+}
 
-    // pub fn neighbours(&self) -> Vec<Self> {
-    //     (0..3)
-    //         .map(|_| (0..=2))
-    //         .multi_cartesian_product()
-    //         .map(|offset| Self {
-    //             x: self.x + offset[0] - 1,
-    //             y: self.y + offset[1] - 1,
-    //             z: self.z + offset[2] - 1,
-    //         })
-    //         .collect()
-    // }
+pub type Point3 = Vec3<u8>;
+
+/// # Errors
+/// If the line is not three comma separated coordinates
+pub fn parse_point(line: &str) -> Result<Point3, ParseError> {
+    let mut coordinates = Vec::with_capacity(3);
+    let mut column = 1;
+    for coordinate in line.split(',') {
+        let message = |err| format!("Cannot parse number: {err}");
+        coordinates.push(
+            coordinate
+                .trim()
+                .parse()
+                .map_err(|err| ParseError::new(1, column, line, message(err)))?,
+        );
+        column += coordinate.chars().count() + 1;
+    }
+    let [x, y, z] = coordinates[..] else {
+        let message = format!("Expected three coordinates, got {}", coordinates.len());
+        return Err(ParseError::new(1, 1, line, message));
+    };
+    Ok(Point3::new(x, y, z))
 }
 
 pub struct Volcano {
@@ -68,6 +60,7 @@ pub struct Volcano {
 }
 
 impl Volcano {
+    #[must_use]
     pub fn surface_area(&self) -> usize {
         let mut surface_area = self.droplets.len() * 6;
         for i in 0..self.droplets.len() {
@@ -80,77 +73,37 @@ impl Volcano {
         surface_area
     }
 
+    #[must_use]
     pub fn exterior_surface_area(&self) -> usize {
         let reachable_by_water = self.can_be_reached_by_water();
         let mut surface_area = 0;
         for droplet in &self.droplets {
-            let n = Point3 {
-                x: droplet.x - 1,
-                y: droplet.y,
-                z: droplet.z,
-            };
-            if reachable_by_water[self.p2i(n)] {
-                surface_area += 1;
-            }
-            let n = Point3 {
-                x: droplet.x + 1,
-                y: droplet.y,
-                z: droplet.z,
-            };
-            if reachable_by_water[self.p2i(n)] {
-                surface_area += 1;
-            }
-            let n = Point3 {
-                x: droplet.x,
-                y: droplet.y - 1,
-                z: droplet.z,
-            };
-            if reachable_by_water[self.p2i(n)] {
-                surface_area += 1;
-            }
-            let n = Point3 {
-                x: droplet.x,
-                y: droplet.y + 1,
-                z: droplet.z,
-            };
-            if reachable_by_water[self.p2i(n)] {
-                surface_area += 1;
-            }
-            let n = Point3 {
-                x: droplet.x,
-                y: droplet.y,
-                z: droplet.z - 1,
-            };
-            if reachable_by_water[self.p2i(n)] {
-                surface_area += 1;
-            }
-            let n = Point3 {
-                x: droplet.x,
-                y: droplet.y,
-                z: droplet.z + 1,
-            };
-            if reachable_by_water[self.p2i(n)] {
-                surface_area += 1;
-            }
+            surface_area += droplet
+                .neighbours()
+                .filter(|&n| reachable_by_water[self.p2i(n)])
+                .count();
         }
         surface_area
     }
 
-    pub fn new(input: &str) -> Self {
-        let droplets = input
-            .lines()
-            .map(Point3::from_str)
-            .collect::<color_eyre::Result<Vec<_>>>()
-            .unwrap();
-        let x_bounds = droplets.iter().map(|p| p.x).minmax().into_option().unwrap();
-        let y_bounds = droplets.iter().map(|p| p.y).minmax().into_option().unwrap();
-        let z_bounds = droplets.iter().map(|p| p.z).minmax().into_option().unwrap();
-        Self {
+    /// # Errors
+    /// If a line is not a droplet, or there are none
+    pub fn parse(input: &str) -> color_eyre::Result<Self> {
+        let droplets = parse::located_lines(input, parse_point)?;
+        let bounds = |axis: fn(&Point3) -> u8| {
+            droplets
+                .iter()
+                .map(axis)
+                .minmax()
+                .into_option()
+                .ok_or_else(|| color_eyre::eyre::eyre!("There are no droplets"))
+        };
+        Ok(Self {
+            x_bounds: bounds(|p| p.x)?,
+            y_bounds: bounds(|p| p.y)?,
+            z_bounds: bounds(|p| p.z)?,
             droplets,
-            x_bounds,
-            y_bounds,
-            z_bounds,
-        }
+        })
     }
 
     const fn p2i(&self, p: Point3) -> usize {
@@ -192,80 +145,21 @@ impl Volcano {
                 * (self.y_bounds.1 + 3 - self.y_bounds.0) as usize
                 * (self.z_bounds.1 + 3 - self.z_bounds.0) as usize
         ];
-        let root = Point3 {
-            x: self.x_bounds.0 - 1,
-            y: self.y_bounds.0 - 1,
-            z: self.z_bounds.0 - 1,
-        };
+        let root = Point3::new(
+            self.x_bounds.0 - 1,
+            self.y_bounds.0 - 1,
+            self.z_bounds.0 - 1,
+        );
         queue.push_back(root);
         let mut explored: HashSet<Point3> = HashSet::new();
         explored.insert(root);
         while let Some(v) = queue.pop_front() {
             can_be_reached[self.p2i(v)] = true;
-            if v.x > self.x_bounds.0 - 1 {
-                let n = Point3 {
-                    x: v.x - 1,
-                    y: v.y,
-                    z: v.z,
-                };
-                if !self.droplets.contains(&n) && explored.insert(n) {
-                    // new
-                    queue.push_back(n);
-                }
-            }
-            if v.y > self.y_bounds.0 - 1 {
-                let n = Point3 {
-                    x: v.x,
-                    y: v.y - 1,
-                    z: v.z,
-                };
-
-                if !self.droplets.contains(&n) && explored.insert(n) {
-                    // new
-                    queue.push_back(n);
-                }
-            }
-            if v.z > self.z_bounds.0 - 1 {
-                let n = Point3 {
-                    x: v.x,
-                    y: v.y,
-                    z: v.z - 1,
-                };
-                if !self.droplets.contains(&n) && explored.insert(n) {
-                    // new
-                    queue.push_back(n);
-                }
-            }
-            if v.x < self.x_bounds.1 + 1 {
-                let n = Point3 {
-                    x: v.x + 1,
-                    y: v.y,
-                    z: v.z,
-                };
-                if !self.droplets.contains(&n) && explored.insert(n) {
-                    // new
-                    queue.push_back(n);
-                }
-            }
-            if v.y < self.y_bounds.1 + 1 {
-                let n = Point3 {
-                    x: v.x,
-                    y: v.y + 1,
-                    z: v.z,
-                };
-                if !self.droplets.contains(&n) && explored.insert(n) {
-                    // new
-                    queue.push_back(n);
-                }
-            }
-            if v.z < self.z_bounds.1 + 1 {
-                let n = Point3 {
-                    x: v.x,
-                    y: v.y,
-                    z: v.z + 1,
-                };
-                if !self.droplets.contains(&n) && explored.insert(n) {
-                    // new
+            for n in v.neighbours() {
+                let in_bounds = (self.x_bounds.0 - 1..=self.x_bounds.1 + 1).contains(&n.x)
+                    && (self.y_bounds.0 - 1..=self.y_bounds.1 + 1).contains(&n.y)
+                    && (self.z_bounds.0 - 1..=self.z_bounds.1 + 1).contains(&n.z);
+                if in_bounds && !self.droplets.contains(&n) && explored.insert(n) {
                     queue.push_back(n);
                 }
             }
@@ -278,27 +172,36 @@ impl Volcano {
 pub mod tests {
     use itertools::Itertools;
 
-    use crate::util::read_example;
+    use crate::util::{read_example, read_input};
 
     use super::*;
 
     #[test]
     fn it_parses_point() {
-        let point: Point3 = "1,2,3".parse().unwrap();
-        assert_eq!(Point3 { x: 1, y: 2, z: 3 }, point);
+        let point = parse_point("1,2,3").unwrap();
+        assert_eq!(Point3::new(1, 2, 3), point);
+        assert!(parse_point("1,2").is_err());
+        assert_eq!(5, parse_point("1,2,x").unwrap_err().column);
+        let err = Day18::parse("1,2,3\n1,-2,3")
+            .err()
+            .unwrap()
+            .downcast::<ParseError>()
+            .unwrap();
+        assert_eq!((2, 3), (err.line, err.column));
+        assert!(Day18::parse("").is_err());
     }
 
     #[test]
     fn it_finds_surface_area() {
         let input = read_example("day18.txt");
-        let volcano = Volcano::new(&input);
+        let volcano = Volcano::parse(&input).unwrap();
         assert_eq!(64, volcano.surface_area());
     }
 
     #[test]
     fn get_bounds() {
-        let input = read_input("day18.txt");
-        let volcano = Volcano::new(&input);
+        let input = read_input("day18.txt").unwrap();
+        let volcano = Volcano::parse(&input).unwrap();
         dbg!(volcano.droplets.iter().map(|p| p.x).minmax());
         dbg!(volcano.droplets.iter().map(|p| p.y).minmax());
         dbg!(volcano.droplets.iter().map(|p| p.z).minmax());
@@ -307,12 +210,12 @@ pub mod tests {
     #[test]
     fn it_converts_point_to_index() {
         let input = read_example("day18.txt");
-        let volcano = Volcano::new(&input);
+        let volcano = Volcano::parse(&input).unwrap();
         let mut i = 0;
         for z in (volcano.z_bounds.0 - 1)..=(volcano.z_bounds.1 + 1) {
             for y in (volcano.y_bounds.0 - 1)..=(volcano.y_bounds.1 + 1) {
                 for x in (volcano.x_bounds.0 - 1)..=(volcano.x_bounds.1 + 1) {
-                    let point = Point3 { x, y, z };
+                    let point = Point3::new(x, y, z);
                     assert_eq!(volcano.p2i(point), i, "Point {point:?} => index {i}");
                     i += 1;
                 }
@@ -323,17 +226,17 @@ pub mod tests {
     #[test]
     fn it_finds_if_point_can_be_reached_by_water() {
         let input = read_example("day18.txt");
-        let volcano = Volcano::new(&input);
+        let volcano = Volcano::parse(&input).unwrap();
         let can_be_reached = volcano.can_be_reached_by_water();
-        assert!(!can_be_reached[volcano.p2i(Point3 { x: 2, y: 2, z: 5 })]);
-        assert!(!can_be_reached[volcano.p2i(Point3 { x: 2, y: 2, z: 4 })]);
-        assert!(can_be_reached[volcano.p2i(Point3 { x: 2, y: 1, z: 1 })]);
+        assert!(!can_be_reached[volcano.p2i(Point3::new(2, 2, 5))]);
+        assert!(!can_be_reached[volcano.p2i(Point3::new(2, 2, 4))]);
+        assert!(can_be_reached[volcano.p2i(Point3::new(2, 1, 1))]);
     }
 
     #[test]
     fn it_finds_exterior_surface_area() {
         let input = read_example("day18.txt");
-        let volcano = Volcano::new(&input);
+        let volcano = Volcano::parse(&input).unwrap();
         assert_eq!(58, volcano.exterior_surface_area());
     }
 }
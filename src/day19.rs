@@ -1,52 +1,87 @@
 use std::collections::HashSet;
 
-use itertools::Itertools;
-use regex::Regex;
-
-use crate::util::read_input;
-
-pub fn solve() {
-    let input = read_input("day19.txt");
-    let blueprints = parse_blueprints(&input);
-    let mut sum: usize = 0;
-    for (idx, blueprint) in blueprints.iter().enumerate() {
-        // dbg!(idx);
-        sum += Swarm::new().max_geodes(24, blueprint).0 as usize * (idx + 1);
+use nom::{
+    bytes::complete::tag,
+    character::complete::u16,
+    combinator::map,
+    sequence::{delimited, terminated, tuple},
+};
+
+pub type Robot = u16;
+
+pub type Blueprint = [[Robot; 4]; 4];
+
+use crate::{
+    solution::Solution,
+    util::{parallel, parse, ParseError},
+};
+
+pub struct Day19;
+
+impl Solution for Day19 {
+    const DAY: u8 = 19;
+    type Input = Vec<Blueprint>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> color_eyre::Result<Self::Input> {
+        Ok(parse_blueprints(input)?)
     }
-    println!("Day 19 part 1: {sum}");
-    let mut prod = 1;
-    for blueprint in blueprints.iter().take(3) {
-        // dbg!(idx);
-        prod *= Swarm::new().max_geodes(32, blueprint).0 as usize;
+
+    fn part1(blueprints: &Self::Input) -> color_eyre::Result<usize> {
+        Ok(max_geodes(blueprints, 24)
+            .into_iter()
+            .enumerate()
+            .map(|(idx, geodes)| geodes * (idx + 1))
+            .sum())
     }
-    println!("Day 19 part 2: {prod}");
-}
 
-type Robot = u16;
-
-type Blueprint = [[Robot; 4]; 4];
-
-pub fn parse_blueprints(input: &str) -> Vec<Blueprint> {
-    let re = Regex::new(
-        r"Blueprint (\d+): Each ore robot costs (\d+) ore\. Each clay robot costs (\d+) ore\. Each obsidian robot costs (\d+) ore and (\d+) clay\. Each geode robot costs (\d+) ore and (\d+) obsidian\.",
-    ).unwrap();
-    let mut blueprints: Vec<Blueprint> = Vec::with_capacity(input.lines().count());
-    for line in input.lines() {
-        if let Some(captures) = re.captures(line) {
-            let c: Vec<Robot> = captures
-                .iter()
-                .skip(1)
-                .filter_map(|v| v.and_then(|v| v.as_str().parse().ok()))
-                .collect_vec();
-            blueprints.push([
-                [c[1], 0, 0, 0],
-                [c[2], 0, 0, 0],
-                [c[3], c[4], 0, 0],
-                [c[5], 0, c[6], 0],
-            ]);
-        }
+    fn part2(blueprints: &Self::Input) -> color_eyre::Result<usize> {
+        Ok(max_geodes(&blueprints[..blueprints.len().min(3)], 32)
+            .into_iter()
+            .product())
     }
-    blueprints
+}
+
+/// The most geodes each blueprint can open in `minutes`, with the blueprints solved in parallel
+#[must_use]
+pub fn max_geodes(blueprints: &[Blueprint], minutes: Robot) -> Vec<usize> {
+    parallel::map(blueprints, |blueprint| {
+        Swarm::new().max_geodes(minutes, blueprint).0.into()
+    })
+}
+
+/// # Errors
+/// If a line is not a blueprint
+pub fn parse_blueprints(input: &str) -> Result<Vec<Blueprint>, ParseError> {
+    parse::located_lines(input.trim_end(), parse_blueprint)
+}
+
+/// # Errors
+/// If the line is not a blueprint
+pub fn parse_blueprint(line: &str) -> Result<Blueprint, ParseError> {
+    parse::nom(
+        line,
+        map(
+            tuple((
+                delimited(tag("Blueprint "), u16, tag(": ")),
+                delimited(tag("Each ore robot costs "), u16, tag(" ore. ")),
+                delimited(tag("Each clay robot costs "), u16, tag(" ore. ")),
+                delimited(tag("Each obsidian robot costs "), u16, tag(" ore and ")),
+                terminated(u16, tag(" clay. ")),
+                delimited(tag("Each geode robot costs "), u16, tag(" ore and ")),
+                terminated(u16, tag(" obsidian.")),
+            )),
+            |(_, ore, clay, obsidian_ore, obsidian_clay, geode_ore, geode_obsidian)| {
+                [
+                    [ore, 0, 0, 0],
+                    [clay, 0, 0, 0],
+                    [obsidian_ore, obsidian_clay, 0, 0],
+                    [geode_ore, 0, geode_obsidian, 0],
+                ]
+            },
+        ),
+    )
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -55,7 +90,14 @@ pub struct Swarm {
     resources: [Robot; 4], // 4 since geodes are considered resources, here
 }
 
+impl Default for Swarm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Swarm {
+    #[must_use]
     pub const fn new() -> Self {
         Self {
             robots: [1, 0, 0, 0],
@@ -68,6 +110,7 @@ impl Swarm {
         }
     }
 
+    #[must_use]
     pub fn tick(mut self, blueprint: &Blueprint) -> Vec<Self> {
         let mut swarms = vec![];
         for (robot_type, &r) in blueprint.iter().enumerate() {
@@ -97,13 +140,17 @@ impl Swarm {
         projected_resources
     }
 
+    /// The most geodes that can be opened in `minutes`, and the swarm that opens them
+    /// # Panics
+    /// Never, there is always at least one swarm
+    #[must_use]
     pub fn max_geodes(self, minutes: Robot, blueprint: &Blueprint) -> (Robot, Self) {
         let mut swarms: HashSet<Self> = HashSet::new();
         swarms.insert(self);
         for _ in 0..minutes {
             // dbg!(tick_number);
 
-            let most_robots = swarms.iter().fold([0,0,0,0], |acc, s|  {
+            let most_robots = swarms.iter().fold([0, 0, 0, 0], |acc, s| {
                 let mut acc = acc;
                 for (i, x) in acc.iter_mut().enumerate() {
                     *x = (*x).max(s.robots[i]);
@@ -131,7 +178,10 @@ impl Swarm {
             // dbg!(swarms.len());
             // dbg!(swarms.iter().map(|s| s.resources[3]).max());
         }
-        let best_swarm = swarms.iter().max_by(|a, b| a.resources[3].cmp(&b.resources[3])).unwrap();
+        let best_swarm = swarms
+            .iter()
+            .max_by(|a, b| a.resources[3].cmp(&b.resources[3]))
+            .unwrap();
         // dbg!(best_swarm);
 
         (best_swarm.resources[3], best_swarm.clone())
@@ -143,6 +193,17 @@ pub mod tests {
     use crate::util::read_example;
 
     use super::*;
+    #[test]
+    fn it_locates_parse_errors() {
+        let input =
+            read_example("day19.txt").replacen("3 ore and 8 clay", "3 ore and 8 obsidian", 1);
+        let err = Day19::parse(&input)
+            .unwrap_err()
+            .downcast::<ParseError>()
+            .unwrap();
+        assert_eq!((2, 108), (err.line, err.column));
+    }
+
     #[test]
     fn it_collects() {
         let mut swarm = Swarm {
@@ -158,7 +219,7 @@ pub mod tests {
     #[test]
     fn it_parses_input() {
         let input = read_example("day19.txt");
-        let blueprints = parse_blueprints(&input);
+        let blueprints = parse_blueprints(&input).unwrap();
         assert_eq!(
             blueprints[0],
             [[4, 0, 0, 0], [2, 0, 0, 0], [3, 14, 0, 0], [2, 0, 7, 0]]
@@ -190,11 +251,12 @@ pub mod tests {
     #[test]
     #[cfg(not(debug_assertions))]
     fn it_solves_part1() {
-        let input = read_input("day19.txt");
-        let blueprints = parse_blueprints(&input);
+        use crate::util::read_input;
+
+        let input = read_input("day19.txt").unwrap();
+        let blueprints = parse_blueprints(&input).unwrap();
         let mut sum = 0;
         for (idx, blueprint) in blueprints.iter().enumerate() {
-            dbg!(idx);
             sum += Swarm::new().max_geodes(24, blueprint).0 as usize * (idx + 1);
         }
         assert_eq!(1404, sum);
@@ -1,22 +1,40 @@
-use std::fmt::Write;
+use std::fmt::Display;
 use std::str::FromStr;
 
-use crate::util::read_input;
+use crate::{
+    solution::Solution,
+    util::{parse, ParseError},
+};
 
-pub fn solve() {
-    const ENCRYPTION_KEY: isize = 811_589_153;
-    let input = read_input("day20.txt");
-    let mut f = File::from_str(&input).unwrap();
-    f.mix();
-    println!("Day 20 part 1: {}", f.get_sum_of_grove_coordinates());
-    let mut f = File::from_str(&input).unwrap();
-    for v in &mut f.0 {
-        v.value *= ENCRYPTION_KEY;
+pub struct Day20;
+
+impl Solution for Day20 {
+    const DAY: u8 = 20;
+    type Input = File;
+    type Answer1 = isize;
+    type Answer2 = isize;
+
+    fn parse(input: &str) -> color_eyre::Result<Self::Input> {
+        File::from_str(input)
     }
-    for _ in 0..10 {
+
+    fn part1(f: &Self::Input) -> color_eyre::Result<isize> {
+        let mut f = f.clone();
         f.mix();
+        Ok(f.get_sum_of_grove_coordinates())
+    }
+
+    fn part2(f: &Self::Input) -> color_eyre::Result<isize> {
+        const ENCRYPTION_KEY: isize = 811_589_153;
+        let mut f = f.clone();
+        for v in &mut f.0 {
+            v.value *= ENCRYPTION_KEY;
+        }
+        for _ in 0..10 {
+            f.mix();
+        }
+        Ok(f.get_sum_of_grove_coordinates())
     }
-    println!("Day 20 part 2: {}", f.get_sum_of_grove_coordinates());
 }
 
 #[derive(Clone, PartialEq, Eq)]
@@ -27,21 +45,44 @@ pub struct Number {
     is_head_for_print: bool,
 }
 
+#[derive(Clone)]
 pub struct File(Vec<Number>);
 
 impl FromStr for File {
     type Err = color_eyre::Report;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let length = s.trim().lines().count();
-        let mut numbers: Vec<Number> = s
-            .trim()
+        let s = s.trim_end();
+        let values: Vec<isize> = parse::lines(s, str::parse)?;
+        let length = values.len();
+        if length < 2 {
+            return Err(
+                ParseError::at(s, "", "The file needs at least two numbers to mix them").into(),
+            );
+        }
+        let mut zeros = s
             .lines()
             .enumerate()
+            .zip(&values)
+            .filter(|(_, &value)| value == 0);
+        if zeros.next().is_none() {
+            return Err(ParseError::at(
+                s,
+                "",
+                "The file has no 0 to find the grove coordinates from",
+            )
+            .into());
+        }
+        if let Some(((i, line), _)) = zeros.next() {
+            return Err(ParseError::new(i + 1, 1, line, "The file has more than one 0").into());
+        }
+        let mut numbers: Vec<Number> = values
+            .into_iter()
+            .enumerate()
             .map(|(index, value)| Number {
                 prev: index.saturating_sub(1),
                 next: index.saturating_add(1),
-                value: value.parse().unwrap(),
+                value,
                 is_head_for_print: false,
             })
             .collect();
@@ -52,9 +93,8 @@ impl FromStr for File {
     }
 }
 
-impl ToString for File {
-    fn to_string(&self) -> String {
-        let mut s = String::new();
+impl Display for File {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut idx = self
             .0
             .iter()
@@ -63,13 +103,13 @@ impl ToString for File {
             .unwrap()
             .0;
         let start = idx;
-        write!(s, "{}", self.0[idx].value).unwrap();
+        write!(f, "{}", self.0[idx].value)?;
         idx = self.0[idx].next;
         while idx != start {
-            write!(s, ", {}", self.0[idx].value).unwrap();
+            write!(f, ", {}", self.0[idx].value)?;
             idx = self.0[idx].next;
         }
-        s
+        Ok(())
     }
 }
 
@@ -144,6 +184,9 @@ impl File {
         self.0[future_index].next = index;
     }
 
+    /// # Panics
+    /// If the file does not contain a 0, which [`File::from_str`] rules out
+    #[must_use]
     pub fn get_sum_of_grove_coordinates(&self) -> isize {
         // get index of value 0:
         let idx_zero = self
@@ -151,7 +194,7 @@ impl File {
             .iter()
             .enumerate()
             .find(|(_, p)| p.value == 0)
-            .unwrap()
+            .expect("Parsed files contain a 0")
             .0;
         self.0[self.step(idx_zero, 1000, false)].value
             + self.0[self.step(idx_zero, 2000, false)].value
@@ -165,6 +208,40 @@ pub mod tests {
 
     use super::*;
 
+    #[test]
+    fn it_locates_parse_errors() {
+        let err = File::from_str("1\n2\n-3\n3 4")
+            .err()
+            .unwrap()
+            .downcast::<crate::util::ParseError>()
+            .unwrap();
+        assert_eq!((4, "3 4"), (err.line, err.text.as_str()));
+    }
+
+    #[test]
+    fn it_rejects_files_it_cannot_mix() {
+        let err = |input: &str| {
+            File::from_str(input)
+                .err()
+                .unwrap()
+                .downcast::<ParseError>()
+                .unwrap()
+        };
+        assert!(err("").message.contains("at least two numbers"));
+        assert!(err("0\n").message.contains("at least two numbers"));
+        let no_zero = err("1\n2\n-3");
+        assert_eq!(
+            (3, 3, "-3"),
+            (no_zero.line, no_zero.column, no_zero.text.as_str())
+        );
+        assert!(no_zero.message.contains("no 0"));
+        let two_zeros = err("0\n2\n0\n4");
+        assert_eq!(
+            (3, "The file has more than one 0"),
+            (two_zeros.line, two_zeros.message.as_str())
+        );
+    }
+
     #[test]
     fn it_mixes_example() {
         let input = read_example("day20.txt");
@@ -199,7 +276,7 @@ pub mod tests {
 
     #[test]
     fn it_solves_part1() {
-        let input = read_input("day20.txt");
+        let input = read_input("day20.txt").unwrap();
         let mut f = File::from_str(&input).unwrap();
         f.mix();
         assert_eq!(13883, f.get_sum_of_grove_coordinates());
@@ -207,7 +284,7 @@ pub mod tests {
 
     #[test]
     fn it_can_do_ten_times_too() {
-        const ENCRYPTION_KEY: isize = 811589153;
+        const ENCRYPTION_KEY: isize = 811_589_153;
         let input = read_example("day20.txt");
         let mut f = File::from_str(&input).unwrap();
         for v in &mut f.0 {
@@ -222,10 +299,10 @@ pub mod tests {
                 .find(|(_, p)| p.value == 0)
                 .unwrap()
                 .0;
-        assert_eq!(811589153, f.0[f.step(idx_zero, 1000, false)].value);
-        assert_eq!(2434767459, f.0[f.step(idx_zero, 2000, false)].value);
-        assert_eq!(-1623178306, f.0[f.step(idx_zero, 3000, false)].value);
-        assert_eq!(1623178306, f.get_sum_of_grove_coordinates());
+        assert_eq!(811_589_153, f.0[f.step(idx_zero, 1000, false)].value);
+        assert_eq!(2_434_767_459, f.0[f.step(idx_zero, 2000, false)].value);
+        assert_eq!(-1_623_178_306, f.0[f.step(idx_zero, 3000, false)].value);
+        assert_eq!(1_623_178_306, f.get_sum_of_grove_coordinates());
     }
     #[test]
     fn it_skips_step_positive() {
@@ -238,15 +315,15 @@ pub mod tests {
 
     #[test]
     fn it_can_do_ten_times_too2() {
-        let input = read_input("day20.txt");
+        const ENCRYPTION_KEY: isize = 811_589_153;
+        let input = read_input("day20.txt").unwrap();
         let mut f = File::from_str(&input).unwrap();
-        const ENCRYPTION_KEY: isize = 811589153;
         for v in &mut f.0 {
             v.value *= ENCRYPTION_KEY;
         }
         for _ in 0..10 {
             f.mix();
         }
-        assert_eq!(19185967576920, f.get_sum_of_grove_coordinates());
+        assert_eq!(19_185_967_576_920, f.get_sum_of_grove_coordinates());
     }
 }
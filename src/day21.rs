@@ -1,35 +1,55 @@
-use std::{collections::HashMap, fmt::Display, str::FromStr};
+use std::{collections::HashMap, fmt::Display, str::FromStr, sync::LazyLock};
 
-use crate::util::read_input;
-use {once_cell::sync::Lazy, regex::Regex};
+use regex::Regex;
 
-pub fn solve() {
-    let input = read_input("day21.txt");
-    let mut monkeys: Monkeys = input.parse().unwrap();
-    println!("Day 21 part 1: {}", monkeys.solve("root").unwrap());
+use crate::{
+    solution::Solution,
+    util::{parse, ParseError},
+};
 
-    let mut monkeys: Monkeys = input.parse().unwrap();
-    monkeys.monkeys.entry("root".to_string()).and_modify(|m| {
-        if let Action::Calculation(c) = m {
-            c.op = Operator::Eq;
-        }
-    });
-    monkeys
-        .monkeys
-        .entry("humn".to_string())
-        .and_modify(|h| *h = Action::Human);
-    let Action::Calculation(root) = monkeys.monkeys.get("root").unwrap().clone() else {
-        panic!()
-    };
-    let result = if let Action::Number(_) = monkeys.monkeys.get(&root.lhs).unwrap() {
-        monkeys.solve_equation(&root.rhs, &root.lhs)
-    } else {
-        monkeys.solve_equation(&root.lhs, &root.rhs)
-    };
-    println!("Day 21 part 2: {result}");
+pub struct Day21;
+
+impl Solution for Day21 {
+    const DAY: u8 = 21;
+    type Input = Monkeys;
+    type Answer1 = Number;
+    type Answer2 = Number;
+
+    fn parse(input: &str) -> color_eyre::Result<Self::Input> {
+        input.parse()
+    }
+
+    fn part1(monkeys: &Self::Input) -> color_eyre::Result<Number> {
+        monkeys
+            .clone()
+            .solve("root")
+            .ok_or_else(|| color_eyre::eyre::eyre!("Cannot solve for root"))
+    }
+
+    fn part2(monkeys: &Self::Input) -> color_eyre::Result<Number> {
+        let mut monkeys = monkeys.clone();
+        monkeys.monkeys.entry("root".to_string()).and_modify(|m| {
+            if let Action::Calculation(c) = m {
+                c.op = Operator::Eq;
+            }
+        });
+        monkeys
+            .monkeys
+            .entry("humn".to_string())
+            .and_modify(|h| *h = Action::Human);
+        let Some(Action::Calculation(root)) = monkeys.monkeys.get("root").cloned() else {
+            return Err(color_eyre::eyre::eyre!("Root monkey is not a calculation"));
+        };
+        let result = if let Some(Action::Number(_)) = monkeys.monkeys.get(&root.lhs) {
+            monkeys.solve_equation(&root.rhs, &root.lhs)
+        } else {
+            monkeys.solve_equation(&root.lhs, &root.rhs)
+        };
+        Ok(result)
+    }
 }
 
-type Number = isize;
+pub type Number = isize;
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct Monkey {
@@ -54,6 +74,9 @@ pub enum Operator {
 }
 
 impl Operator {
+    /// # Panics
+    /// For [`Operator::Eq`], which cannot be applied
+    #[must_use]
     pub fn apply(self, lhs: Number, rhs: Number) -> Number {
         match self {
             Self::Add => lhs + rhs,
@@ -64,6 +87,7 @@ impl Operator {
         }
     }
 
+    #[must_use]
     pub const fn get_inverse(self) -> Self {
         match self {
             Self::Add => Self::Sub,
@@ -74,6 +98,7 @@ impl Operator {
         }
     }
 
+    #[must_use]
     pub fn is_commutative(self) -> bool {
         self == Self::Add || self == Self::Mul
     }
@@ -113,7 +138,7 @@ impl Display for Operator {
 }
 
 #[derive(Debug, Clone)]
-struct Monkeys {
+pub struct Monkeys {
     monkeys: HashMap<String, Action>,
 }
 
@@ -121,11 +146,7 @@ impl FromStr for Monkeys {
     type Err = color_eyre::Report;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let res: Vec<Monkey> = s
-            .trim()
-            .lines()
-            .map(str::parse)
-            .collect::<color_eyre::Result<Vec<_>>>()?;
+        let res: Vec<Monkey> = parse::located_lines(s.trim_end(), str::parse)?;
         let mut result = HashMap::new();
         for monkey in res {
             result.insert(monkey.name, monkey.action);
@@ -135,27 +156,35 @@ impl FromStr for Monkeys {
 }
 
 impl FromStr for Monkey {
-    type Err = color_eyre::Report;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        static RE: Lazy<Regex> =
-            Lazy::new(|| Regex::new(r"^(\w+): (\w+)(?: ([\+\-\*\/]) (\w+))?$").unwrap());
-        let cap = RE
-            .captures(s)
-            .ok_or_else(|| color_eyre::eyre::eyre!("Cannot parse line {s}"))?;
-        if cap.iter().filter(Option::is_some).count() > 3 {
+        static RE: LazyLock<Regex> =
+            LazyLock::new(|| Regex::new(r"^(\w+): (\w+)(?: ([\+\-\*\/]) (\w+))?$").unwrap());
+        let cap = RE.captures(s).ok_or_else(|| {
+            ParseError::new(1, 1, s, "Expected 'name: number' or 'name: lhs op rhs'")
+        })?;
+        let error = |group: usize, err: &dyn Display| {
+            ParseError::new(
+                1,
+                cap.get(group).map_or(1, |m| m.start() + 1),
+                s,
+                format!("{err:#}"),
+            )
+        };
+        if cap.iter().flatten().count() > 3 {
             Ok(Self {
                 name: cap[1].to_owned(),
                 action: Action::Calculation(Calculation {
                     lhs: cap[2].to_owned(),
-                    op: cap[3].parse()?,
+                    op: cap[3].parse().map_err(|err| error(3, &err))?,
                     rhs: cap[4].to_owned(),
                 }),
             })
         } else {
             Ok(Self {
                 name: cap[1].to_owned(),
-                action: Action::Number(cap[2].parse()?),
+                action: Action::Number(cap[2].parse().map_err(|err| error(2, &err))?),
             })
         }
     }
@@ -175,6 +204,10 @@ impl Monkeys {
         None
     }
 
+    /// Whether `child` is `root` or one of the monkeys it depends on
+    /// # Panics
+    /// If a monkey depends on one that does not exist
+    #[must_use]
     pub fn contains(&self, root: &str, child: &str) -> bool {
         if root == child {
             return true;
@@ -185,6 +218,8 @@ impl Monkeys {
         self.contains(&calc.lhs, child) || self.contains(&calc.rhs, child)
     }
 
+    /// # Panics
+    /// If `root` is missing or is not a calculation
     pub fn get_equation(&mut self) -> String {
         let Action::Calculation(root) = self.monkeys.get("root").unwrap().clone() else {
             panic!("Nonono")
@@ -198,6 +233,8 @@ impl Monkeys {
         self.get_equation_part("root")
     }
 
+    /// # Panics
+    /// If a monkey is missing, or the equation cannot be solved by inverting one operation at a time
     pub fn solve_equation(&mut self, variable_side: &str, fix_side: &str) -> Number {
         let _ = self.get_equation();
         // println!("\n{}", );
@@ -251,7 +288,7 @@ impl Monkeys {
                             panic!()
                         };
                         self.monkeys
-                            .entry(r.lhs.to_string())
+                            .entry(r.lhs.clone())
                             .and_modify(|f| *f = Action::Number(old_fix));
                         self.monkeys
                             .entry(variable_side.to_string())
@@ -314,6 +351,23 @@ pub mod tests {
 
     use super::*;
 
+    #[test]
+    fn it_locates_parse_errors() {
+        let input = read_example("day21.txt");
+        let err = |input: &str| {
+            Monkeys::from_str(input)
+                .unwrap_err()
+                .downcast::<ParseError>()
+                .unwrap()
+        };
+        let number = err(&input.replace("dbpl: 5", "dbpl: five"));
+        assert_eq!(
+            (2, 7, "dbpl: five"),
+            (number.line, number.column, number.text.as_str())
+        );
+        assert_eq!(3, err(&input.replace("sllz + lgvd", "sllz plus lgvd")).line);
+    }
+
     #[test]
     fn it_parses_monkey() -> color_eyre::Result<()> {
         let input = "root: pppw + sjmn";
@@ -351,9 +405,9 @@ pub mod tests {
     }
     #[test]
     fn it_solves_part1() {
-        let input = read_input("day21.txt");
+        let input = read_input("day21.txt").unwrap();
         let mut monkeys: Monkeys = input.parse().unwrap();
-        assert_eq!(Some(194058098264286), monkeys.solve("root"));
+        assert_eq!(Some(194_058_098_264_286), monkeys.solve("root"));
     }
 
     #[test]
@@ -366,7 +420,7 @@ pub mod tests {
 
     #[test]
     fn that_real_input_does_not_have_humn_on_both_sides() {
-        let input = read_input("day21.txt");
+        let input = read_input("day21.txt").unwrap();
         let monkeys: Monkeys = input.parse().unwrap();
         let Action::Calculation(root) = monkeys.monkeys.get("root").unwrap() else {
             panic!("Nonono")
@@ -411,7 +465,7 @@ pub mod tests {
         let mut monkeys: Monkeys = input.parse().unwrap();
         monkeys.monkeys.entry("root".to_string()).and_modify(|m| {
             if let Action::Calculation(c) = m {
-                c.op = Operator::Eq
+                c.op = Operator::Eq;
             }
         });
         monkeys
@@ -431,11 +485,11 @@ pub mod tests {
 
     #[test]
     fn it_solves_equation_from_input() {
-        let input = read_input("day21.txt");
+        let input = read_input("day21.txt").unwrap();
         let mut monkeys: Monkeys = input.parse().unwrap();
         monkeys.monkeys.entry("root".to_string()).and_modify(|m| {
             if let Action::Calculation(c) = m {
-                c.op = Operator::Eq
+                c.op = Operator::Eq;
             }
         });
         monkeys
@@ -450,6 +504,6 @@ pub mod tests {
         } else {
             monkeys.solve_equation(&root.lhs, &root.rhs)
         };
-        assert_eq!(3592056845086, result);
+        assert_eq!(3_592_056_845_086, result);
     }
 }
@@ -1,72 +1,249 @@
-use std::fmt::{Display, Formatter};
+use crate::{
+    solution::Solution,
+    util::{parse, Compass, Direction, Grid, ParseError},
+};
+use color_eyre::eyre::eyre;
 use nom::branch::alt;
-use nom::character::complete::{alpha1, digit1};
-use nom::combinator::{all_consuming, map};
-use nom::{Finish, IResult};
-use crate::util::{Direction, read_input};
-
-pub fn solve() {
-    let input = read_input("day22.txt");
-    let (map,mut me) = init(&input);
-    me.follow_instructions(&map);
-    println!("Day 22 part 1: {}", me.get_password());
+use nom::character::complete::{char, u8};
+use nom::combinator::{map, value};
+use nom::multi::many1;
+use nom::IResult;
+use std::collections::VecDeque;
+use std::fmt::{Display, Formatter};
+
+pub struct Day22;
+
+impl Solution for Day22 {
+    const DAY: u8 = 22;
+    type Input = (Map, Me);
+    type Answer1 = isize;
+    type Answer2 = isize;
+
+    fn parse(input: &str) -> color_eyre::Result<Self::Input> {
+        init(input)
+    }
+
+    fn part1((map, me): &Self::Input) -> color_eyre::Result<isize> {
+        let mut me = me.clone();
+        me.follow_instructions(map);
+        Ok(me.get_password())
+    }
+
+    fn part2((map, me): &Self::Input) -> color_eyre::Result<isize> {
+        let cube = Cube::fold(map)?;
+        let mut me = me.clone();
+        me.follow_instructions_on_cube(map, &cube);
+        Ok(me.get_password())
+    }
 }
 
-pub fn init(input: &str) -> (Map, Me) {
-    let (map, instructions) = input.split_once("\n\n").unwrap();
-    let map = Map::parse(map);
-    let mut me = Me::parse(instructions);
+/// # Errors
+/// If the input is not a map followed by the instructions
+pub fn init(input: &str) -> color_eyre::Result<(Map, Me)> {
+    let (map, instructions) = input
+        .split_once("\n\n")
+        .ok_or_else(|| eyre!("Expected the map and the instructions separated by an empty line"))?;
+    let map = Map::parse(map)?;
+    let mut me = Me::parse(instructions).map_err(|err| err.offset(map.grid.height() + 1))?;
     me.set_starting_point(&map);
-    (map, me)
+    Ok((map, me))
 }
 
 pub struct Map {
-    grid: Vec<Tile>,
+    grid: Grid<Tile>,
     width: isize,
     height: isize,
 }
 
 impl Map {
-    fn parse(s: &str) -> Self {
-        let width = isize::try_from(s.lines().map(str::len).max().unwrap()).unwrap();
-        let height = isize::try_from(s.lines().count()).unwrap();
-        let grid = vec![Tile::Void; usize::try_from(width * height).unwrap()];
-        let mut map = Self { grid, width, height };
-        for (y, line) in s.lines().enumerate() {
-            for (x, c) in line.chars().enumerate() {
-                map.set(isize::try_from(x).unwrap(), isize::try_from(y).unwrap(), match c {
-                    ' ' => Tile::Void,
-                    '.' => Tile::Open,
-                    '#' => Tile::Wall,
-                    _ => unreachable!()
-                });
-            }
-        }
-        map
-    }
-
-    fn set(&mut self, x: isize, y: isize, tile: Tile) {
-        self.grid[usize::try_from(y * self.width + x).unwrap()] = tile;
+    fn parse(s: &str) -> color_eyre::Result<Self> {
+        let grid = Grid::parse_ragged(s, Tile::Void, |c| match c {
+            ' ' => Ok(Tile::Void),
+            '.' => Ok(Tile::Open),
+            '#' => Ok(Tile::Wall),
+            _ => Err(eyre!("Unknown tile '{c}'")),
+        })?;
+        Ok(Self {
+            width: isize::try_from(grid.width())?,
+            height: isize::try_from(grid.height())?,
+            grid,
+        })
     }
 
+    /// [`Tile::Void`] outside of the map
     fn get(&self, x: isize, y: isize) -> Tile {
-        if x < 0 || y < 0 || x >= self.width || y >= self.height {
-            Tile::Void
-        } else {
-            self.grid[usize::try_from(y * self.width + x).unwrap()]
-        }
+        self.grid.get_signed(x, y).copied().unwrap_or_default()
     }
 }
 
 impl Display for Map {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        for y in 0..self.height {
-            for x in 0..self.width {
-                write!(f, "{}", self.get(x, y))?;
+        write!(f, "{}", self.grid)
+    }
+}
+
+type Vector = [isize; 3];
+
+const fn neg(v: Vector) -> Vector {
+    [-v[0], -v[1], -v[2]]
+}
+
+const fn add(a: Vector, b: Vector) -> Vector {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+const fn scale(v: Vector, factor: isize) -> Vector {
+    [v[0] * factor, v[1] * factor, v[2] * factor]
+}
+
+const fn dot(a: Vector, b: Vector) -> isize {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+/// A face of the cube: where it is on the map, and where it ended up after folding.
+/// `right` and `down` are the directions of the map's x and y axis on the face.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Face {
+    origin: (isize, isize),
+    normal: Vector,
+    right: Vector,
+    down: Vector,
+}
+
+impl Face {
+    /// The direction in space of walking in `direction` on the map
+    const fn towards(&self, direction: Direction) -> Vector {
+        match direction {
+            Direction::Up => neg(self.down),
+            Direction::Right => self.right,
+            Direction::Down => self.down,
+            Direction::Left => neg(self.right),
+        }
+    }
+
+    /// The neighbouring face in the net, folded over the shared edge
+    fn fold(&self, direction: Direction, size: isize) -> Self {
+        let (dx, dy) = Compass::from(direction).offset();
+        let origin = (self.origin.0 + dx * size, self.origin.1 + dy * size);
+        let (normal, right, down) = match direction {
+            Direction::Up => (neg(self.down), self.right, self.normal),
+            Direction::Right => (self.right, neg(self.normal), self.down),
+            Direction::Down => (self.down, self.right, neg(self.normal)),
+            Direction::Left => (neg(self.right), self.normal, self.down),
+        };
+        Self {
+            origin,
+            normal,
+            right,
+            down,
+        }
+    }
+}
+
+/// The map folded into a cube.
+///
+/// A tile at `(x, y)` of a face is the point `size * normal + (2x + 1 - size) * right + (2y + 1 - size) * down`,
+/// so that the tiles are centered on the faces of a cube from `-size` to `size` in every axis.
+#[derive(Debug)]
+pub struct Cube {
+    size: isize,
+    faces: Vec<Face>,
+}
+
+impl Cube {
+    /// Finds the six faces on the map and folds them together, starting from the first face on the map
+    /// and folding its neighbours over the shared edges. This works for every net of a cube.
+    /// # Errors
+    /// If the map is not the net of a cube
+    pub fn fold(map: &Map) -> color_eyre::Result<Self> {
+        let tiles = map.grid.iter().filter(|tile| **tile != Tile::Void).count();
+        let size = (1..=tiles)
+            .find(|size| 6 * size * size >= tiles)
+            .filter(|size| 6 * size * size == tiles)
+            .ok_or_else(|| eyre!("{tiles} tiles cannot be the six faces of a cube"))?;
+        let size = isize::try_from(size)?;
+        let first = (0..map.width)
+            .step_by(usize::try_from(size)?)
+            .find(|&x| map.get(x, 0) != Tile::Void)
+            .ok_or_else(|| eyre!("The first row of the map is empty"))?;
+        let mut faces = vec![Face {
+            origin: (first, 0),
+            normal: [0, 0, -1],
+            right: [1, 0, 0],
+            down: [0, 1, 0],
+        }];
+        let mut queue = VecDeque::from([faces[0]]);
+        while let Some(face) = queue.pop_front() {
+            for direction in [
+                Direction::Up,
+                Direction::Right,
+                Direction::Down,
+                Direction::Left,
+            ] {
+                let neighbour = face.fold(direction, size);
+                if map.get(neighbour.origin.0, neighbour.origin.1) != Tile::Void
+                    && !faces.iter().any(|face| face.origin == neighbour.origin)
+                {
+                    faces.push(neighbour);
+                    queue.push_back(neighbour);
+                }
             }
-            writeln!(f)?;
         }
-        Ok(())
+        if faces.len() != 6
+            || faces
+                .iter()
+                .any(|a| faces.iter().filter(|b| a.normal == b.normal).count() > 1)
+        {
+            return Err(eyre!(
+                "The map is not the net of a cube with faces of size {size}"
+            ));
+        }
+        Ok(Self { size, faces })
+    }
+
+    fn face_at(&self, (x, y): (isize, isize)) -> &Face {
+        self.faces
+            .iter()
+            .find(|face| {
+                (face.origin.0..face.origin.0 + self.size).contains(&x)
+                    && (face.origin.1..face.origin.1 + self.size).contains(&y)
+            })
+            .expect("Position is on the cube")
+    }
+
+    /// Where walking in `direction` off the edge of a face at `position` leads to, and the new direction
+    fn wrap(&self, position: (isize, isize), direction: Direction) -> ((isize, isize), Direction) {
+        let face = self.face_at(position);
+        let (x, y) = (position.0 - face.origin.0, position.1 - face.origin.1);
+        let point = add(
+            scale(face.normal, self.size),
+            add(
+                scale(face.right, 2 * x + 1 - self.size),
+                scale(face.down, 2 * y + 1 - self.size),
+            ),
+        );
+        let towards = face.towards(direction);
+        let target = self
+            .faces
+            .iter()
+            .find(|face| face.normal == towards)
+            .expect("Every side of the cube has a face");
+        // Over the edge, the point moves by one in the old direction and one away from the old face
+        let point = add(point, add(towards, neg(face.normal)));
+        let (x, y) = (
+            (dot(point, target.right) + self.size - 1) / 2,
+            (dot(point, target.down) + self.size - 1) / 2,
+        );
+        let direction = [
+            Direction::Up,
+            Direction::Right,
+            Direction::Down,
+            Direction::Left,
+        ]
+        .into_iter()
+        .find(|&direction| target.towards(direction) == neg(face.normal))
+        .expect("The old face is next to the new one");
+        ((target.origin.0 + x, target.origin.1 + y), direction)
     }
 }
 
@@ -77,18 +254,21 @@ pub struct Me {
     direction: Direction,
 }
 
-
 impl Me {
-    fn _digit_or_direction(input: &str) -> IResult<&str, &str> {
-        alt((digit1, alpha1))(input)
+    fn _instruction(input: &str) -> IResult<&str, Instruction> {
+        alt((
+            map(u8, Instruction::Move),
+            value(Instruction::TurnRight, char('R')),
+            value(Instruction::TurnLeft, char('L')),
+        ))(input)
     }
-    fn parse(s: &str) -> Self {
-        let instructions: Vec<Instruction> = all_consuming(
-            map(nom::multi::many1(
-                Self::_digit_or_direction),
-                |v| v.into_iter().map(Instruction::from).collect::<Vec<Instruction>>())
-        )(s.trim()).finish().unwrap().1;
-        Self { instructions, position: (0, 0), direction: Direction::Right }
+    fn parse(s: &str) -> Result<Self, ParseError> {
+        let instructions = parse::nom(s.trim_end(), many1(Self::_instruction))?;
+        Ok(Self {
+            instructions,
+            position: (0, 0),
+            direction: Direction::Right,
+        })
     }
 
     pub fn set_starting_point(&mut self, map: &Map) {
@@ -122,7 +302,9 @@ impl Me {
                     }
                 }
             }
-            Tile::Open => { self.position.1 -= 1; }
+            Tile::Open => {
+                self.position.1 -= 1;
+            }
             Tile::Wall => {}
         }
     }
@@ -142,7 +324,9 @@ impl Me {
                     }
                 }
             }
-            Tile::Open => { self.position.1 += 1; }
+            Tile::Open => {
+                self.position.1 += 1;
+            }
             Tile::Wall => {}
         }
     }
@@ -162,7 +346,9 @@ impl Me {
                     }
                 }
             }
-            Tile::Open => { self.position.0 -= 1; }
+            Tile::Open => {
+                self.position.0 -= 1;
+            }
             Tile::Wall => {}
         }
     }
@@ -182,10 +368,49 @@ impl Me {
                     }
                 }
             }
-            Tile::Open => { self.position.0 += 1; }
+            Tile::Open => {
+                self.position.0 += 1;
+            }
             Tile::Wall => {}
         }
     }
+    fn follow_instructions_on_cube(&mut self, map: &Map, cube: &Cube) {
+        let instructions = self.instructions.clone();
+        for instruction in instructions {
+            self.follow_instruction_on_cube(map, cube, instruction);
+        }
+    }
+
+    fn follow_instruction_on_cube(&mut self, map: &Map, cube: &Cube, instruction: Instruction) {
+        match instruction {
+            Instruction::Move(num_steps) => {
+                for _ in 0..num_steps {
+                    if !self.try_move_on_cube(map, cube) {
+                        return;
+                    }
+                }
+            }
+            turn => self.follow_instruction(map, turn),
+        }
+    }
+
+    /// Moves one step, over the edge of the face if needed. Returns `false` if a wall is in the way.
+    fn try_move_on_cube(&mut self, map: &Map, cube: &Cube) -> bool {
+        let (dx, dy) = Compass::from(self.direction).offset();
+        let next = (self.position.0 + dx, self.position.1 + dy);
+        let (position, direction) = if map.get(next.0, next.1) == Tile::Void {
+            cube.wrap(self.position, self.direction)
+        } else {
+            (next, self.direction)
+        };
+        if map.get(position.0, position.1) == Tile::Wall {
+            return false;
+        }
+        self.position = position;
+        self.direction = direction;
+        true
+    }
+
     fn follow_instruction(&mut self, map: &Map, instruction: Instruction) {
         match instruction {
             Instruction::TurnLeft => self.direction = self.direction.turn(true),
@@ -193,10 +418,10 @@ impl Me {
             Instruction::Move(num_steps) => {
                 for _ in 0..num_steps {
                     match self.direction {
-                        Direction::Up => { self.try_move_up(map) }
-                        Direction::Right => { self.try_move_right(map) }
-                        Direction::Down => { self.try_move_down(map) }
-                        Direction::Left => { self.try_move_left(map) }
+                        Direction::Up => self.try_move_up(map),
+                        Direction::Right => self.try_move_right(map),
+                        Direction::Down => self.try_move_down(map),
+                        Direction::Left => self.try_move_left(map),
                     }
                 }
             }
@@ -204,12 +429,14 @@ impl Me {
     }
 
     const fn get_password(&self) -> isize {
-        (self.position.1 + 1) * 1_000 + (self.position.0 + 1) * 4 + match self.direction {
-            Direction::Right => 0,
-            Direction::Down => 1,
-            Direction::Left => 2,
-            Direction::Up => 3,
-        }
+        (self.position.1 + 1) * 1_000
+            + (self.position.0 + 1) * 4
+            + match self.direction {
+                Direction::Right => 0,
+                Direction::Down => 1,
+                Direction::Left => 2,
+                Direction::Up => 3,
+            }
     }
 }
 
@@ -223,11 +450,15 @@ pub enum Tile {
 
 impl Display for Tile {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", match self {
-            Self::Void => ' ',
-            Self::Open => '.',
-            Self::Wall => '#',
-        })
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Void => ' ',
+                Self::Open => '.',
+                Self::Wall => '#',
+            }
+        )
     }
 }
 
@@ -238,33 +469,49 @@ pub enum Instruction {
     TurnRight,
 }
 
-impl From<&str> for Instruction {
-    fn from(value: &str) -> Self {
-        match value {
-            "R" => Self::TurnRight,
-            "L" => Self::TurnLeft,
-            _ => Self::Move(value.parse().unwrap_or(0))
-        }
-    }
-}
-
 #[cfg(test)]
 mod tests {
-    use itertools::Itertools;
-    use crate::util::read_example;
     use super::*;
+    use crate::util::read_example;
+    use itertools::Itertools;
 
     #[test]
     fn it_parses_instructions() {
-        let expected = vec![Instruction::Move(10), Instruction::TurnRight, Instruction::Move(5), Instruction::TurnLeft];
-        assert_eq!(Me::parse("10R5L"), Me { instructions: expected, position: (0, 0), direction: Direction::Right });
+        let expected = vec![
+            Instruction::Move(10),
+            Instruction::TurnRight,
+            Instruction::Move(5),
+            Instruction::TurnLeft,
+        ];
+        assert!(Me::parse("10R5X").is_err());
+        assert_eq!(
+            Me::parse("10R5L").unwrap(),
+            Me {
+                instructions: expected,
+                position: (0, 0),
+                direction: Direction::Right
+            }
+        );
+    }
+
+    #[test]
+    fn it_locates_parse_errors() {
+        let input = read_example("day22.txt");
+        let err = |input: &str| init(input).err().unwrap().downcast::<ParseError>().unwrap();
+        let instruction = err(&input.replace("10R5L5R", "10R5X5R"));
+        assert_eq!((14, 5), (instruction.line, instruction.column));
+        let tile = err(&input.replacen(".#..", ".o..", 1));
+        assert_eq!(
+            ParseError::new(2, 10, "        .o..", "Unknown tile 'o'"),
+            tile
+        );
     }
 
     #[test]
     fn it_parses_map() {
         let input = read_example("day22.txt");
         let (map, _) = input.split_once("\n\n").unwrap();
-        let map = Map::parse(map);
+        let map = Map::parse(map).unwrap();
         let expected = r"        ...#
         .#..
         #...
@@ -277,7 +524,7 @@ mod tests {
         .....#..
         .#......
         ......#.";
-        let actual: String = map.to_string().lines().map(|line| line.trim_end()).join("\n");
+        let actual: String = map.to_string().lines().map(str::trim_end).join("\n");
         assert_eq!(expected, actual);
     }
 
@@ -285,33 +532,124 @@ mod tests {
     fn it_follows_instructions() {
         let input = read_example("day22.txt");
         let (map, instructions) = input.split_once("\n\n").unwrap();
-        let map = Map::parse(map);
-        let mut me = Me::parse(instructions);
+        let map = Map::parse(map).unwrap();
+        let mut me = Me::parse(instructions).unwrap();
         me.set_starting_point(&map);
         assert_eq!((8, 0), me.position);
         me.follow_instruction(&map, me.instructions[0]);
-        assert_eq!((10,0), me.position);
+        assert_eq!((10, 0), me.position);
         assert_eq!(Direction::Right, me.direction);
         me.follow_instruction(&map, me.instructions[1]);
-        assert_eq!((10,0), me.position);
+        assert_eq!((10, 0), me.position);
         assert_eq!(Direction::Down, me.direction);
         me.follow_instruction(&map, me.instructions[2]);
-        assert_eq!((10,5), me.position);
+        assert_eq!((10, 5), me.position);
         assert_eq!(Direction::Down, me.direction);
         me.follow_instruction(&map, me.instructions[3]);
-        assert_eq!((10,5), me.position);
+        assert_eq!((10, 5), me.position);
         assert_eq!(Direction::Right, me.direction);
         me.follow_instruction(&map, me.instructions[4]);
-        assert_eq!((3,5), me.position);
+        assert_eq!((3, 5), me.position);
         assert_eq!(Direction::Right, me.direction);
     }
     #[test]
     fn it_follows_instructions_to_the_end() {
         let input = read_example("day22.txt");
-        let (map, mut me): (Map, Me) = init(&input);
+        let (map, mut me): (Map, Me) = init(&input).unwrap();
         me.follow_instructions(&map);
-        assert_eq!((7,5), me.position);
+        assert_eq!((7, 5), me.position);
         assert_eq!(Direction::Right, me.direction);
         assert_eq!(6_032, me.get_password());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn it_follows_instructions_on_the_cube() {
+        let input = read_example("day22.txt");
+        let (map, mut me): (Map, Me) = init(&input).unwrap();
+        let cube = Cube::fold(&map).unwrap();
+        assert_eq!(4, cube.size);
+        me.follow_instructions_on_cube(&map, &cube);
+        assert_eq!((6, 4), me.position);
+        assert_eq!(Direction::Up, me.direction);
+        assert_eq!(5_031, me.get_password());
+    }
+
+    #[test]
+    fn it_wraps_over_cube_edges() {
+        let input = read_example("day22.txt");
+        let (map, _) = init(&input).unwrap();
+        let cube = Cube::fold(&map).unwrap();
+        // The examples of the puzzle description
+        assert_eq!(
+            ((14, 8), Direction::Down),
+            cube.wrap((11, 5), Direction::Right)
+        );
+        assert_eq!(
+            ((1, 7), Direction::Up),
+            cube.wrap((10, 11), Direction::Down)
+        );
+        assert_eq!(((8, 2), Direction::Right), cube.wrap((6, 4), Direction::Up));
+    }
+
+    /// A map with all tiles open in the faces marked by `#`
+    fn net(layout: &str, size: usize) -> Map {
+        let map = layout
+            .lines()
+            .flat_map(|line| {
+                let row: String = line
+                    .chars()
+                    .flat_map(|c| std::iter::repeat_n(if c == '#' { '.' } else { ' ' }, size))
+                    .collect();
+                std::iter::repeat_n(row, size)
+            })
+            .join("\n");
+        Map::parse(&map).unwrap()
+    }
+
+    #[test]
+    fn it_folds_any_net() {
+        let nets = [
+            "..#.\n###.\n..##",
+            ".##\n.#.\n##.\n#..",
+            "#...\n####\n#...",
+            "##..\n.##.\n..##",
+            "..#\n.##\n##.\n#..",
+            ".#..\n####\n..#.",
+        ];
+        for layout in nets {
+            let size = 3;
+            let map = net(layout, size);
+            let cube = Cube::fold(&map).unwrap_or_else(|err| panic!("{layout}: {err}"));
+            // Walking around the cube in a straight line leads back to the start
+            for y in 0..map.height {
+                for x in 0..map.width {
+                    if map.get(x, y) == Tile::Void {
+                        continue;
+                    }
+                    for direction in [
+                        Direction::Up,
+                        Direction::Right,
+                        Direction::Down,
+                        Direction::Left,
+                    ] {
+                        let mut me = Me {
+                            instructions: vec![],
+                            position: (x, y),
+                            direction,
+                        };
+                        for _ in 0..4 * size {
+                            assert!(me.try_move_on_cube(&map, &cube));
+                        }
+                        assert_eq!(((x, y), direction), (me.position, me.direction), "{layout}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn it_rejects_maps_that_are_no_cube() {
+        assert!(Cube::fold(&net("####\n#...\n#...", 2)).is_err());
+        assert!(Cube::fold(&net("###\n#..", 2)).is_err());
+    }
+}
@@ -1,93 +1,70 @@
+use itertools::Itertools;
 use std::collections::{HashMap, HashSet};
-use itertools::{Itertools, MinMaxResult};
 use std::fmt::Write;
-use crate::util::read_input;
-
-pub fn solve() {
-    let input = read_input("day23.txt");
-    let elves = get_elves(&input);
-    let (_, empty_tiles) = tick_and_count(elves, 10);
-    println!("Day 23 part 1: {empty_tiles}");
-    let elves = get_elves(&input);
-    let (_, rounds) = tick_until_static(elves);
-    println!("Day 23 part 2: {rounds}");
-}
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
-struct Elf {
-    x: isize,
-    y: isize,
-}
+use crate::{
+    solution::Solution,
+    util::{Compass, Grid, ParseError, Vec2},
+};
 
-impl Elf {
-    const fn offset(&self, x: isize, y: isize) -> Self {
-        Self { x: self.x + x, y: self.y + y }
-    }
-    const fn north(&self) -> [Self; 3] {
-        [self.offset(0, -1), self.offset(1, -1), self.offset(-1, -1)]
-    }
-    const fn south(&self) -> [Self; 3] {
-        [self.offset(0, 1), self.offset(1, 1), self.offset(-1, 1)]
+pub struct Day23;
+
+impl Solution for Day23 {
+    const DAY: u8 = 23;
+    type Input = HashSet<Elf>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> color_eyre::Result<Self::Input> {
+        get_elves(input)
     }
-    const fn west(&self) -> [Self; 3] {
-        [self.offset(-1, 0), self.offset(-1, 1), self.offset(-1, -1)]
+
+    fn part1(elves: &Self::Input) -> color_eyre::Result<usize> {
+        let (_, empty_tiles) = tick_and_count(elves.clone(), 10);
+        Ok(empty_tiles)
     }
 
-    const fn east(&self) -> [Self; 3] {
-        [self.offset(1, 0), self.offset(1, 1), self.offset(1, -1)]
+    fn part2(elves: &Self::Input) -> color_eyre::Result<usize> {
+        let (_, rounds) = tick_until_static(elves.clone());
+        Ok(rounds)
     }
 }
 
+pub type Elf = Vec2<isize>;
+
+/// The directions the elves consider, in the order of the first round
+const PROPOSALS: [Compass; 4] = [Compass::N, Compass::S, Compass::W, Compass::E];
+
+/// The three positions that have to be free to move in a direction
+fn looking(elf: Elf, direction: Compass) -> [Elf; 3] {
+    [
+        direction.turn_half_left(),
+        direction,
+        direction.turn_half_right(),
+    ]
+    .map(|d| elf + d.into())
+}
 
 fn tick(elves: &HashSet<Elf>, iteration: usize) -> (HashSet<Elf>, bool) {
     let mut targets: HashMap<Elf, Vec<usize>> = HashMap::new();
     let elves_list = elves.iter().collect_vec();
     for (i, elf) in elves_list.iter().enumerate() {
-        let mut alone = true;
-        for (x, y) in (-1..=1).cartesian_product(-1..=1) {
-            if x == 0 && y == 0 {
-                continue;
-            }
-            if elves_list.contains(&&Elf { x: elf.x + x, y: elf.y + y }) {
-                alone = false;
-                break;
-            }
-        }
+        let alone = elf
+            .neighbours_with_diagonals()
+            .all(|neighbour| !elves.contains(&neighbour));
         if alone {
             targets.try_insert(**elf, vec![i]).unwrap();
         } else {
             let mut target_found = false;
-            for check in 0..4 {
-                match (iteration + check) % 4 {
-                    0 => {
-                        if elf.north().iter().all(|e| !elves_list.contains(&e)) {
-                            targets.entry(Elf { x: elf.x, y: elf.y - 1 }).and_modify(|e| e.push(i)).or_insert_with(||vec![i]);
-                            target_found = true;
-                            break;
-                        }
-                    }
-                    1 => {
-                        if elf.south().iter().all(|e| !elves_list.contains(&e)) {
-                            targets.entry(Elf { x: elf.x, y: elf.y + 1 }).and_modify(|e| e.push(i)).or_insert_with(||vec![i]);
-                            target_found = true;
-                            break;
-                        }
-                    }
-                    2 => {
-                        if elf.west().iter().all(|e| !elves_list.contains(&e)) {
-                            targets.entry(Elf { x: elf.x - 1, y: elf.y }).and_modify(|e| e.push(i)).or_insert_with(||vec![i]);
-                            target_found = true;
-                            break;
-                        }
-                    }
-                    3 => {
-                        if elf.east().iter().all(|e| !elves_list.contains(&e)) {
-                            targets.entry(Elf { x: elf.x + 1, y: elf.y }).and_modify(|e| e.push(i)).or_insert_with(||vec![i]);
-                            target_found = true;
-                            break;
-                        }
-                    }
-                    _ => unreachable!()
+            for check in 0..PROPOSALS.len() {
+                let direction = PROPOSALS[(iteration + check) % PROPOSALS.len()];
+                if looking(**elf, direction).iter().all(|e| !elves.contains(e)) {
+                    targets
+                        .entry(**elf + direction.into())
+                        .and_modify(|e| e.push(i))
+                        .or_insert_with(|| vec![i]);
+                    target_found = true;
+                    break;
                 }
             }
             if !target_found {
@@ -116,8 +93,9 @@ fn tick_and_count(mut elves: HashSet<Elf>, rounds: usize) -> (HashSet<Elf>, usiz
         (elves, _) = tick(&elves, i);
         //println!("{}", to_string(&elves));
     }
-    let (MinMaxResult::MinMax(x_min, x_max), MinMaxResult::MinMax(y_min, y_max)) = get_dimensions(&elves) else { panic!() };
-    let v = (x_max.abs_diff(x_min) + 1) * (y_max.abs_diff(y_min) + 1) - elves.len();
+    let v = get_dimensions(&elves).map_or(0, |((x_min, x_max), (y_min, y_max))| {
+        (x_max.abs_diff(x_min) + 1) * (y_max.abs_diff(y_min) + 1) - elves.len()
+    });
     (elves, v)
 }
 
@@ -133,19 +111,22 @@ fn tick_until_static(mut elves: HashSet<Elf>) -> (HashSet<Elf>, usize) {
     }
 }
 
-fn get_dimensions(elves: &HashSet<Elf>) -> (MinMaxResult<isize>, MinMaxResult<isize>) {
-    let x = elves.iter().map(|e| e.x).minmax();
-    let y = elves.iter().map(|e| e.y).minmax();
-    (x, y)
+/// The smallest and largest `x` and `y` of the elves, `None` if there are none
+fn get_dimensions(elves: &HashSet<Elf>) -> Option<((isize, isize), (isize, isize))> {
+    let x = elves.iter().map(|e| e.x).minmax().into_option()?;
+    let y = elves.iter().map(|e| e.y).minmax().into_option()?;
+    Some((x, y))
 }
 
 #[allow(unused)]
 fn to_string(elves: &HashSet<Elf>) -> String {
     let mut result = String::new();
-    let (MinMaxResult::MinMax(x_min, x_max), MinMaxResult::MinMax(y_min, y_max)) = get_dimensions(elves) else { panic!() };
+    let Some(((x_min, x_max), (y_min, y_max))) = get_dimensions(elves) else {
+        return result;
+    };
     for y in y_min..=y_max {
         for x in x_min..=x_max {
-            if elves.contains(&Elf { x, y }) {
+            if elves.contains(&Elf::new(x, y)) {
                 write!(result, "#").unwrap();
             } else {
                 write!(result, ".").unwrap();
@@ -156,28 +137,27 @@ fn to_string(elves: &HashSet<Elf>) -> String {
     result
 }
 
-fn get_elves(input: &str) -> HashSet<Elf> {
-    input
-        .trim()
-        .lines()
-        .enumerate()
-        .flat_map(|(y, line)|
-            line
-                .trim()
-                .chars()
-                .enumerate()
-                .filter_map(move |(x, e)| {
-                    if e == '#' {
-                        Some(Elf { x: isize::try_from(x).unwrap(), y: isize::try_from(y).unwrap() })
-                    } else { None }
-                }))
-        .collect()
+fn get_elves(input: &str) -> color_eyre::Result<HashSet<Elf>> {
+    let grid = Grid::parse_ragged(input.trim_end(), false, |c| match c {
+        '#' => Ok(true),
+        '.' => Ok(false),
+        _ => Err(color_eyre::eyre::eyre!("Unknown tile '{c}'")),
+    })?;
+    let elves: HashSet<Elf> = grid
+        .positions()
+        .filter(|&position| grid[position])
+        .map(|(x, y)| Ok(Elf::new(isize::try_from(x)?, isize::try_from(y)?)))
+        .collect::<color_eyre::Result<_>>()?;
+    if elves.is_empty() {
+        return Err(ParseError::at(input, "", "There are no elves ('#') in the grove").into());
+    }
+    Ok(elves)
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::util::read_example;
     use super::*;
+    use crate::util::read_example;
 
     #[test]
     fn it_prints_grid() {
@@ -188,7 +168,7 @@ mod tests {
 #.###..
 ##.#.##
 .#..#..";
-        let elves = get_elves(&expected);
+        let elves = get_elves(expected).unwrap();
         assert_eq!(expected, to_string(&elves).trim());
     }
 
@@ -205,15 +185,36 @@ mod tests {
 #.
 .#
 #.";
-        let elves = get_elves(input);
+        let elves = get_elves(input).unwrap();
         let (elves, _) = tick(&elves, 0);
         assert_eq!(to_string(&elves).trim_end(), expected);
     }
 
+    #[test]
+    fn it_locates_parse_errors() {
+        let err = get_elves("..#\n.x.")
+            .unwrap_err()
+            .downcast::<crate::util::ParseError>()
+            .unwrap();
+        assert_eq!((2, 2), (err.line, err.column));
+        let err = get_elves("...\n...")
+            .unwrap_err()
+            .downcast::<crate::util::ParseError>()
+            .unwrap();
+        assert_eq!((2, 4), (err.line, err.column));
+        assert!(get_elves("").is_err());
+    }
+
+    #[test]
+    fn it_counts_a_single_elf() {
+        let elves = get_elves("..#").unwrap();
+        assert_eq!(0, tick_and_count(elves, 10).1);
+    }
+
     #[test]
     fn it_counts_empty_tiles_after_rounds() {
         let input = read_example("day23.txt");
-        let elves = get_elves(&input);
+        let elves = get_elves(&input).unwrap();
         let (elves, empty_tiles) = tick_and_count(elves, 10);
         println!("{}", to_string(&elves));
         assert_eq!(110, empty_tiles);
@@ -222,9 +223,9 @@ mod tests {
     #[test]
     fn it_runs_until_it_doesnt() {
         let input = read_example("day23.txt");
-        let elves = get_elves(&input);
+        let elves = get_elves(&input).unwrap();
         let (elves, count) = tick_until_static(elves);
         println!("{}", to_string(&elves));
         assert_eq!(20, count);
     }
-}
\ No newline at end of file
+}
@@ -1,26 +1,45 @@
-use std::collections::HashSet;
+use crate::{
+    solution::Solution,
+    util::{search, Direction, Grid, ParseError, Vec2},
+};
+use color_eyre::eyre::eyre;
 use std::fmt::{Display, Formatter};
 use std::str::FromStr;
-use itertools::Itertools;
-use crate::util::{Direction, read_input};
 
-pub fn solve() {
-    let input = read_input("day24.txt");
-    let mut valley: Valley = input.parse().unwrap();
-    println!("Day 24 part 1: {}", valley.find_fastest_path(Point::new(1, 0), Point::new(valley.width - 2, valley.height - 1)));
-    let mut valley: Valley = input.parse().unwrap();
-    let s = valley.find_fastest_path(Point::new(1, 0), Point::new(valley.width - 2, valley.height - 1))
-        + valley.find_fastest_path(Point::new(valley.width - 2, valley.height - 1), Point::new(1, 0))
-        + valley.find_fastest_path(Point::new(1, 0), Point::new(valley.width - 2, valley.height - 1));
-    println!("Day 24 part 2: {s}"); // 688 is too low, strangely (690 also, to verify it's not an offbytwo)
-}
+pub struct Day24;
+
+impl Solution for Day24 {
+    const DAY: u8 = 24;
+    type Input = Valley;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> color_eyre::Result<Self::Input> {
+        input.parse()
+    }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
-struct Point {
-    x: u8,
-    y: u8,
+    fn part1(valley: &Self::Input) -> color_eyre::Result<usize> {
+        let (start, goal) = valley.entrance_and_exit();
+        valley.find_fastest_path(start, goal, 0)
+    }
+
+    fn part2(valley: &Self::Input) -> color_eyre::Result<usize> {
+        let (start, goal) = valley.entrance_and_exit();
+        let there = valley.find_fastest_path(start, goal, 0)?;
+        let back = valley.find_fastest_path(goal, start, there)?;
+        valley.find_fastest_path(start, goal, back)
+    }
 }
 
+type Point = Vec2<usize>;
+
+const DIRECTIONS: [Direction; 4] = [
+    Direction::Up,
+    Direction::Right,
+    Direction::Down,
+    Direction::Left,
+];
+
 impl TryFrom<char> for Direction {
     type Error = color_eyre::Report;
 
@@ -30,118 +49,124 @@ impl TryFrom<char> for Direction {
             '>' => Ok(Self::Right),
             'v' => Ok(Self::Down),
             '<' => Ok(Self::Left),
-            _ => Err(color_eyre::eyre::eyre!("Illegal direction: {value}"))
+            _ => Err(color_eyre::eyre::eyre!("Illegal direction: {value}")),
         }
     }
 }
 
-impl Point {
-    const fn new(x: u8, y: u8) -> Self {
-        Self { x, y }
-    }
-}
-
-#[derive(Default)]
-struct Valley {
-    blizzards: Vec<Blizzard>,
-    width: u8,
-    height: u8,
+/// A valley of at least 1x1 inside its walls, as [`Valley::from_str`] checks
+#[derive(Clone)]
+pub struct Valley {
+    /// Where the blizzards are at the start
+    blizzards: Grid<Option<Direction>>,
+    /// `true` for the walls around the valley
+    walls: Grid<bool>,
 }
 
 impl Valley {
-    fn find_fastest_path(&mut self, from: Point, to: Point) -> usize {
-        let mut ticks = 0;
-        let mut expeditions = HashSet::new();
-        expeditions.insert(from);
-        loop {
-            self.tick_blizzards();
-            expeditions = self.get_expedition_movements(&expeditions);
-            ticks += 1;
-            //dbg!(&expeditions.len());
-            if expeditions.iter().any(|expedition| *expedition == to) {
-                return ticks;
-            }
+    const fn width(&self) -> usize {
+        self.walls.width()
+    }
+
+    const fn height(&self) -> usize {
+        self.walls.height()
+    }
+
+    /// The size of the area the blizzards move in
+    const fn inner(&self) -> (usize, usize) {
+        (self.width() - 2, self.height() - 2)
+    }
+
+    /// After this many minutes, all blizzards are back where they started, which is at least 1
+    const fn period(&self) -> usize {
+        let (mut a, mut b) = self.inner();
+        while b != 0 {
+            (a, b) = (b, a % b);
         }
+        self.inner().0 * self.inner().1 / a
     }
-    fn contains_blizzard(&self, point: Point) -> bool {
-        self.blizzards.iter().any(|b| b.position == point)
+
+    const fn entrance_and_exit(&self) -> (Point, Point) {
+        (
+            Point::new(1, 0),
+            Point::new(self.width() - 2, self.height() - 1),
+        )
     }
-    fn get_expedition_movements(&self, expeditions: &HashSet<Point>) -> HashSet<Point> {
-        let mut new_expeditions = HashSet::new();
-        for expedition in expeditions {
-            if !self.contains_blizzard(*expedition) {
-                // wait
-                new_expeditions.insert(*expedition);
-            }
-            if expedition.y > 1 || expedition.y == 1 && expedition.x == 1 {
-                // up
-                let target = Point::new(expedition.x, expedition.y - 1);
-                if !self.contains_blizzard(target) {
-                    new_expeditions.insert(target);
-                }
-            }
-            if expedition.y < self.height - 2 || expedition.y == self.height - 2 && expedition.x == self.width - 2 {
-                // down
-                let target = Point::new(expedition.x, expedition.y + 1);
-                if !self.contains_blizzard(target) {
-                    new_expeditions.insert(target);
-                }
-            }
-            if expedition.x > 1 {
-                // left
-                let target = Point::new(expedition.x - 1, expedition.y);
-                if !self.contains_blizzard(target) {
-                    new_expeditions.insert(target);
-                }
-            }
-            if expedition.x < self.width - 2 && expedition.y > 0 {
-                // right
-                let target = Point::new(expedition.x + 1, expedition.y);
-                if !self.contains_blizzard(target) {
-                    new_expeditions.insert(target);
-                }
-            }
+
+    /// The minute the expedition arrives at `to` at the earliest, when leaving `from` at minute `start`
+    fn find_fastest_path(&self, from: Point, to: Point, start: usize) -> color_eyre::Result<usize> {
+        let period = self.period();
+        // the blizzards repeat, so there are only `period` different states per position
+        search::bfs(
+            [(from, start % period)],
+            |(position, time): (Point, usize)| {
+                let time = (time + 1) % period;
+                // wait or move to any free neighbour
+                std::iter::once(position)
+                    .chain(position.neighbours())
+                    .filter(move |&target| {
+                        self.walls.get(target.x, target.y) == Some(&false)
+                            && !self.contains_blizzard(target, time)
+                    })
+                    .map(move |target| (target, time))
+            },
+        )
+        .find(|((position, _), _)| *position == to)
+        .map(|(_, minutes)| start + minutes)
+        .ok_or_else(|| eyre!("There is no way from {from} to {to} through the valley"))
+    }
+
+    fn contains_blizzard(&self, point: Point, time: usize) -> bool {
+        DIRECTIONS
+            .into_iter()
+            .any(|direction| self.contains_blizzard_moving(point, direction, time))
+    }
+
+    /// Whether a blizzard moving in a direction is at a position at a minute. The only blizzard
+    /// that can be there started in the same row or column, `time` steps against its direction.
+    fn contains_blizzard_moving(&self, point: Point, direction: Direction, time: usize) -> bool {
+        let (width, height) = self.inner();
+        if !(1..=width).contains(&point.x) || !(1..=height).contains(&point.y) {
+            return false;
         }
-        new_expeditions
+        let (x, y) = (point.x - 1, point.y - 1);
+        let (dx, dy) = (time % width, time % height);
+        let origin = match direction {
+            Direction::Up => (point.x, (y + dy) % height + 1),
+            Direction::Right => ((x + width - dx) % width + 1, point.y),
+            Direction::Down => (point.x, (y + height - dy) % height + 1),
+            Direction::Left => ((x + dx) % width + 1, point.y),
+        };
+        self.blizzards[origin] == Some(direction)
     }
-    fn tick_blizzards(&mut self) {
-        for blizzard in &mut self.blizzards {
-            match blizzard.direction {
-                Direction::Up => {
-                    blizzard.position.y -= 1;
-                    if blizzard.position.y == 0 {
-                        blizzard.position.y = self.height - 2;
-                    }
-                }
-                Direction::Right => {
-                    blizzard.position.x += 1;
-                    if blizzard.position.x == self.width - 1 {
-                        blizzard.position.x = 1;
-                    }
-                }
-                Direction::Down => {
-                    blizzard.position.y += 1;
-                    if blizzard.position.y == self.height - 1 {
-                        blizzard.position.y = 1;
-                    }
+
+    /// Draws the valley at a minute
+    fn render(&self, time: usize) -> String {
+        let mut drawing = String::new();
+        for (y, row) in self.walls.rows().enumerate() {
+            for (x, &wall) in row.iter().enumerate() {
+                if wall {
+                    drawing.push('#');
+                    continue;
                 }
-                Direction::Left => {
-                    blizzard.position.x -= 1;
-                    if blizzard.position.x == 0 {
-                        blizzard.position.x = self.width - 2;
-                    }
+                let blizzards: Vec<Direction> = DIRECTIONS
+                    .into_iter()
+                    .filter(|&direction| {
+                        self.contains_blizzard_moving(Point::new(x, y), direction, time)
+                    })
+                    .collect();
+                match blizzards.len() {
+                    0 => drawing.push('.'),
+                    1 => drawing.push_str(&blizzards[0].to_string()),
+                    n => drawing.push_str(&n.to_string()),
                 }
             }
+            drawing.push('\n');
         }
+        drawing
     }
 }
 
-#[derive(Debug, Clone)]
-struct Blizzard {
-    direction: Direction,
-    position: Point,
-}
-
 impl Display for Direction {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -155,30 +180,7 @@ impl Display for Direction {
 
 impl Display for Valley {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let right_wall = self.width - 1;
-        let bottom_wall = self.height - 1;
-        for y in 0..self.height {
-            for x in 0..self.width {
-                match (x, y) {
-                    (0, _) => write!(f, "#")?,
-                    (x, _) if x == right_wall => write!(f, "#")?,
-                    (x, 0) if x != 1 => write!(f, "#")?,
-                    (x, y) if y == bottom_wall && x != right_wall - 1 => write!(f, "#")?,
-                    (x, y) => {
-                        let p = Point { x, y };
-
-                        let blizzards = self.blizzards.iter().filter_map(|b| if b.position == p { Some(b.direction) } else { None }).collect_vec();
-                        match blizzards.len() {
-                            0 => write!(f, "."),
-                            1 => write!(f, "{}", blizzards[0]),
-                            x => write!(f, "{x}")
-                        }?;
-                    }
-                }
-            }
-            writeln!(f)?;
-        }
-        Ok(())
+        write!(f, "{}", self.render(0))
     }
 }
 
@@ -186,87 +188,151 @@ impl FromStr for Valley {
     type Err = color_eyre::Report;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let height = s.trim().lines().count();
-        let width = s.trim().lines().next().ok_or_else(|| color_eyre::eyre::eyre!("Empty input"))?.len();
-        let mut valley = Self {
-            width: width.try_into()?,
-            height: height.try_into()?,
-            ..Default::default()
-        };
+        let s = s.trim_end();
+        let walls = Grid::parse(s, |c| match c {
+            '#' => Ok(true),
+            '.' | '^' | '>' | 'v' | '<' => Ok(false),
+            _ => Err(eyre!("Unknown tile '{c}'")),
+        })?;
+        let blizzards = Grid::parse(s, |c| match c {
+            '#' | '.' => Ok(None),
+            c => Direction::try_from(c).map(Some),
+        })?;
+        let valley = Self { blizzards, walls };
+        valley.check_walls(s)?;
+        Ok(valley)
+    }
+}
 
-        for (y, line) in s.trim().lines().enumerate() {
-            for (x, c) in line.trim().chars().enumerate() {
-                if ['^', '>', 'v', '<'].contains(&c) {
-                    valley.blizzards.push(Blizzard { direction: c.try_into()?, position: Point::new(u8::try_from(x)?, u8::try_from(y)?) });
+impl Valley {
+    /// Checks that the valley has room inside and is walled in, but for the entrance and the exit
+    fn check_walls(&self, s: &str) -> Result<(), ParseError> {
+        let (width, height) = (self.width(), self.height());
+        if width < 3 || height < 3 {
+            let message = format!(
+                "The valley is {width}x{height}, but has to be at least 3x3 with its walls"
+            );
+            return Err(ParseError::new(
+                1,
+                1,
+                s.lines().next().unwrap_or_default(),
+                message,
+            ));
+        }
+        let (entrance, exit) = self.entrance_and_exit();
+        for (y, line) in s.lines().enumerate() {
+            for x in 0..width {
+                let point = Point::new(x, y);
+                let open = !self.walls[(x, y)] && self.blizzards[(x, y)].is_none();
+                let border = x == 0 || y == 0 || x == width - 1 || y == height - 1;
+                let message = if point == entrance {
+                    (!open).then_some("The entrance has to be open")
+                } else if point == exit {
+                    (!open).then_some("The exit has to be open")
+                } else {
+                    (border && !self.walls[(x, y)])
+                        .then_some("The valley has to be surrounded by a wall")
+                };
+                if let Some(message) = message {
+                    return Err(ParseError::new(y + 1, x + 1, line, message));
                 }
             }
         }
-        Ok(valley)
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::util::read_example;
     use super::*;
+    use crate::util::read_example;
 
     #[test]
     fn it_parses_valley() {
         let input = read_example("day24_1.txt");
         let valley: Valley = input.parse().unwrap();
-        assert_eq!(2, valley.blizzards.len());
+        assert_eq!(2, valley.blizzards.iter().flatten().count());
+        let err = input.replace("#.#", "#o#").parse::<Valley>().err().unwrap();
+        assert_eq!(1, err.downcast::<crate::util::ParseError>().unwrap().line);
+    }
+
+    #[test]
+    fn it_rejects_valleys_without_walls() {
+        let error = |input: &str| {
+            input
+                .parse::<Valley>()
+                .err()
+                .unwrap()
+                .downcast::<ParseError>()
+                .unwrap()
+        };
+        assert!(error("").message.contains("at least 3x3"));
+        assert!(error("#.#\n#.#").message.contains("at least 3x3"));
+        let err = error("#.#\n#..\n#.#");
+        assert_eq!(
+            (2, 3, "The valley has to be surrounded by a wall"),
+            (err.line, err.column, err.message.as_str())
+        );
+        let err = error("###\n#.#\n#.#");
+        assert_eq!(
+            (1, 2, "The entrance has to be open"),
+            (err.line, err.column, err.message.as_str())
+        );
+        let err = error("#.#\n#.#\n#v#");
+        assert_eq!(
+            (3, 2, "The exit has to be open"),
+            (err.line, err.column, err.message.as_str())
+        );
+        assert!("#.#\n#.#\n#.#".parse::<Valley>().is_ok());
     }
 
     #[test]
     fn it_displays_valley() {
-        let mut input = read_example("day24_1.txt");
+        let input = read_example("day24_1.txt");
         let valley: Valley = input.parse().unwrap();
         assert_eq!(valley.to_string().trim(), input.trim());
     }
 
     #[test]
     fn it_ticks_blizzards() {
-        let mut input = read_example("day24_1.txt");
-        let mut valley: Valley = input.parse().unwrap();
-        input.replace_range(1..=1, "E");
-        valley.tick_blizzards();
-        valley.tick_blizzards();
+        let valley: Valley = read_example("day24_1.txt").parse().unwrap();
         let expected = r"#.#####
 #...v.#
 #..>..#
 #.....#
 #.....#
 #.....#
-#####.#".replace('\r', "");
-        assert_eq!(valley.to_string().trim(), expected.trim());
-        valley.tick_blizzards();
+#####.#"
+            .replace('\r', "");
+        assert_eq!(valley.render(2).trim(), expected.trim());
         let expected = r"#.#####
 #.....#
 #...2.#
 #.....#
 #.....#
 #.....#
-#####.#".replace('\r', "");
-        assert_eq!(valley.to_string().trim(), expected.trim());
+#####.#"
+            .replace('\r', "");
+        assert_eq!(valley.render(3).trim(), expected.trim());
+        assert_eq!(valley.render(0), valley.render(valley.period()));
     }
 
     #[test]
     fn it_finds_fastest_path() {
         let input = read_example("day24_2.txt");
-        let mut valley: Valley = input.parse().unwrap();
-        assert_eq!(valley.find_fastest_path(Point::new(1, 0), Point::new(valley.width - 2, valley.height - 1)), 18);
+        let valley: Valley = input.parse().unwrap();
+        let (start, goal) = valley.entrance_and_exit();
+        assert_eq!(18, valley.find_fastest_path(start, goal, 0).unwrap());
     }
 
     #[test]
     fn it_finds_fastest_path_to_and_back_and_to_again() {
         let input = read_example("day24_2.txt");
-        let mut valley: Valley = input.parse().unwrap();
-        let a = valley.find_fastest_path(Point::new(1, 0), Point::new(valley.width - 2, valley.height - 1));
-        let b = valley.find_fastest_path(Point::new(valley.width - 2, valley.height - 1), Point::new(1, 0));
-        let c = valley.find_fastest_path(Point::new(1, 0), Point::new(valley.width - 2, valley.height - 1));
-        dbg!(a);
-        dbg!(b);
-        dbg!(c);
-        assert_eq!(a+b+c, 54);
+        let valley: Valley = input.parse().unwrap();
+        let (start, goal) = valley.entrance_and_exit();
+        let there = valley.find_fastest_path(start, goal, 0).unwrap();
+        let back = valley.find_fastest_path(goal, start, there).unwrap();
+        assert_eq!(41, back);
+        assert_eq!(54, valley.find_fastest_path(start, goal, back).unwrap());
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,292 @@
+use std::{
+    fmt::Display,
+    iter::Sum,
+    ops::{Add, AddAssign},
+    str::FromStr,
+};
+
+use color_eyre::eyre::eyre;
+
+use crate::{
+    solution::Solution,
+    util::{parse, ParseError},
+};
+
+pub struct Day25;
+
+impl Solution for Day25 {
+    const DAY: u8 = 25;
+    type Input = Vec<Snafu>;
+    type Answer1 = Snafu;
+    type Answer2 = Snafu;
+
+    fn parse(input: &str) -> color_eyre::Result<Self::Input> {
+        Ok(parse::lines(input, str::parse)?)
+    }
+
+    fn part1(numbers: &Self::Input) -> color_eyre::Result<Snafu> {
+        Ok(numbers.iter().sum())
+    }
+
+    // There is no puzzle for part 2, it is given for finishing all other days.
+}
+
+/// A number in balanced base 5, written with the digits `=` (-2), `-` (-1), `0`, `1` and `2`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Snafu {
+    /// Least significant digit first, without leading zeros (so zero has no digits)
+    digits: Vec<i8>,
+}
+
+impl Snafu {
+    fn from_digits(mut digits: Vec<i8>) -> Self {
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+        Self { digits }
+    }
+
+    fn to_i128(&self) -> Option<i128> {
+        self.digits.iter().rev().try_fold(0_i128, |acc, &digit| {
+            acc.checked_mul(5)?.checked_add(i128::from(digit))
+        })
+    }
+}
+
+impl FromStr for Snafu {
+    type Err = color_eyre::Report;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let s = input.trim();
+        if s.is_empty() {
+            return Err(ParseError::at(input, s, "Cannot parse an empty SNAFU number").into());
+        }
+        let digits = s
+            .char_indices()
+            .rev()
+            .map(|(i, c)| match c {
+                '=' => Ok(-2),
+                '-' => Ok(-1),
+                '0' => Ok(0),
+                '1' => Ok(1),
+                '2' => Ok(2),
+                _ => Err(ParseError::at(
+                    input,
+                    &s[i..],
+                    format!("Invalid SNAFU digit '{c}' in '{s}'"),
+                )),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self::from_digits(digits))
+    }
+}
+
+impl Display for Snafu {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.digits.is_empty() {
+            return write!(f, "0");
+        }
+        for digit in self.digits.iter().rev() {
+            let c = match digit {
+                -2 => '=',
+                -1 => '-',
+                0 => '0',
+                1 => '1',
+                2 => '2',
+                _ => unreachable!("SNAFU digits are between -2 and 2"),
+            };
+            write!(f, "{c}")?;
+        }
+        Ok(())
+    }
+}
+
+impl From<i128> for Snafu {
+    fn from(mut value: i128) -> Self {
+        let mut digits = Vec::new();
+        while value != 0 {
+            // -2..=2 instead of 0..5, so 3 and 4 are carried over to the next digit
+            let remainder = value.rem_euclid(5);
+            let carry = i128::from(remainder > 2);
+            digits.push(i8::try_from(remainder - 5 * carry).expect("Digit is between -2 and 2"));
+            value = value.div_euclid(5) + carry;
+        }
+        Self { digits }
+    }
+}
+
+impl From<i64> for Snafu {
+    fn from(value: i64) -> Self {
+        i128::from(value).into()
+    }
+}
+
+impl TryFrom<&Snafu> for i128 {
+    type Error = color_eyre::Report;
+
+    fn try_from(value: &Snafu) -> Result<Self, Self::Error> {
+        value
+            .to_i128()
+            .ok_or_else(|| eyre!("{value} does not fit into an i128"))
+    }
+}
+
+impl TryFrom<&Snafu> for i64 {
+    type Error = color_eyre::Report;
+
+    fn try_from(value: &Snafu) -> Result<Self, Self::Error> {
+        value
+            .to_i128()
+            .and_then(|value| Self::try_from(value).ok())
+            .ok_or_else(|| eyre!("{value} does not fit into an i64"))
+    }
+}
+
+impl AddAssign<&Self> for Snafu {
+    /// Adds digit by digit, carrying over if the sum leaves the range of a digit
+    fn add_assign(&mut self, rhs: &Self) {
+        let length = self.digits.len().max(rhs.digits.len()) + 1;
+        self.digits.resize(length, 0);
+        let mut carry = 0;
+        for (i, digit) in self.digits.iter_mut().enumerate() {
+            let mut sum = *digit + rhs.digits.get(i).copied().unwrap_or_default() + carry;
+            carry = 0;
+            if sum > 2 {
+                sum -= 5;
+                carry = 1;
+            } else if sum < -2 {
+                sum += 5;
+                carry = -1;
+            }
+            *digit = sum;
+        }
+        *self = Self::from_digits(std::mem::take(&mut self.digits));
+    }
+}
+
+impl AddAssign for Snafu {
+    fn add_assign(&mut self, rhs: Self) {
+        *self += &rhs;
+    }
+}
+
+impl Add for Snafu {
+    type Output = Self;
+
+    fn add(mut self, rhs: Self) -> Self::Output {
+        self += &rhs;
+        self
+    }
+}
+
+impl<'a> Add<&'a Snafu> for &Snafu {
+    type Output = Snafu;
+
+    fn add(self, rhs: &'a Snafu) -> Self::Output {
+        let mut sum = self.clone();
+        sum += rhs;
+        sum
+    }
+}
+
+impl<'a> Sum<&'a Self> for Snafu {
+    fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), |mut sum, number| {
+            sum += number;
+            sum
+        })
+    }
+}
+
+impl Sum for Snafu {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), Add::add)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::util::read_example;
+
+    use super::*;
+
+    const EXAMPLES: [(i64, &str); 15] = [
+        (0, "0"),
+        (1, "1"),
+        (2, "2"),
+        (3, "1="),
+        (4, "1-"),
+        (5, "10"),
+        (6, "11"),
+        (7, "12"),
+        (8, "2="),
+        (9, "2-"),
+        (10, "20"),
+        (15, "1=0"),
+        (20, "1-0"),
+        (2022, "1=11-2"),
+        (314_159_265, "1121-1110-1=0"),
+    ];
+
+    #[test]
+    fn it_converts_decimals() {
+        for (decimal, snafu) in EXAMPLES {
+            assert_eq!(snafu, Snafu::from(decimal).to_string());
+            assert_eq!(
+                decimal,
+                i64::try_from(&snafu.parse::<Snafu>().unwrap()).unwrap()
+            );
+        }
+        assert_eq!("-", Snafu::from(-1_i64).to_string());
+        assert_eq!(
+            -2022,
+            i64::try_from(&"-2--1=".parse::<Snafu>().unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn it_converts_large_numbers() {
+        let max = Snafu::from(i128::MAX);
+        assert_eq!(i128::MAX, i128::try_from(&max).unwrap());
+        assert!(i64::try_from(&max).is_err());
+        assert!(i128::try_from(&(&max + &Snafu::from(1_i64))).is_err());
+    }
+
+    #[test]
+    fn it_adds_in_snafu_digits() {
+        for (a, _) in EXAMPLES {
+            for (b, _) in EXAMPLES {
+                assert_eq!(Snafu::from(a + b), Snafu::from(a) + Snafu::from(b));
+                assert_eq!(Snafu::from(a - b), Snafu::from(a) + Snafu::from(-b));
+            }
+        }
+        assert_eq!(
+            Snafu::default(),
+            Snafu::from(2022_i64) + Snafu::from(-2022_i64)
+        );
+    }
+
+    #[test]
+    fn it_rejects_invalid_digits() {
+        let err = "1=3"
+            .parse::<Snafu>()
+            .unwrap_err()
+            .downcast::<ParseError>()
+            .unwrap();
+        assert_eq!((1, 3), (err.line, err.column));
+        let err = Day25::parse("1=\n 2x")
+            .unwrap_err()
+            .downcast::<ParseError>()
+            .unwrap();
+        assert_eq!((2, 3), (err.line, err.column));
+        assert!("".parse::<Snafu>().is_err());
+    }
+
+    #[test]
+    fn it_sums_fuel_requirements() {
+        let input = read_example("day25.txt");
+        assert_eq!("2=-1=0", Day25::solve_part1(&input).unwrap().to_string());
+        let numbers = Day25::parse(&input).unwrap();
+        assert_eq!(4890, i64::try_from(&numbers.iter().sum::<Snafu>()).unwrap());
+    }
+}
@@ -0,0 +1,258 @@
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use color_eyre::{
+    eyre::{eyre, WrapErr},
+    Section,
+};
+
+/// Environment variable holding the session cookie used to download puzzle inputs
+pub const SESSION_VAR: &str = "AOC2022_SESSION";
+/// Environment variable overriding the server puzzle inputs are downloaded from
+pub const BASE_URL_VAR: &str = "AOC2022_BASE_URL";
+
+/// Something that can obtain the puzzle input of a day, e.g. from the Advent of Code website
+pub trait Fetcher {
+    /// # Errors
+    /// If the input cannot be obtained
+    fn fetch(&self, day: u8) -> color_eyre::Result<String>;
+}
+
+/// Downloads `{base_url}/day/{day}/input` with the given session cookie
+pub struct HttpFetcher {
+    base_url: String,
+    session: String,
+    agent: ureq::Agent,
+}
+
+impl HttpFetcher {
+    pub const DEFAULT_BASE_URL: &'static str = "https://adventofcode.com/2022";
+
+    #[must_use]
+    pub fn new(base_url: impl Into<String>, session: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_owned(),
+            session: session.into(),
+            agent: ureq::AgentBuilder::new()
+                .timeout(Duration::from_secs(30))
+                .user_agent("github.com/seeba8/aoc2022")
+                .build(),
+        }
+    }
+
+    /// Configured by [`SESSION_VAR`] and [`BASE_URL_VAR`]; `None` if there is no session
+    #[must_use]
+    pub fn from_env() -> Option<Self> {
+        let session = std::env::var(SESSION_VAR)
+            .ok()
+            .filter(|s| !s.trim().is_empty())?;
+        let base_url =
+            std::env::var(BASE_URL_VAR).unwrap_or_else(|_| Self::DEFAULT_BASE_URL.to_owned());
+        Some(Self::new(base_url, session.trim()))
+    }
+}
+
+impl Fetcher for HttpFetcher {
+    fn fetch(&self, day: u8) -> color_eyre::Result<String> {
+        let url = format!("{}/day/{day}/input", self.base_url);
+        let response = self
+            .agent
+            .get(&url)
+            .set("Cookie", &format!("session={}", self.session))
+            .call()
+            .map_err(|err| match err {
+                ureq::Error::Status(status, _) => eyre!("'{url}' responded with status {status}"),
+                ureq::Error::Transport(transport) => eyre!("Cannot request {transport}"),
+            })
+            .suggestion(format!(
+                "Check that {SESSION_VAR} holds a valid session cookie"
+            ))?;
+        response
+            .into_string()
+            .wrap_err_with(|| format!("Cannot read the response of '{url}'"))
+    }
+}
+
+/// Looks up puzzle inputs in a local cache directory (as `dayNN.txt`)
+/// and fills the cache through the fetcher if it is missing.
+pub struct InputProvider {
+    cache_dir: PathBuf,
+    fetcher: Option<Box<dyn Fetcher>>,
+}
+
+impl InputProvider {
+    /// A provider that only reads from the cache
+    #[must_use]
+    pub fn new(cache_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            cache_dir: cache_dir.into(),
+            fetcher: None,
+        }
+    }
+
+    /// A provider downloading missing (or empty) inputs if [`SESSION_VAR`] is set
+    #[must_use]
+    pub fn from_env(cache_dir: impl Into<PathBuf>) -> Self {
+        let provider = Self::new(cache_dir);
+        match HttpFetcher::from_env() {
+            Some(fetcher) => provider.with_fetcher(fetcher),
+            None => provider,
+        }
+    }
+
+    #[must_use]
+    pub fn with_fetcher(mut self, fetcher: impl Fetcher + 'static) -> Self {
+        self.fetcher = Some(Box::new(fetcher));
+        self
+    }
+
+    #[must_use]
+    pub fn path(&self, day: u8) -> PathBuf {
+        self.cache_dir.join(format!("day{day:02}.txt"))
+    }
+
+    /// # Errors
+    /// If the input is neither cached nor can be fetched, or the cache cannot be written
+    pub fn get(&self, day: u8) -> color_eyre::Result<String> {
+        let path = self.path(day);
+        // `new-day` leaves an empty file to be filled in, which is as good as missing
+        let cached = std::fs::metadata(&path).is_ok_and(|meta| meta.is_file() && meta.len() > 0);
+        if cached || (path.is_file() && self.fetcher.is_none()) {
+            return crate::util::read_input_file(&path);
+        }
+        let Some(fetcher) = &self.fetcher else {
+            return crate::util::read_input_file(&path).suggestion(format!(
+                "Set {SESSION_VAR} to your session cookie to download it"
+            ));
+        };
+        let input = fetcher
+            .fetch(day)
+            .wrap_err_with(|| format!("Cannot fetch the puzzle input of day {day}"))?;
+        Self::store(&path, &input)?;
+        Ok(input)
+    }
+
+    fn store(path: &Path, input: &str) -> color_eyre::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .wrap_err_with(|| format!("Cannot create input cache '{}'", dir.display()))?;
+        }
+        std::fs::write(path, input)
+            .wrap_err_with(|| format!("Cannot write input cache '{}'", path.display()))
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::{
+        cell::Cell,
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        rc::Rc,
+        thread::JoinHandle,
+    };
+
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("aoc2022-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    /// Answers a single request with the given status and body and returns the request head
+    fn serve_once(status: &'static str, body: &'static str) -> (String, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}/2022", listener.local_addr().unwrap());
+        let handle = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let head: Vec<String> = BufReader::new(stream.try_clone().unwrap())
+                .lines()
+                .map(Result::unwrap)
+                .take_while(|line| !line.is_empty())
+                .collect();
+            write!(
+                stream,
+                "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+            head
+        });
+        (base_url, handle)
+    }
+
+    struct CountingFetcher(Rc<Cell<usize>>);
+
+    impl Fetcher for CountingFetcher {
+        fn fetch(&self, day: u8) -> color_eyre::Result<String> {
+            self.0.set(self.0.get() + 1);
+            Ok(format!("input of day {day}\n"))
+        }
+    }
+
+    #[test]
+    fn it_fetches_over_http_with_session() {
+        let (base_url, server) = serve_once("200 OK", "1000\n2000\n");
+        let fetcher = HttpFetcher::new(base_url, "secret");
+        assert_eq!("1000\n2000\n", fetcher.fetch(1).unwrap());
+        let head = server.join().unwrap();
+        assert_eq!("GET /2022/day/1/input HTTP/1.1", head[0]);
+        assert!(
+            head.iter().any(|line| line == "Cookie: session=secret"),
+            "{head:?}"
+        );
+    }
+
+    #[test]
+    fn it_reports_http_errors() {
+        let (base_url, server) = serve_once("400 Bad Request", "Please log in");
+        let err = HttpFetcher::new(base_url, "expired").fetch(5).unwrap_err();
+        assert!(err.to_string().contains("400"), "{err}");
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn it_caches_fetched_inputs() {
+        let dir = temp_dir("cache");
+        let calls = Rc::new(Cell::new(0));
+        let provider = InputProvider::new(&dir).with_fetcher(CountingFetcher(Rc::clone(&calls)));
+        assert_eq!("input of day 7\n", provider.get(7).unwrap());
+        assert_eq!("input of day 7\n", provider.get(7).unwrap());
+        assert_eq!(1, calls.get());
+        assert_eq!(
+            "input of day 7\n",
+            std::fs::read_to_string(dir.join("day07.txt")).unwrap()
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn it_replaces_empty_inputs() {
+        let dir = temp_dir("placeholder");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("day07.txt"), "").unwrap();
+        assert_eq!("", InputProvider::new(&dir).get(7).unwrap());
+        let provider = InputProvider::new(&dir).with_fetcher(CountingFetcher(Rc::default()));
+        assert_eq!("input of day 7\n", provider.get(7).unwrap());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn it_prefers_the_cache() {
+        let provider = InputProvider::new("examples/").with_fetcher(CountingFetcher(Rc::default()));
+        assert_eq!(
+            crate::util::read_example("day01.txt"),
+            provider.get(1).unwrap()
+        );
+    }
+
+    #[test]
+    fn it_fails_without_cache_and_fetcher() {
+        let dir = temp_dir("empty");
+        let err = InputProvider::new(&dir).get(3).unwrap_err();
+        assert!(err.to_string().contains("day03.txt"), "{err}");
+    }
+}
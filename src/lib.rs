@@ -0,0 +1,49 @@
+//! Solutions to [Advent of Code 2022](https://adventofcode.com/2022).
+//!
+//! Every day is a module with a `DayNN` type implementing [`solution::Solution`],
+//! next to the types its input is parsed into:
+//!
+//! ```
+//! use aoc2022::{day13::Day13, day21::Monkeys, solution::Solution};
+//!
+//! let pairs = Day13::parse("[1,[2]]\n[1,3]")?;
+//! assert_eq!(1, Day13::part1(&pairs)?);
+//!
+//! let mut monkeys: Monkeys = "root: x * two\nx: 21\ntwo: 2".parse()?;
+//! assert_eq!(Some(42), monkeys.solve("root"));
+//! # Ok::<(), color_eyre::Report>(())
+//! ```
+
+#![deny(clippy::all)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::nursery)]
+#![feature(map_try_insert)]
+
+pub mod day01;
+pub mod day02;
+pub mod day03;
+pub mod day04;
+pub mod day05;
+pub mod day06;
+pub mod day07;
+pub mod day08;
+pub mod day09;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day16;
+pub mod day17;
+pub mod day18;
+pub mod day19;
+pub mod day20;
+pub mod day21;
+pub mod day22;
+pub mod day23;
+pub mod day24;
+pub mod day25;
+pub mod input;
+pub mod solution;
+pub mod util;
@@ -0,0 +1,455 @@
+use std::{
+    fmt::{Display, Write},
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use aoc2022::solution::{Solution, Unsolved};
+use aoc2022::util::{parallel, InputSource, MissingInput};
+use aoc2022::{
+    day01, day02, day03, day04, day05, day06, day07, day08, day09, day10, day11, day12, day13,
+    day14, day15, day16, day17, day18, day19, day20, day21, day22, day23, day24, day25,
+};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl TryFrom<u8> for Part {
+    type Error = color_eyre::Report;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(Self::One),
+            2 => Ok(Self::Two),
+            _ => Err(color_eyre::eyre::eyre!(
+                "There is no part {value}, only 1 and 2"
+            )),
+        }
+    }
+}
+
+impl Display for Part {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::One => write!(f, "1"),
+            Self::Two => write!(f, "2"),
+        }
+    }
+}
+
+pub struct Answer {
+    pub part: Part,
+    /// `None` if the part is not solved (or too slow to be run every time)
+    pub value: Option<String>,
+    pub elapsed: Duration,
+}
+
+/// The answers of a day together with the time it took to parse the input
+pub struct Report {
+    pub parse: Duration,
+    pub answers: Vec<Answer>,
+}
+
+impl Report {
+    #[must_use]
+    pub fn elapsed(&self, part: Part) -> Option<Duration> {
+        self.answers
+            .iter()
+            .find(|answer| answer.part == part)
+            .map(|answer| answer.elapsed)
+    }
+
+    #[must_use]
+    pub fn total(&self) -> Duration {
+        self.parse
+            + self
+                .answers
+                .iter()
+                .map(|answer| answer.elapsed)
+                .sum::<Duration>()
+    }
+}
+
+type Runner = fn(&str, &[Part]) -> color_eyre::Result<Report>;
+
+pub struct Day {
+    pub number: u8,
+    run: Runner,
+}
+
+impl Day {
+    #[must_use]
+    pub const fn of<S: Solution>() -> Self {
+        Self {
+            number: S::DAY,
+            run: run_solution::<S>,
+        }
+    }
+
+    /// Parses the input once and solves the given parts, timing each step
+    /// # Errors
+    /// If parsing or one of the parts fails
+    pub fn run(&self, input: &str, parts: &[Part]) -> color_eyre::Result<Report> {
+        (self.run)(input, parts)
+    }
+}
+
+fn run_solution<S: Solution>(input: &str, parts: &[Part]) -> color_eyre::Result<Report> {
+    let start = Instant::now();
+    let parsed = S::parse(input)?;
+    let parse = start.elapsed();
+    let answers = parts
+        .iter()
+        .map(|&part| {
+            let start = Instant::now();
+            let value = match part {
+                Part::One => S::part1(&parsed).map(|answer| answer.to_string()),
+                Part::Two => S::part2(&parsed).map(|answer| answer.to_string()),
+            };
+            let elapsed = start.elapsed();
+            match value {
+                Ok(value) => Ok(Answer {
+                    part,
+                    value: Some(value),
+                    elapsed,
+                }),
+                Err(err) if err.is::<Unsolved>() => Ok(Answer {
+                    part,
+                    value: None,
+                    elapsed,
+                }),
+                Err(err) => Err(err),
+            }
+        })
+        .collect::<color_eyre::Result<_>>()?;
+    Ok(Report { parse, answers })
+}
+
+pub const DAYS: [Day; 25] = [
+    Day::of::<day01::Day01>(),
+    Day::of::<day02::Day02>(),
+    Day::of::<day03::Day03>(),
+    Day::of::<day04::Day04>(),
+    Day::of::<day05::Day05>(),
+    Day::of::<day06::Day06>(),
+    Day::of::<day07::Day07>(),
+    Day::of::<day08::Day08>(),
+    Day::of::<day09::Day09>(),
+    Day::of::<day10::Day10>(),
+    Day::of::<day11::Day11>(),
+    Day::of::<day12::Day12>(),
+    Day::of::<day13::Day13>(),
+    Day::of::<day14::Day14>(),
+    Day::of::<day15::Day15>(),
+    Day::of::<day16::Day16>(),
+    Day::of::<day17::Day17>(),
+    Day::of::<day18::Day18>(),
+    Day::of::<day19::Day19>(),
+    Day::of::<day20::Day20>(),
+    Day::of::<day21::Day21>(),
+    Day::of::<day22::Day22>(),
+    Day::of::<day23::Day23>(),
+    Day::of::<day24::Day24>(),
+    Day::of::<day25::Day25>(),
+];
+
+#[must_use]
+pub fn get_day(number: u8) -> Option<&'static Day> {
+    DAYS.iter().find(|day| day.number == number)
+}
+
+/// How the answers are printed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    /// A line per part, for humans
+    #[default]
+    Text,
+    /// A JSON array of [`Entry`], for scripts
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = color_eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(color_eyre::eyre::eyre!(
+                "Unknown format '{s}', expected 'text' or 'json'"
+            )),
+        }
+    }
+}
+
+/// The outcome of a part in the machine readable output.
+/// Both `answer` and `error` are `None` if the part is not solved.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Entry {
+    pub day: u8,
+    pub part: u8,
+    pub answer: Option<String>,
+    pub elapsed_ns: u64,
+    pub error: Option<String>,
+}
+
+impl Entry {
+    /// An entry per part. If the day failed, every part gets its error.
+    #[must_use]
+    pub fn of(day: u8, parts: &[Part], result: &color_eyre::Result<Report>) -> Vec<Self> {
+        let part_number = |part: Part| match part {
+            Part::One => 1,
+            Part::Two => 2,
+        };
+        match result {
+            Ok(report) => report
+                .answers
+                .iter()
+                .map(|answer| Self {
+                    day,
+                    part: part_number(answer.part),
+                    answer: answer.value.clone(),
+                    elapsed_ns: u64::try_from(answer.elapsed.as_nanos()).unwrap_or(u64::MAX),
+                    error: None,
+                })
+                .collect(),
+            Err(err) => parts
+                .iter()
+                .map(|&part| Self {
+                    day,
+                    part: part_number(part),
+                    answer: None,
+                    elapsed_ns: 0,
+                    error: Some(format!("{err:#}")),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Reads the input of a day and solves the selected parts
+/// # Errors
+/// If the day is not solved, its input cannot be read or solving fails
+pub fn solve_day(number: u8, parts: &[Part], input: &InputSource) -> color_eyre::Result<Report> {
+    let day =
+        get_day(number).ok_or_else(|| color_eyre::eyre::eyre!("Day {number} is not solved"))?;
+    day.run(&input.read(number)?, parts)
+}
+
+/// Solves the days one after the other as the iterator is advanced or, if [`parallel::jobs`] allows
+/// more than one thread, all at once up front. Either way, they are returned in the given order.
+pub fn solve_days<'a>(
+    days: &'a [u8],
+    parts: &'a [Part],
+    input: &'a InputSource,
+) -> Box<dyn Iterator<Item = (u8, color_eyre::Result<Report>)> + 'a> {
+    let solve = |&number: &u8| (number, solve_day(number, parts, input));
+    if parallel::jobs() > 1 {
+        Box::new(parallel::map(days, solve).into_iter())
+    } else {
+        Box::new(days.iter().map(solve))
+    }
+}
+
+/// Runs the selected parts of the selected days and prints the answers,
+/// followed by a table of how long each step took if `time` is set.
+/// Days without an input file are skipped.
+/// As JSON, failing days are reported in the output instead of aborting.
+/// # Errors
+/// If a day is not registered, its input cannot be read or a solver fails
+pub fn run(
+    days: &[u8],
+    parts: &[Part],
+    input: &InputSource,
+    time: bool,
+    format: Format,
+) -> color_eyre::Result<()> {
+    if format == Format::Json {
+        return run_json(days, parts, input);
+    }
+    let mut reports = Vec::with_capacity(days.len());
+    for (number, report) in solve_days(days, parts, input) {
+        let report = match report {
+            Err(err) if err.is::<MissingInput>() => {
+                println!("Day {number:02} is skipped because its input is missing ({err})");
+                continue;
+            }
+            report => report?,
+        };
+        for Answer { part, value, .. } in &report.answers {
+            match value {
+                Some(answer) if answer.contains('\n') => {
+                    println!("Day {number:02} part {part}:\n{answer}");
+                }
+                Some(answer) => println!("Day {number:02} part {part}: {answer}"),
+                None => println!(
+                    "Day {number:02} part {part} is skipped because it is not solved or too slow"
+                ),
+            }
+        }
+        reports.push((number, report));
+    }
+    if time {
+        println!();
+        print!("{}", timing_table(&reports));
+    }
+    Ok(())
+}
+
+fn run_json(days: &[u8], parts: &[Part], input: &InputSource) -> color_eyre::Result<()> {
+    let mut entries = Vec::with_capacity(days.len() * parts.len());
+    for (number, result) in solve_days(days, parts, input) {
+        entries.extend(Entry::of(number, parts, &result));
+    }
+    println!("{}", serde_json::to_string_pretty(&entries)?);
+    let failed = entries.iter().filter(|entry| entry.error.is_some()).count();
+    if failed > 0 {
+        return Err(color_eyre::eyre::eyre!(
+            "{failed} of {} parts failed",
+            entries.len()
+        ));
+    }
+    Ok(())
+}
+
+/// Formats the time of each step as a table, slowest days are easy to spot in the last column
+#[must_use]
+pub fn timing_table(reports: &[(u8, Report)]) -> String {
+    let cell = |elapsed: Option<Duration>| elapsed.map_or_else(|| "-".to_owned(), format_duration);
+    let mut table = format!(
+        "{:>3} {:>10} {:>10} {:>10} {:>10}\n",
+        "Day", "Parse", "Part 1", "Part 2", "Total"
+    );
+    for (number, report) in reports {
+        let _ = writeln!(
+            table,
+            "{number:>3} {:>10} {:>10} {:>10} {:>10}",
+            format_duration(report.parse),
+            cell(report.elapsed(Part::One)),
+            cell(report.elapsed(Part::Two)),
+            format_duration(report.total()),
+        );
+    }
+    let total: Duration = reports.iter().map(|(_, report)| report.total()).sum();
+    let _ = writeln!(table, "{:>3} {:>43}", "All", format_duration(total));
+    table
+}
+
+/// Formats a duration with three significant digits in the most fitting unit
+#[must_use]
+pub fn format_duration(duration: Duration) -> String {
+    let nanos = duration.as_secs_f64() * 1e9;
+    let (value, unit) = if nanos < 1e3 {
+        (nanos, "ns")
+    } else if nanos < 1e6 {
+        (nanos / 1e3, "µs")
+    } else if nanos < 1e9 {
+        (nanos / 1e6, "ms")
+    } else {
+        (nanos / 1e9, "s")
+    };
+    let precision = usize::from(value < 10.0) + usize::from(value < 100.0);
+    format!("{value:.precision$} {unit}")
+}
+
+#[cfg(test)]
+pub mod tests {
+    use aoc2022::util::read_example;
+
+    use super::*;
+
+    #[test]
+    fn it_registers_every_day_once_in_order() {
+        let numbers: Vec<u8> = DAYS.iter().map(|day| day.number).collect();
+        assert_eq!((1..=25).collect::<Vec<u8>>(), numbers);
+    }
+
+    #[test]
+    fn it_looks_up_days() {
+        assert_eq!(16, get_day(16).unwrap().number);
+        assert!(get_day(26).is_none());
+    }
+
+    #[test]
+    fn it_runs_the_selected_parts() {
+        let input = read_example("day01.txt");
+        let answers = get_day(1)
+            .unwrap()
+            .run(&input, &[Part::Two])
+            .unwrap()
+            .answers;
+        assert_eq!(1, answers.len());
+        assert_eq!(Part::Two, answers[0].part);
+        assert_eq!(Some("45000".to_owned()), answers[0].value);
+    }
+
+    #[test]
+    fn it_skips_unsolved_parts() {
+        let input = read_example("day25.txt");
+        let answers = get_day(25)
+            .unwrap()
+            .run(&input, &[Part::One, Part::Two])
+            .unwrap()
+            .answers;
+        assert_eq!(Some("2=-1=0".to_owned()), answers[0].value);
+        assert_eq!(None, answers[1].value);
+    }
+
+    #[test]
+    fn it_lists_entries_for_json() {
+        let input = read_example("day25.txt");
+        let parts = [Part::One, Part::Two];
+        let entries = Entry::of(25, &parts, &get_day(25).unwrap().run(&input, &parts));
+        assert_eq!(Some("2=-1=0".to_owned()), entries[0].answer);
+        assert_eq!(
+            (2, None, None),
+            (
+                entries[1].part,
+                entries[1].answer.clone(),
+                entries[1].error.clone()
+            )
+        );
+
+        let entries = Entry::of(
+            3,
+            &parts,
+            &Err(color_eyre::eyre::eyre!("Cannot read input")),
+        );
+        assert_eq!(2, entries.len());
+        assert_eq!(Some("Cannot read input"), entries[1].error.as_deref());
+        let json = serde_json::to_string(&entries[0]).unwrap();
+        assert_eq!(
+            r#"{"day":3,"part":1,"answer":null,"elapsed_ns":0,"error":"Cannot read input"}"#,
+            json
+        );
+    }
+
+    #[test]
+    fn it_formats_durations() {
+        assert_eq!("999 ns", format_duration(Duration::from_nanos(999)));
+        assert_eq!("1.50 µs", format_duration(Duration::from_nanos(1500)));
+        assert_eq!("12.3 ms", format_duration(Duration::from_micros(12_345)));
+        assert_eq!("2.00 s", format_duration(Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn it_prints_a_timing_table() {
+        let report = Report {
+            parse: Duration::from_micros(50),
+            answers: vec![Answer {
+                part: Part::Two,
+                value: Some("45000".to_owned()),
+                elapsed: Duration::from_millis(2),
+            }],
+        };
+        let table = timing_table(&[(1, report)]);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!("Day      Parse     Part 1     Part 2      Total", lines[0]);
+        assert_eq!("  1    50.0 µs          -    2.00 ms    2.05 ms", lines[1]);
+        assert_eq!("All                                     2.05 ms", lines[2]);
+    }
+}
@@ -0,0 +1,246 @@
+use std::{
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::eyre::{eyre, WrapErr};
+use regex::Regex;
+
+/// rustfmt's line width, which the regenerated `use` of the runner sticks to
+const MAX_WIDTH: usize = 100;
+
+/// A solver that compiles, for `dayNN.rs`
+fn template(day: u8) -> String {
+    format!(
+        "\
+use crate::solution::Solution;
+
+pub struct Day{day:02};
+
+impl Solution for Day{day:02} {{
+    const DAY: u8 = {day};
+    type Input = Vec<String>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> color_eyre::Result<Self::Input> {{
+        Ok(input.lines().map(str::to_owned).collect())
+    }}
+
+    fn part1(input: &Self::Input) -> color_eyre::Result<usize> {{
+        Ok(input.len())
+    }}
+}}
+
+#[cfg(test)]
+pub mod tests {{
+    use super::*;
+    use crate::util::read_example;
+
+    #[test]
+    fn it_solves_part1() {{
+        let input = Day{day:02}::parse(&read_example(\"day{day:02}.txt\")).unwrap();
+        assert_eq!(0, Day{day:02}::part1(&input).unwrap());
+    }}
+}}
+"
+    )
+}
+
+/// Adds `pub mod dayNN;` to the crate root, after the days before it
+fn register_module(lib: &str, day: u8) -> color_eyre::Result<String> {
+    let module = format!("pub mod day{day:02};");
+    let mut lines: Vec<&str> = lib.lines().collect();
+    if lines.contains(&module.as_str()) {
+        return Err(eyre!("Day {day} is already a module of the crate"));
+    }
+    let is_day = |line: &&str| line.starts_with("pub mod day");
+    let at = lines
+        .iter()
+        .rposition(|line| is_day(line) && **line < *module)
+        .map(|i| i + 1)
+        .or_else(|| lines.iter().position(|line| line.starts_with("pub mod ")))
+        .ok_or_else(|| eyre!("Cannot find the modules of the crate"))?;
+    lines.insert(at, &module);
+    Ok(lines.join("\n") + "\n")
+}
+
+/// Adds the day to the imports and to `DAYS` of the runner
+fn register_day(runner: &str, day: u8) -> color_eyre::Result<String> {
+    let name = format!("day{day:02}");
+    let imports = Regex::new(r"use aoc2022::\{([^}]*)\};").unwrap();
+    let captures = imports
+        .captures(runner)
+        .ok_or_else(|| eyre!("Cannot find the import of the days in the runner"))?;
+    let mut modules: Vec<&str> = captures[1]
+        .split(',')
+        .map(str::trim)
+        .filter(|m| !m.is_empty())
+        .collect();
+    if modules.contains(&name.as_str()) {
+        return Err(eyre!("Day {day} is already registered with the runner"));
+    }
+    modules.push(&name);
+    modules.sort_unstable();
+    let runner = runner.replacen(&captures[0], &format_imports(&modules), 1);
+
+    let days = Regex::new(r"pub const DAYS: \[Day; \d+\] = \[\n((?:.*\n)*?)\];").unwrap();
+    let captures = days
+        .captures(&runner)
+        .ok_or_else(|| eyre!("Cannot find DAYS in the runner"))?;
+    let mut entries: Vec<String> = captures[1].lines().map(str::to_owned).collect();
+    entries.push(format!("    Day::of::<{name}::Day{day:02}>(),"));
+    entries.sort_unstable();
+    let table = format!(
+        "pub const DAYS: [Day; {}] = [\n{}\n];",
+        entries.len(),
+        entries.join("\n")
+    );
+    Ok(runner.replacen(&captures[0], &table, 1))
+}
+
+/// Formats `use aoc2022::{...};` like rustfmt: on one line if it fits, else filling indented lines
+fn format_imports(modules: &[&str]) -> String {
+    let single = format!("use aoc2022::{{{}}};", modules.join(", "));
+    if single.len() <= MAX_WIDTH {
+        return single;
+    }
+    let mut imports = String::from("use aoc2022::{\n");
+    let mut line = String::from("   ");
+    for module in modules {
+        if line.len() + module.len() + 2 > MAX_WIDTH {
+            writeln!(imports, "{line}").unwrap();
+            line = String::from("   ");
+        }
+        write!(line, " {module},").unwrap();
+    }
+    writeln!(imports, "{line}").unwrap();
+    imports + "};"
+}
+
+fn create(path: &Path, contents: &str) -> color_eyre::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .wrap_err_with(|| format!("Cannot create '{}'", dir.display()))?;
+    }
+    std::fs::write(path, contents).wrap_err_with(|| format!("Cannot write '{}'", path.display()))
+}
+
+/// Generates `src/dayNN.rs`, registers it and adds empty example and input files
+/// (keeping existing ones), in the repository at `root` and the input directory `inputs`.
+/// Returns the files that were written.
+/// # Errors
+/// If the day already exists or a file cannot be read or written
+pub fn new_day(root: &Path, inputs: &Path, day: u8) -> color_eyre::Result<Vec<PathBuf>> {
+    let module = root.join(format!("src/day{day:02}.rs"));
+    if module.exists() {
+        return Err(eyre!("'{}' already exists", module.display()));
+    }
+    let lib = root.join("src/lib.rs");
+    let runner = root.join("src/runner.rs");
+    let read = |path: &Path| {
+        std::fs::read_to_string(path).wrap_err_with(|| format!("Cannot read '{}'", path.display()))
+    };
+    // check everything before writing anything, so a failure leaves the tree as it was
+    let registered_lib = register_module(&read(&lib)?, day)?;
+    let registered_runner = register_day(&read(&runner)?, day)?;
+
+    let mut written = Vec::new();
+    for (path, contents) in [
+        (module, template(day)),
+        (lib, registered_lib),
+        (runner, registered_runner),
+    ] {
+        create(&path, &contents)?;
+        written.push(path);
+    }
+    for path in [
+        root.join(format!("examples/day{day:02}.txt")),
+        inputs.join(format!("day{day:02}.txt")),
+    ] {
+        if !path.exists() {
+            create(&path, "")?;
+            written.push(path);
+        }
+    }
+    Ok(written)
+}
+
+/// Scaffolds the day in the current directory and lists what was written
+/// # Errors
+/// See [`new_day`]
+pub fn run(day: u8, inputs: &Path) -> color_eyre::Result<()> {
+    for path in new_day(Path::new("."), inputs, day)? {
+        println!("wrote {}", path.display());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    const RUNNER: &str = "\
+use aoc2022::{day01, day03};
+
+pub const DAYS: [Day; 2] = [
+    Day::of::<day01::Day01>(),
+    Day::of::<day03::Day03>(),
+];
+";
+
+    #[test]
+    fn it_registers_modules_in_order() {
+        let lib = "//! Docs\n\npub mod day01;\npub mod day03;\npub mod util;\n";
+        assert_eq!(
+            "//! Docs\n\npub mod day01;\npub mod day02;\npub mod day03;\npub mod util;\n",
+            register_module(lib, 2).unwrap()
+        );
+        assert_eq!(
+            "pub mod day01;\npub mod util;\n",
+            register_module("pub mod util;\n", 1).unwrap()
+        );
+        assert!(register_module(lib, 3).is_err());
+    }
+
+    #[test]
+    fn it_registers_days_with_the_runner() {
+        let runner = register_day(RUNNER, 2).unwrap();
+        assert!(
+            runner.starts_with("use aoc2022::{day01, day02, day03};\n"),
+            "{runner}"
+        );
+        assert!(runner.contains(
+            "[Day; 3] = [\n    Day::of::<day01::Day01>(),\n    Day::of::<day02::Day02>(),\n"
+        ));
+        assert!(register_day(RUNNER, 3).is_err());
+        assert!(register_day("", 3).is_err());
+    }
+
+    #[test]
+    fn it_wraps_long_imports() {
+        let names: Vec<String> = (1..=25).map(|day| format!("day{day:02}")).collect();
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        let imports = format_imports(&names);
+        assert!(imports.lines().all(|line| line.len() <= MAX_WIDTH));
+        assert_eq!(4, imports.lines().count());
+        assert!(imports.contains("    day01, day02,") && imports.ends_with("day25,\n};"));
+    }
+
+    #[test]
+    fn it_scaffolds_a_day() {
+        let root = std::env::temp_dir().join(format!("aoc2022-scaffold-{}", std::process::id()));
+        create(&root.join("src/lib.rs"), "pub mod day01;\npub mod day03;\n").unwrap();
+        create(&root.join("src/runner.rs"), RUNNER).unwrap();
+        let written = new_day(&root, &root.join("inputs"), 2).unwrap();
+        assert_eq!(5, written.len());
+        let module = std::fs::read_to_string(root.join("src/day02.rs")).unwrap();
+        assert!(module.contains("pub struct Day02;") && module.contains("const DAY: u8 = 2;"));
+        assert_eq!(
+            "",
+            std::fs::read_to_string(root.join("inputs/day02.txt")).unwrap()
+        );
+        assert!(new_day(&root, &root.join("inputs"), 2).is_err());
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
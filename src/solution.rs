@@ -0,0 +1,52 @@
+use std::fmt::Display;
+
+/// The common interface of every day's puzzle.
+///
+/// The input is parsed once and then shared by both parts.
+pub trait Solution {
+    const DAY: u8;
+    type Input;
+    type Answer1: Display;
+    type Answer2: Display;
+
+    /// # Errors
+    /// If the input is malformed
+    fn parse(input: &str) -> color_eyre::Result<Self::Input>;
+
+    /// # Errors
+    /// If there is no answer for the given input
+    fn part1(input: &Self::Input) -> color_eyre::Result<Self::Answer1>;
+
+    /// Defaults to an [`Unsolved`] error for days whose second part is not done yet.
+    /// # Errors
+    /// If there is no answer for the given input
+    fn part2(_input: &Self::Input) -> color_eyre::Result<Self::Answer2> {
+        Err(Unsolved.into())
+    }
+
+    /// Parses the input and solves part 1
+    /// # Errors
+    /// If parsing or solving fails
+    fn solve_part1(input: &str) -> color_eyre::Result<Self::Answer1> {
+        Self::part1(&Self::parse(input)?)
+    }
+
+    /// Parses the input and solves part 2
+    /// # Errors
+    /// If parsing or solving fails
+    fn solve_part2(input: &str) -> color_eyre::Result<Self::Answer2> {
+        Self::part2(&Self::parse(input)?)
+    }
+}
+
+/// Returned by [`Solution::part2`] if a day's second part is not solved (or too slow to be run every time)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Unsolved;
+
+impl Display for Unsolved {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "not solved")
+    }
+}
+
+impl std::error::Error for Unsolved {}
@@ -1,21 +1,110 @@
-use std::str::FromStr;
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
-/// # Panics
-/// This may panic if there is a problem with the file
-#[must_use] pub fn read_input(filename: &str) -> String {
-    std::fs::read_to_string(std::path::Path::new("resources/").join(filename)).unwrap()
+use color_eyre::{eyre::WrapErr, Section};
+
+use crate::input::InputProvider;
+
+mod compass;
+pub mod cycle;
+mod grid;
+pub mod ocr;
+pub mod parallel;
+pub mod parse;
+pub mod search;
+mod vec;
+
+pub use compass::Compass;
+pub use grid::Grid;
+pub use parse::ParseError;
+pub use vec::{Number, Vec2, Vec3};
+
+/// Environment variable pointing to the directory containing the puzzle inputs
+pub const INPUT_DIR_VAR: &str = "AOC2022_INPUT_DIR";
+const DEFAULT_INPUT_DIR: &str = "resources/";
+
+/// Where the puzzle input of a day is read from
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum InputSource {
+    /// `dayNN.txt` in [`input_dir`], downloaded there if it is missing and a session is configured
+    #[default]
+    Default,
+    /// `dayNN.txt` in the given directory, downloaded there like [`InputSource::Default`]
+    Directory(PathBuf),
+    /// The given file, regardless of the day
+    File(PathBuf),
+    Stdin,
+}
+
+impl InputSource {
+    /// # Errors
+    /// If the input cannot be read
+    pub fn read(&self, day: u8) -> color_eyre::Result<String> {
+        match self {
+            Self::Default => InputProvider::from_env(input_dir()).get(day),
+            Self::Directory(dir) => InputProvider::from_env(dir).get(day),
+            Self::File(path) => read_input_file(path),
+            Self::Stdin => {
+                let mut input = String::new();
+                std::io::stdin()
+                    .read_to_string(&mut input)
+                    .wrap_err("Cannot read puzzle input from stdin")?;
+                Ok(input)
+            }
+        }
+    }
+}
+
+/// The directory set in [`INPUT_DIR_VAR`], or `resources/` if it is not set
+#[must_use]
+pub fn input_dir() -> PathBuf {
+    std::env::var_os(INPUT_DIR_VAR).map_or_else(|| PathBuf::from(DEFAULT_INPUT_DIR), PathBuf::from)
+}
+
+/// # Errors
+/// If the file does not exist in [`input_dir`] or cannot be read
+pub fn read_input(filename: &str) -> color_eyre::Result<String> {
+    read_input_file(&input_dir().join(filename))
+}
+
+/// The error of reading a puzzle input file that does not exist,
+/// which runners report for the day instead of giving up on the others
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingInput(pub PathBuf);
+
+impl std::fmt::Display for MissingInput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Puzzle input '{}' does not exist", self.0.display())
+    }
+}
+
+impl std::error::Error for MissingInput {}
+
+pub(crate) fn read_input_file(path: &Path) -> color_eyre::Result<String> {
+    match std::fs::read_to_string(path) {
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Err(MissingInput(path.to_owned()).into()),
+        result => result.wrap_err_with(|| format!("Cannot read puzzle input '{}'", path.display())),
+    }
+    .suggestion(format!(
+            "Save your puzzle input there, point {INPUT_DIR_VAR} or --input-dir to the directory containing it, \
+            or pass the file with --input <FILE> ('-' reads from stdin)"
+        ))
 }
 
 /// # Panics
 /// This may panic if there is a problem with the file
-#[must_use] pub fn read_example(filename: &str) -> String {
+#[must_use]
+pub fn read_example(filename: &str) -> String {
     std::fs::read_to_string(std::path::Path::new("examples/").join(filename)).unwrap()
 }
 
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum Direction {
     Up,
+    #[default]
     Right,
     Down,
     Left,
@@ -30,17 +119,11 @@ impl FromStr for Direction {
             "R" | ">" => Ok(Self::Right),
             "D" | "V" => Ok(Self::Down),
             "L" | "<" => Ok(Self::Left),
-            _ => Err(color_eyre::eyre::eyre!("Cannot parse direction '{s}'"))
+            _ => Err(color_eyre::eyre::eyre!("Cannot parse direction '{s}'")),
         }
     }
 }
 
-impl Default for Direction {
-    fn default() -> Self {
-        Self::Right
-    }
-}
-
 impl Direction {
     #[must_use]
     pub const fn turn(&self, turn_left: bool) -> Self {
@@ -59,7 +142,6 @@ impl Direction {
                 Self::Left => Self::Up,
             }
         }
-
     }
 }
 
@@ -145,6 +227,26 @@ impl Iterator for Spiral {
 #[cfg(test)]
 pub mod tests {
     use super::*;
+
+    #[test]
+    fn it_reads_input_from_directory() {
+        let source = InputSource::Directory(PathBuf::from("examples/"));
+        assert_eq!(read_example("day01.txt"), source.read(1).unwrap());
+        let source = InputSource::File(PathBuf::from("examples/day09_2.txt"));
+        assert_eq!(read_example("day09_2.txt"), source.read(9).unwrap());
+    }
+
+    #[test]
+    fn it_names_missing_input_file() {
+        let source = InputSource::File(PathBuf::from("does/not/exist/day03.txt"));
+        let err = source.read(3).unwrap_err();
+        assert!(err.to_string().contains("day03.txt"), "{err}");
+        assert!(err.is::<MissingInput>());
+        assert!(!InputSource::File(PathBuf::from("examples/"))
+            .read(3)
+            .unwrap_err()
+            .is::<MissingInput>());
+    }
     #[test]
     fn it_spirals() {
         let spiral = Spiral::new(5, 5);
@@ -168,4 +270,4 @@ pub mod tests {
             indices
         );
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,178 @@
+use std::{fmt::Display, ops::Neg};
+
+use super::{Direction, Number, Vec2};
+
+/// The eight directions of a compass rose, with north pointing up (towards smaller `y`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Compass {
+    N,
+    NE,
+    E,
+    SE,
+    S,
+    SW,
+    W,
+    NW,
+}
+
+impl Compass {
+    /// All directions, clockwise starting with north
+    pub const ALL: [Self; 8] = [
+        Self::N,
+        Self::NE,
+        Self::E,
+        Self::SE,
+        Self::S,
+        Self::SW,
+        Self::W,
+        Self::NW,
+    ];
+
+    pub fn iter() -> impl Iterator<Item = Self> {
+        Self::ALL.into_iter()
+    }
+
+    /// Turns clockwise by `eighths` * 45°, or counterclockwise if it is negative
+    #[must_use]
+    pub const fn rotate(self, eighths: i8) -> Self {
+        Self::ALL[(self as i8 + eighths).rem_euclid(8) as usize]
+    }
+
+    /// Turns by 90° clockwise
+    #[must_use]
+    pub const fn turn_right(self) -> Self {
+        self.rotate(2)
+    }
+
+    /// Turns by 90° counterclockwise
+    #[must_use]
+    pub const fn turn_left(self) -> Self {
+        self.rotate(-2)
+    }
+
+    /// Turns by 45° clockwise
+    #[must_use]
+    pub const fn turn_half_right(self) -> Self {
+        self.rotate(1)
+    }
+
+    /// Turns by 45° counterclockwise
+    #[must_use]
+    pub const fn turn_half_left(self) -> Self {
+        self.rotate(-1)
+    }
+
+    #[must_use]
+    pub const fn opposite(self) -> Self {
+        self.rotate(4)
+    }
+
+    #[must_use]
+    pub const fn is_diagonal(self) -> bool {
+        matches!(self, Self::NE | Self::SE | Self::SW | Self::NW)
+    }
+
+    /// The step `(dx, dy)` in this direction
+    #[must_use]
+    pub const fn offset(self) -> (isize, isize) {
+        match self {
+            Self::N => (0, -1),
+            Self::NE => (1, -1),
+            Self::E => (1, 0),
+            Self::SE => (1, 1),
+            Self::S => (0, 1),
+            Self::SW => (-1, 1),
+            Self::W => (-1, 0),
+            Self::NW => (-1, -1),
+        }
+    }
+
+    /// The direction of the step that gets closest to `(dx, dy)`, like a king in chess,
+    /// or `None` if there is nowhere to go
+    #[must_use]
+    pub fn towards((dx, dy): (isize, isize)) -> Option<Self> {
+        Self::iter().find(|direction| direction.offset() == (dx.signum(), dy.signum()))
+    }
+}
+
+impl From<Direction> for Compass {
+    fn from(direction: Direction) -> Self {
+        match direction {
+            Direction::Up => Self::N,
+            Direction::Right => Self::E,
+            Direction::Down => Self::S,
+            Direction::Left => Self::W,
+        }
+    }
+}
+
+impl<T: Number + Neg<Output = T>> From<Compass> for Vec2<T> {
+    /// The unit step in a direction
+    fn from(direction: Compass) -> Self {
+        let unit = |d: isize| match d {
+            -1 => -T::ONE,
+            0 => T::ZERO,
+            _ => T::ONE,
+        };
+        let (dx, dy) = direction.offset();
+        Self::new(unit(dx), unit(dy))
+    }
+}
+
+impl Display for Compass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn it_turns() {
+        assert_eq!(Compass::E, Compass::N.turn_right());
+        assert_eq!(Compass::W, Compass::N.turn_left());
+        assert_eq!(Compass::NW, Compass::N.turn_half_left());
+        assert_eq!(Compass::N, Compass::NW.turn_half_right());
+        assert_eq!(Compass::SW, Compass::NE.opposite());
+        assert_eq!(Compass::SE, Compass::W.rotate(-11));
+        for direction in Compass::iter() {
+            assert_eq!(direction, direction.turn_right().turn_left());
+            assert_eq!(direction.opposite(), direction.turn_right().turn_right());
+        }
+    }
+
+    #[test]
+    fn it_converts_to_offsets() {
+        assert_eq!(
+            4,
+            Compass::iter()
+                .filter(|direction| direction.is_diagonal())
+                .count()
+        );
+        for direction in Compass::iter() {
+            let (dx, dy) = direction.offset();
+            let (ox, oy) = direction.opposite().offset();
+            assert_eq!((0, 0), (dx + ox, dy + oy));
+            assert_eq!(Some(direction), Compass::towards((dx * 5, dy * 3)));
+        }
+        assert_eq!(None, Compass::towards((0, 0)));
+        assert_eq!(Vec2::new(-1, 1), Vec2::<i32>::from(Compass::SW));
+    }
+
+    #[test]
+    fn it_agrees_with_direction() {
+        for direction in [
+            Direction::Up,
+            Direction::Right,
+            Direction::Down,
+            Direction::Left,
+        ] {
+            let compass = Compass::from(direction);
+            assert!(!compass.is_diagonal());
+            assert_eq!(Compass::from(direction.turn(false)), compass.turn_right());
+            assert_eq!(Compass::from(direction.turn(true)), compass.turn_left());
+        }
+    }
+}
@@ -0,0 +1,215 @@
+//! Cycle detection, to extrapolate simulations that repeat themselves to huge step counts
+
+use std::{collections::HashMap, hash::Hash};
+
+use super::Number;
+
+/// From step `start` on, every state repeats after `length` steps
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: usize,
+    pub length: usize,
+}
+
+impl Cycle {
+    /// The first step with the same state as `step`
+    #[must_use]
+    pub const fn equivalent_step(self, step: usize) -> usize {
+        if step < self.start {
+            step
+        } else {
+            self.start + (step - self.start) % self.length
+        }
+    }
+
+    /// Extrapolates a metric that grows by the same amount in every cycle (like the height of a tower)
+    /// to the `target` step. `metrics[i]` is the value after step `i` and must cover at least one cycle.
+    /// # Panics
+    /// If there are not enough metrics, or the number of cycles does not fit into `T`
+    #[must_use]
+    pub fn extrapolate<T: Number + TryFrom<usize>>(self, metrics: &[T], target: usize) -> T {
+        if let Some(&metric) = metrics.get(target) {
+            return metric;
+        }
+        let end = self.start + self.length;
+        assert!(
+            metrics.len() > end,
+            "Cannot extrapolate from {} metrics",
+            metrics.len()
+        );
+        let growth = metrics[end] - metrics[self.start];
+        let cycles = T::try_from((target - self.start) / self.length)
+            .unwrap_or_else(|_| panic!("Too many cycles to extrapolate to step {target}"));
+        metrics[self.equivalent_step(target)] + growth * cycles
+    }
+}
+
+/// Floyd's tortoise and hare: finds the cycle of `initial`, `step(initial)`, … in constant memory.
+/// Never returns if the states do not repeat.
+pub fn floyd<S: Clone + PartialEq>(initial: S, step: impl Fn(&S) -> S) -> Cycle {
+    let mut tortoise = step(&initial);
+    let mut hare = step(&tortoise);
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&step(&hare));
+    }
+    // the distance between them is now a multiple of the length, so they meet at the start
+    let mut start = 0;
+    tortoise = initial;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+    let mut length = 1;
+    hare = step(&tortoise);
+    while tortoise != hare {
+        hare = step(&hare);
+        length += 1;
+    }
+    Cycle { start, length }
+}
+
+/// Brent's algorithm: like [`floyd`], but with fewer calls to `step`
+pub fn brent<S: Clone + PartialEq>(initial: S, step: impl Fn(&S) -> S) -> Cycle {
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(&initial);
+    while tortoise != hare {
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+        hare = step(&hare);
+        length += 1;
+    }
+    // start the hare `length` steps ahead, so they meet at the start of the cycle
+    tortoise = initial.clone();
+    hare = initial;
+    for _ in 0..length {
+        hare = step(&hare);
+    }
+    let mut start = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+    Cycle { start, length }
+}
+
+/// Remembers the first step every state was seen at, for simulations that are too expensive
+/// to run twice or whose state is only a summary (that can be hashed) of the full state
+#[derive(Debug, Clone)]
+pub struct Detector<K> {
+    seen: HashMap<K, usize>,
+}
+
+impl<K: Eq + Hash> Detector<K> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            seen: HashMap::new(),
+        }
+    }
+
+    /// Records the state after `step`, and returns the cycle if it was seen before
+    pub fn observe(&mut self, step: usize, state: K) -> Option<Cycle> {
+        match self.seen.try_insert(state, step) {
+            Ok(_) => None,
+            Err(err) => {
+                let start = *err.entry.get();
+                Some(Cycle {
+                    start,
+                    length: step - start,
+                })
+            }
+        }
+    }
+}
+
+impl<K: Eq + Hash> Default for Detector<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    /// Starting at 3: 3, 10, 2, 5, 4, 6, 4, 6, …
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    fn step(x: &u32) -> u32 {
+        (x * x + 1) % 11
+    }
+
+    #[test]
+    fn it_finds_cycles() {
+        let expected = Cycle {
+            start: 4,
+            length: 2,
+        };
+        assert_eq!(expected, floyd(3, step));
+        assert_eq!(expected, brent(3, step));
+        assert_eq!(
+            Cycle {
+                start: 0,
+                length: 5
+            },
+            floyd(0, |x| (x + 1) % 5)
+        );
+        assert_eq!(
+            Cycle {
+                start: 0,
+                length: 5
+            },
+            brent(0, |x| (x + 1) % 5)
+        );
+        assert_eq!(
+            Cycle {
+                start: 0,
+                length: 1
+            },
+            brent(7, |x| *x)
+        );
+    }
+
+    #[test]
+    fn it_detects_repeated_states() {
+        let mut detector = Detector::new();
+        let mut state = 3;
+        let cycle = (0..)
+            .find_map(|i| {
+                let cycle = detector.observe(i, state);
+                state = step(&state);
+                cycle
+            })
+            .unwrap();
+        assert_eq!(
+            Cycle {
+                start: 4,
+                length: 2
+            },
+            cycle
+        );
+    }
+
+    #[test]
+    fn it_extrapolates() {
+        let cycle = Cycle {
+            start: 2,
+            length: 3,
+        };
+        assert_eq!(2, cycle.equivalent_step(2));
+        assert_eq!(3, cycle.equivalent_step(9));
+        assert_eq!(1, cycle.equivalent_step(1));
+        // grows by 1 before the cycle, then by 1, 2 and 3 in every cycle
+        let heights = [0, 1, 2, 3, 5, 8, 9];
+        assert_eq!(5, cycle.extrapolate(&heights, 4));
+        assert_eq!(14, cycle.extrapolate(&heights, 8));
+        assert_eq!(2 + 6 * 1_000, cycle.extrapolate(&heights, 2 + 3 * 1_000));
+    }
+}
@@ -0,0 +1,355 @@
+use std::{
+    fmt::Display,
+    ops::{Index, IndexMut},
+};
+
+use color_eyre::eyre::eyre;
+
+use super::{Compass, ParseError};
+
+/// A rectangular grid stored row by row, indexed by `(x, y)` with `(0, 0)` in the top left corner
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T: Clone> Grid<T> {
+    #[must_use]
+    pub fn new(width: usize, height: usize, fill: T) -> Self {
+        Self {
+            cells: vec![fill; width * height],
+            width,
+            height,
+        }
+    }
+
+    /// Like [`Grid::parse`], but lines shorter than the longest one are padded with `fill`
+    /// # Errors
+    /// If a character cannot be mapped to a cell, as a [`ParseError`]
+    pub fn parse_ragged(
+        s: &str,
+        fill: T,
+        mut cell: impl FnMut(char) -> color_eyre::Result<T>,
+    ) -> color_eyre::Result<Self> {
+        let width = s
+            .lines()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or_default();
+        let mut grid = Self::new(width, s.lines().count(), fill);
+        for (y, line) in s.lines().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                grid[(x, y)] = cell(c)
+                    .map_err(|err| ParseError::new(y + 1, x + 1, line, format!("{err:#}")))?;
+            }
+        }
+        Ok(grid)
+    }
+}
+
+impl<T> Grid<T> {
+    /// Parses a character map, one line per row
+    /// # Errors
+    /// If a character cannot be mapped to a cell or the lines differ in length, as a [`ParseError`]
+    pub fn parse(
+        s: &str,
+        mut cell: impl FnMut(char) -> color_eyre::Result<T>,
+    ) -> color_eyre::Result<Self> {
+        let mut cells = Vec::with_capacity(s.len());
+        let mut width = None;
+        let mut height = 0;
+        for (y, line) in s.lines().enumerate() {
+            let length = line.chars().count();
+            let expected = *width.get_or_insert(length);
+            if expected != length {
+                let message = format!("Line has {length} characters instead of {expected}");
+                return Err(ParseError::new(y + 1, length.min(expected) + 1, line, message).into());
+            }
+            for (x, c) in line.chars().enumerate() {
+                cells.push(
+                    cell(c)
+                        .map_err(|err| ParseError::new(y + 1, x + 1, line, format!("{err:#}")))?,
+                );
+            }
+            height += 1;
+        }
+        Ok(Self {
+            cells,
+            width: width.unwrap_or_default(),
+            height,
+        })
+    }
+
+    /// # Errors
+    /// If the number of cells is not a multiple of the width
+    pub fn from_cells(width: usize, cells: Vec<T>) -> color_eyre::Result<Self> {
+        if width == 0 || !cells.len().is_multiple_of(width) {
+            return Err(eyre!(
+                "{} cells cannot be split into rows of {width}",
+                cells.len()
+            ));
+        }
+        Ok(Self {
+            height: cells.len() / width,
+            cells,
+            width,
+        })
+    }
+
+    #[must_use]
+    pub const fn width(&self) -> usize {
+        self.width
+    }
+
+    #[must_use]
+    pub const fn height(&self) -> usize {
+        self.height
+    }
+
+    #[must_use]
+    pub const fn contains(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height
+    }
+
+    /// The index of a position in the cells, row by row
+    #[must_use]
+    pub const fn index_of(&self, x: usize, y: usize) -> usize {
+        y * self.width + x
+    }
+
+    #[must_use]
+    pub const fn position_of(&self, index: usize) -> (usize, usize) {
+        (index % self.width, index / self.width)
+    }
+
+    #[must_use]
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        self.contains(x, y)
+            .then(|| &self.cells[self.index_of(x, y)])
+    }
+
+    #[must_use]
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+        let index = self.index_of(x, y);
+        self.contains(x, y).then(|| &mut self.cells[index])
+    }
+
+    /// Like [`Grid::get`] for positions that may be left of or above the grid
+    #[must_use]
+    pub fn get_signed(&self, x: isize, y: isize) -> Option<&T> {
+        self.get(usize::try_from(x).ok()?, usize::try_from(y).ok()?)
+    }
+
+    /// # Panics
+    /// If the row is out of bounds
+    #[must_use]
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks_exact(self.width.max(1))
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        self.cells
+            .iter()
+            .skip(x)
+            .step_by(self.width.max(1))
+            .take(self.height)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.cells.iter_mut()
+    }
+
+    /// All positions, row by row
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    /// The first position (row by row) whose cell matches
+    pub fn find(&self, predicate: impl Fn(&T) -> bool) -> Option<(usize, usize)> {
+        self.cells
+            .iter()
+            .position(predicate)
+            .map(|index| self.position_of(index))
+    }
+
+    /// The neighbours to the top, right, bottom and left that are inside the grid
+    pub fn neighbours(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        [(0, -1), (1, 0), (0, 1), (-1, 0)]
+            .into_iter()
+            .filter_map(move |delta| self.offset(x, y, delta))
+    }
+
+    /// Like [`Grid::neighbours`], including the diagonal neighbours
+    pub fn neighbours_with_diagonals(
+        &self,
+        x: usize,
+        y: usize,
+    ) -> impl Iterator<Item = (usize, usize)> + '_ {
+        Compass::iter().filter_map(move |direction| self.offset(x, y, direction.offset()))
+    }
+
+    /// The cells from `(x, y)` (exclusive) to the edge of the grid in a direction
+    pub fn ray(
+        &self,
+        x: usize,
+        y: usize,
+        direction: impl Into<Compass>,
+    ) -> impl Iterator<Item = &T> + '_ {
+        let delta = direction.into().offset();
+        std::iter::successors(self.offset(x, y, delta), move |&(x, y)| {
+            self.offset(x, y, delta)
+        })
+        .map(|(x, y)| &self[(x, y)])
+    }
+
+    #[must_use]
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            cells: self.cells.iter().map(f).collect(),
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    fn offset(&self, x: usize, y: usize, (dx, dy): (isize, isize)) -> Option<(usize, usize)> {
+        let (x, y) = (x.checked_add_signed(dx)?, y.checked_add_signed(dy)?);
+        self.contains(x, y).then_some((x, y))
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &Self::Output {
+        assert!(
+            self.contains(x, y),
+            "({x}, {y}) is outside of the {}x{} grid",
+            self.width,
+            self.height
+        );
+        &self.cells[self.index_of(x, y)]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut Self::Output {
+        assert!(
+            self.contains(x, y),
+            "({x}, {y}) is outside of the {}x{} grid",
+            self.width,
+            self.height
+        );
+        let index = self.index_of(x, y);
+        &mut self.cells[index]
+    }
+}
+
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::util::Direction;
+
+    use super::*;
+
+    fn digits(s: &str) -> color_eyre::Result<Grid<u32>> {
+        Grid::parse(s, |c| {
+            c.to_digit(10).ok_or_else(|| eyre!("'{c}' is not a digit"))
+        })
+    }
+
+    #[test]
+    fn it_parses_character_maps() {
+        let grid = digits("123\n456\n").unwrap();
+        assert_eq!((3, 2), (grid.width(), grid.height()));
+        assert_eq!(6, grid[(2, 1)]);
+        assert_eq!(&[4, 5, 6], grid.row(1));
+        assert_eq!(vec![&2, &5], grid.column(1).collect::<Vec<_>>());
+        assert_eq!("123\n456\n", grid.to_string());
+        let err = |s| digits(s).unwrap_err().downcast::<ParseError>().unwrap();
+        let ragged = err("12\n345");
+        assert_eq!((2, 3), (ragged.line, ragged.column));
+        assert_eq!(ParseError::new(1, 2, "1x", "'x' is not a digit"), err("1x"));
+    }
+
+    #[test]
+    fn it_pads_ragged_lines() {
+        let grid = Grid::parse_ragged("  #\n#", ' ', Ok).unwrap();
+        assert_eq!("  #\n#  \n", grid.to_string());
+    }
+
+    #[test]
+    fn it_checks_bounds() {
+        let grid = digits("12\n34").unwrap();
+        assert_eq!(Some(&3), grid.get(0, 1));
+        assert_eq!(None, grid.get(2, 0));
+        assert_eq!(None, grid.get_signed(-1, 0));
+        assert_eq!(Some(&4), grid.get_signed(1, 1));
+    }
+
+    #[test]
+    fn it_finds_neighbours() {
+        let grid = Grid::from_cells(5, (0..25).collect()).unwrap();
+        let mut neighbours: Vec<usize> = grid.neighbours(1, 1).map(|(x, y)| grid[(x, y)]).collect();
+        neighbours.sort_unstable();
+        assert_eq!(vec![1, 5, 7, 11], neighbours);
+
+        let mut neighbours: Vec<usize> = grid.neighbours(0, 1).map(|(x, y)| grid[(x, y)]).collect();
+        neighbours.sort_unstable();
+        assert_eq!(vec![0, 6, 10], neighbours);
+
+        assert_eq!(3, grid.neighbours_with_diagonals(0, 0).count());
+        assert_eq!(8, grid.neighbours_with_diagonals(2, 2).count());
+    }
+
+    #[test]
+    fn it_casts_rays() {
+        let grid = Grid::from_cells(3, (0..9).collect()).unwrap();
+        assert_eq!(
+            vec![&3, &0],
+            grid.ray(0, 2, Direction::Up).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![&5],
+            grid.ray(1, 1, Direction::Right).collect::<Vec<_>>()
+        );
+        assert_eq!(0, grid.ray(0, 0, Direction::Left).count());
+        assert_eq!(
+            vec![&4, &8],
+            grid.ray(0, 0, Compass::SE).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn it_finds_positions() {
+        let grid = digits("12\n34").unwrap();
+        assert_eq!(Some((1, 1)), grid.find(|&cell| cell == 4));
+        assert_eq!((1, 1), grid.position_of(grid.index_of(1, 1)));
+        assert_eq!(
+            vec![(0, 0), (1, 0), (0, 1), (1, 1)],
+            grid.positions().collect::<Vec<_>>()
+        );
+        assert_eq!("24\n68\n", grid.map(|cell| cell * 2).to_string());
+    }
+}
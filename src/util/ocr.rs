@@ -0,0 +1,364 @@
+//! Reading the letters Advent of Code draws with lit pixels (`#`), like the CRT of day 10
+
+use std::fmt::{Display, Write};
+
+/// A fixed-size font: glyphs are `width` × `height` pixels and start every `pitch` columns
+#[derive(Debug, Clone, Copy)]
+pub struct Font {
+    pub width: usize,
+    pub height: usize,
+    pub pitch: usize,
+    glyphs: &'static [(char, &'static [&'static str])],
+}
+
+impl Font {
+    /// The letters the font can draw
+    pub fn letters(&self) -> impl Iterator<Item = char> {
+        self.glyphs.iter().map(|&(letter, _)| letter)
+    }
+
+    fn glyph(&self, letter: char) -> Option<&'static [&'static str]> {
+        self.glyphs
+            .iter()
+            .find(|&&(l, _)| l == letter)
+            .map(|&(_, rows)| rows)
+    }
+
+    /// The letter whose pixels are the `pitch` wide cell, ignoring the blank space after the glyph
+    fn letter(&self, cell: &[Vec<bool>]) -> Option<char> {
+        self.glyphs.iter().find_map(|&(letter, rows)| {
+            let matches = rows.iter().zip(cell).all(|(row, pixels)| {
+                (0..self.pitch)
+                    .all(|x| row.as_bytes().get(x).is_some_and(|&b| b == b'#') == pixels[x])
+            });
+            matches.then_some(letter)
+        })
+    }
+
+    /// Draws `text` with `#` and `.` in this font, leaving a blank cell for spaces
+    /// # Errors
+    /// If the font has no glyph for one of the letters
+    pub fn render(&self, text: &str) -> Result<String, UnknownLetter> {
+        let glyphs = text
+            .chars()
+            .map(|letter| match letter {
+                ' ' => Ok(&[] as &[&str]),
+                _ => self.glyph(letter).ok_or(UnknownLetter(letter)),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let rows: Vec<String> = (0..self.height)
+            .map(|y| {
+                glyphs.iter().fold(String::new(), |mut row, glyph| {
+                    write!(
+                        row,
+                        "{:.<pitch$}",
+                        glyph.get(y).unwrap_or(&""),
+                        pitch = self.pitch
+                    )
+                    .unwrap();
+                    row
+                })
+            })
+            .collect();
+        Ok(rows.join("\n"))
+    }
+}
+
+/// The font of most puzzles, with glyphs 4 (Y: 5) pixels wide
+pub const SMALL: Font = Font {
+    width: 4,
+    height: 6,
+    pitch: 5,
+    glyphs: &[
+        ('A', &[".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+        ('B', &["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+        ('C', &[".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+        ('E', &["####", "#...", "###.", "#...", "#...", "####"]),
+        ('F', &["####", "#...", "###.", "#...", "#...", "#..."]),
+        ('G', &[".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+        ('H', &["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+        ('I', &[".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+        ('J', &["..##", "...#", "...#", "...#", "#..#", ".##."]),
+        ('K', &["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+        ('L', &["#...", "#...", "#...", "#...", "#...", "####"]),
+        ('O', &[".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+        ('P', &["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+        ('R', &["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+        ('S', &[".###", "#...", "#...", ".##.", "...#", "###."]),
+        ('U', &["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+        ('Y', &["#...#", "#...#", ".#.#.", "..#..", "..#..", "..#.."]),
+        ('Z', &["####", "...#", "..#.", ".#..", "#...", "####"]),
+    ],
+};
+
+/// The font of 2018's day 10, with glyphs 6 pixels wide
+pub const LARGE: Font = Font {
+    width: 6,
+    height: 10,
+    pitch: 8,
+    glyphs: &[
+        (
+            'A',
+            &[
+                "..##..", ".#..#.", "#....#", "#....#", "#....#", "######", "#....#", "#....#",
+                "#....#", "#....#",
+            ],
+        ),
+        (
+            'B',
+            &[
+                "#####.", "#....#", "#....#", "#....#", "#####.", "#....#", "#....#", "#....#",
+                "#....#", "#####.",
+            ],
+        ),
+        (
+            'C',
+            &[
+                ".####.", "#....#", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....",
+                "#....#", ".####.",
+            ],
+        ),
+        (
+            'E',
+            &[
+                "######", "#.....", "#.....", "#.....", "#####.", "#.....", "#.....", "#.....",
+                "#.....", "######",
+            ],
+        ),
+        (
+            'F',
+            &[
+                "######", "#.....", "#.....", "#.....", "#####.", "#.....", "#.....", "#.....",
+                "#.....", "#.....",
+            ],
+        ),
+        (
+            'G',
+            &[
+                ".####.", "#....#", "#.....", "#.....", "#.....", "#..###", "#....#", "#....#",
+                "#...##", ".###.#",
+            ],
+        ),
+        (
+            'H',
+            &[
+                "#....#", "#....#", "#....#", "#....#", "######", "#....#", "#....#", "#....#",
+                "#....#", "#....#",
+            ],
+        ),
+        (
+            'J',
+            &[
+                "...###", "....#.", "....#.", "....#.", "....#.", "....#.", "....#.", "#...#.",
+                "#...#.", ".###..",
+            ],
+        ),
+        (
+            'K',
+            &[
+                "#....#", "#...#.", "#..#..", "#.#...", "##....", "##....", "#.#...", "#..#..",
+                "#...#.", "#....#",
+            ],
+        ),
+        (
+            'L',
+            &[
+                "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....",
+                "#.....", "######",
+            ],
+        ),
+        (
+            'N',
+            &[
+                "#....#", "##...#", "##...#", "#.#..#", "#.#..#", "#..#.#", "#..#.#", "#...##",
+                "#...##", "#....#",
+            ],
+        ),
+        (
+            'P',
+            &[
+                "#####.", "#....#", "#....#", "#....#", "#####.", "#.....", "#.....", "#.....",
+                "#.....", "#.....",
+            ],
+        ),
+        (
+            'R',
+            &[
+                "#####.", "#....#", "#....#", "#....#", "#####.", "#..#..", "#...#.", "#...#.",
+                "#....#", "#....#",
+            ],
+        ),
+        (
+            'X',
+            &[
+                "#....#", "#....#", ".#..#.", ".#..#.", "..##..", "..##..", ".#..#.", ".#..#.",
+                "#....#", "#....#",
+            ],
+        ),
+        (
+            'Z',
+            &[
+                "######", ".....#", ".....#", "....#.", "...#..", "..#...", ".#....", "#.....",
+                "#.....", "######",
+            ],
+        ),
+    ],
+};
+
+/// The letters of a picture, next to the picture itself
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Text {
+    pub text: String,
+    pub picture: String,
+}
+
+impl Display for Text {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+/// Returned by [`Font::render`] for letters the font cannot draw
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnknownLetter(pub char);
+
+impl Display for UnknownLetter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "The font has no glyph for '{}'", self.0)
+    }
+}
+
+impl std::error::Error for UnknownLetter {}
+
+/// Why a picture could not be read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OcrError {
+    /// No font is as high as the picture
+    UnknownHeight(usize),
+    /// The glyphs at these positions (counting from 0) are no letters of the font
+    UnknownGlyphs {
+        positions: Vec<usize>,
+        picture: String,
+    },
+}
+
+impl Display for OcrError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownHeight(height) => write!(f, "There is no font {height} pixels high"),
+            Self::UnknownGlyphs { positions, picture } => {
+                let positions: Vec<String> = positions.iter().map(ToString::to_string).collect();
+                write!(
+                    f,
+                    "Unrecognized glyphs at positions {}:\n{picture}",
+                    positions.join(", ")
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for OcrError {}
+
+/// Reads a picture of `#` (lit) and other characters (dark) in the font matching its height
+/// # Errors
+/// If there is no such font or a glyph is not one of its letters
+pub fn read(picture: &str) -> Result<Text, OcrError> {
+    let height = picture.lines().count();
+    let font = [SMALL, LARGE]
+        .into_iter()
+        .find(|font| font.height == height)
+        .ok_or(OcrError::UnknownHeight(height))?;
+    read_with(&font, picture)
+}
+
+/// Like [`read`], with the given font
+/// # Errors
+/// If a glyph is not one of the letters of the font
+pub fn read_with(font: &Font, picture: &str) -> Result<Text, OcrError> {
+    let pixels: Vec<Vec<bool>> = picture
+        .lines()
+        .map(|row| row.chars().map(|c| c == '#').collect())
+        .collect();
+    let width = pixels.iter().map(Vec::len).max().unwrap_or(0);
+    let cells = (0..width.div_ceil(font.pitch)).map(|i| {
+        pixels
+            .iter()
+            .map(|row| {
+                (i * font.pitch..(i + 1) * font.pitch)
+                    .map(|x| row.get(x) == Some(&true))
+                    .collect()
+            })
+            .collect::<Vec<Vec<bool>>>()
+    });
+    let mut text = String::new();
+    let mut positions = Vec::new();
+    for (i, cell) in cells.enumerate() {
+        if cell.iter().flatten().all(|&lit| !lit) {
+            text.push(' ');
+        } else if let Some(letter) = font.letter(&cell) {
+            text.push(letter);
+        } else {
+            positions.push(i);
+        }
+    }
+    let picture = picture.to_owned();
+    if positions.is_empty() {
+        Ok(Text {
+            text: text.trim_end().to_owned(),
+            picture,
+        })
+    } else {
+        Err(OcrError::UnknownGlyphs { positions, picture })
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn it_reads_every_letter() {
+        for font in [SMALL, LARGE] {
+            let letters: String = font.letters().collect();
+            let picture = font.render(&letters).unwrap();
+            assert!(picture
+                .lines()
+                .all(|row| row.len() == letters.len() * font.pitch));
+            assert_eq!(letters, read(&picture).unwrap().text);
+        }
+        assert_eq!(Err(UnknownLetter('Q')), SMALL.render("QA"));
+    }
+
+    #[test]
+    fn it_reads_a_screen() {
+        let screen = "\
+.##..###..####.#..#.
+#..#.#..#.#....#..#.
+#..#.###..###..####.
+####.#..#.#....#..#.
+#..#.#..#.#....#..#.
+#..#.###..####.#..#.";
+        let text = read(screen).unwrap();
+        assert_eq!("ABEH", text.to_string());
+        assert_eq!(screen, text.picture);
+        assert_eq!("A B", read(&SMALL.render("A B ").unwrap()).unwrap().text);
+    }
+
+    #[test]
+    fn it_lists_unknown_glyphs() {
+        let mut rows: Vec<String> = SMALL
+            .render("ABCE")
+            .unwrap()
+            .lines()
+            .map(str::to_owned)
+            .collect();
+        rows[0].replace_range(8..9, "#");
+        rows[3].replace_range(17..18, "#");
+        let Err(OcrError::UnknownGlyphs { positions, .. }) = read(&rows.join("\n")) else {
+            panic!("Expected unknown glyphs");
+        };
+        assert_eq!(vec![1, 3], positions);
+        assert_eq!(Err(OcrError::UnknownHeight(2)), read("#.\n.#"));
+    }
+}
@@ -0,0 +1,147 @@
+//! Spreading independent work over several threads, for whole days and their heaviest loops
+//!
+//! Everything runs on the calling thread unless [`set_jobs`] allowed more threads. Work started
+//! from within one of these threads runs sequentially, so that parallel days do not each spawn
+//! their own set of threads.
+
+use std::{
+    cell::Cell,
+    panic,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
+static JOBS: AtomicUsize = AtomicUsize::new(1);
+
+thread_local! {
+    static IS_WORKER: Cell<bool> = const { Cell::new(false) };
+}
+
+/// The number of threads [`map`] and [`find_map`] may use
+#[must_use]
+pub fn jobs() -> usize {
+    JOBS.load(Ordering::Relaxed)
+}
+
+/// Allows [`map`] and [`find_map`] to use up to `jobs` threads, 1 runs everything sequentially
+pub fn set_jobs(jobs: usize) {
+    JOBS.store(jobs.max(1), Ordering::Relaxed);
+}
+
+fn threads_for(items: usize) -> usize {
+    if IS_WORKER.get() {
+        1
+    } else {
+        jobs().min(items)
+    }
+}
+
+/// Runs `work` on `threads` scoped threads and collects what they return
+fn spawn<R: Send>(threads: usize, work: impl Fn() -> Vec<R> + Sync) -> Vec<R> {
+    thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    IS_WORKER.set(true);
+                    work()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|err| panic::resume_unwind(err))
+            })
+            .collect()
+    })
+}
+
+/// Like `items.iter().map(f).collect()`, with the items distributed over up to [`jobs`] threads
+pub fn map<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> R + Sync) -> Vec<R> {
+    let threads = threads_for(items.len());
+    if threads <= 1 {
+        return items.iter().map(f).collect();
+    }
+    let next = AtomicUsize::new(0);
+    let mut results = spawn(threads, || {
+        let mut results = Vec::new();
+        loop {
+            let i = next.fetch_add(1, Ordering::Relaxed);
+            let Some(item) = items.get(i) else {
+                return results;
+            };
+            results.push((i, f(item)));
+        }
+    });
+    results.sort_unstable_by_key(|&(i, _)| i);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// Like `items.iter().find_map(f)`, with the items distributed over up to [`jobs`] threads.
+/// The result is always the one of the first matching item; items after it are skipped.
+pub fn find_map<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> Option<R> + Sync) -> Option<R> {
+    let threads = threads_for(items.len());
+    if threads <= 1 {
+        return items.iter().find_map(f);
+    }
+    let next = AtomicUsize::new(0);
+    let found = AtomicUsize::new(usize::MAX);
+    let results = spawn(threads, || loop {
+        // items are handed out in order, so everything before a match is still looked at
+        let i = next.fetch_add(1, Ordering::Relaxed);
+        let Some(item) = items.get(i).filter(|_| i < found.load(Ordering::Relaxed)) else {
+            return Vec::new();
+        };
+        if let Some(result) = f(item) {
+            found.fetch_min(i, Ordering::Relaxed);
+            return vec![(i, result)];
+        }
+    });
+    results
+        .into_iter()
+        .min_by_key(|&(i, _)| i)
+        .map(|(_, result)| result)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    /// [`set_jobs`] is global, so the tests share it and only ever raise it
+    fn with_threads() {
+        set_jobs(jobs().max(4));
+    }
+
+    #[test]
+    fn it_keeps_the_order() {
+        with_threads();
+        let items: Vec<u64> = (0..100).collect();
+        let squares = map(&items, |&x| {
+            thread::sleep(std::time::Duration::from_micros(100 - x));
+            x * x
+        });
+        assert_eq!(items.iter().map(|x| x * x).collect::<Vec<_>>(), squares);
+        assert!(map(&[] as &[u8], |&x| x).is_empty());
+    }
+
+    #[test]
+    fn it_finds_the_first_match() {
+        with_threads();
+        let items: Vec<u64> = (0..1000).collect();
+        assert_eq!(
+            Some(30),
+            find_map(&items, |&x| (x > 0 && x % 15 == 0 && x % 2 == 0)
+                .then_some(x))
+        );
+        assert_eq!(None, find_map(&items, |&x| (x > 1000).then_some(x)));
+    }
+
+    #[test]
+    fn it_runs_nested_work_sequentially() {
+        with_threads();
+        let nested = map(&[1, 2, 3], |_| map(&[1, 2], |_| IS_WORKER.get()));
+        assert_eq!(vec![vec![true, true]; 3], nested);
+    }
+}
@@ -0,0 +1,199 @@
+//! Parse errors that point at the offending text of the puzzle input
+
+use std::fmt::Display;
+
+use nom::{combinator::all_consuming, Finish, IResult};
+
+/// What could not be parsed, and where: `line` and `column` start at 1,
+/// `text` is the whole line containing the error
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub text: String,
+    pub message: String,
+}
+
+impl ParseError {
+    #[must_use]
+    pub fn new(line: usize, column: usize, text: impl Into<String>, message: impl Display) -> Self {
+        Self {
+            line,
+            column,
+            text: text.into(),
+            message: message.to_string(),
+        }
+    }
+
+    /// An error at the start of `rest`, which has to be a part of `input`
+    /// (like the remaining input of a parser), or at the end of `input` if it is not
+    #[must_use]
+    pub fn at(input: &str, rest: &str, message: impl Display) -> Self {
+        let offset = (rest.as_ptr() as usize)
+            .checked_sub(input.as_ptr() as usize)
+            .filter(|&offset| offset <= input.len())
+            .unwrap_or(input.len());
+        let before = &input[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        let line_end = input[offset..]
+            .find('\n')
+            .map_or(input.len(), |i| offset + i);
+        Self::new(
+            before.matches('\n').count() + 1,
+            before[line_start..].chars().count() + 1,
+            input[line_start..line_end].trim_end_matches('\r'),
+            message,
+        )
+    }
+
+    /// Moves an error found in a single line (or block of lines) to its place in the whole input
+    #[must_use]
+    pub const fn offset(mut self, lines: usize) -> Self {
+        self.line += lines;
+        self
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )?;
+        writeln!(f, "  | {}", self.text)?;
+        write!(f, "  | {:>width$}", "^", width = self.column)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parses every line of the input, reporting the first line that fails.
+///
+/// A line parser points at the token that failed by returning a [`ParseError`] located within the
+/// line (see [`ParseError::at`]), other errors are reported at the start of the line.
+/// # Errors
+/// If a line cannot be parsed
+pub fn lines<T, E: Into<color_eyre::Report>>(
+    input: &str,
+    mut parse: impl FnMut(&str) -> Result<T, E>,
+) -> Result<Vec<T>, ParseError> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| {
+            parse(line).map_err(|err| match err.into().downcast::<ParseError>() {
+                Ok(located) => located.offset(i),
+                Err(err) => ParseError::new(i + 1, 1, line, format!("{err:#}")),
+            })
+        })
+        .collect()
+}
+
+/// Like [`lines`], for line parsers that already locate their errors within the line
+/// # Errors
+/// If a line cannot be parsed
+pub fn located_lines<T>(
+    input: &str,
+    mut parse: impl FnMut(&str) -> Result<T, ParseError>,
+) -> Result<Vec<T>, ParseError> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| parse(line).map_err(|err| err.offset(i)))
+        .collect()
+}
+
+/// Runs a nom parser that has to consume the whole input
+/// # Errors
+/// If the parser fails, located where it failed
+pub fn nom<'a, T>(
+    input: &'a str,
+    parser: impl FnMut(&'a str) -> IResult<&'a str, T>,
+) -> Result<T, ParseError> {
+    all_consuming(parser)(input)
+        .finish()
+        .map(|(_, value)| value)
+        .map_err(|err| {
+            let message = if err.input.is_empty() {
+                "Unexpected end of input".to_owned()
+            } else {
+                format!("Unexpected input ({:?} expected)", err.code)
+            };
+            ParseError::at(input, err.input, message)
+        })
+}
+
+#[cfg(test)]
+pub mod tests {
+    use nom::{bytes::complete::tag, character::complete::u8, sequence::preceded};
+
+    use super::*;
+
+    #[test]
+    fn it_locates_errors() {
+        let input = "first\nsecond line\nthird";
+        let err = ParseError::at(input, &input[13..], "Not a number");
+        assert_eq!(ParseError::new(2, 8, "second line", "Not a number"), err);
+        assert_eq!(
+            "line 2, column 8: Not a number\n  | second line\n  |        ^",
+            err.to_string()
+        );
+        assert_eq!(3, ParseError::at(input, "elsewhere", "").line);
+        assert_eq!(
+            ParseError::new(5, 8, "second line", "Not a number"),
+            err.offset(3)
+        );
+    }
+
+    #[test]
+    fn it_reports_failing_lines() {
+        let numbers = lines("1\n2\nx\n4", str::parse::<u8>);
+        assert_eq!(
+            Err(ParseError::new(3, 1, "x", "invalid digit found in string")),
+            numbers
+        );
+        assert_eq!(Ok(vec![1, 2]), lines("1\n2", str::parse::<u8>));
+        let pairs = lines("1 2\n3 x", |line: &str| {
+            let (a, b) = line.split_once(' ').unwrap_or_default();
+            Ok::<_, ParseError>((
+                a.parse::<u8>()
+                    .map_err(|err| ParseError::at(line, a, err))?,
+                b.parse::<u8>()
+                    .map_err(|err| ParseError::at(line, b, err))?,
+            ))
+        });
+        assert_eq!(
+            Err(ParseError::new(
+                2,
+                3,
+                "3 x",
+                "invalid digit found in string"
+            )),
+            pairs
+        );
+        let located = located_lines("x=1\nx=y", |line: &str| {
+            nom(line, |input| preceded(tag("x="), u8)(input))
+        });
+        assert_eq!(
+            Err(ParseError::new(
+                2,
+                3,
+                "x=y",
+                "Unexpected input (Digit expected)"
+            )),
+            located
+        );
+    }
+
+    #[test]
+    fn it_locates_nom_errors() {
+        let parser = |input| preceded(tag("x="), u8)(input);
+        assert_eq!(Ok(12), nom("x=12", parser));
+        let err = nom("x=a", parser).unwrap_err();
+        assert_eq!((1, 3), (err.line, err.column));
+        let err = nom("x=", parser).unwrap_err();
+        assert_eq!("Unexpected end of input", err.message);
+        assert_eq!(1, nom("x=1 and more", parser).unwrap_err().line);
+    }
+}
@@ -0,0 +1,408 @@
+//! Shortest path searches over graphs given as a neighbour function,
+//! so the graph never has to be built explicitly.
+
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+    hash::Hash,
+};
+
+use super::Number;
+
+/// The distances from the start nodes to every reached node, and how they were reached
+#[derive(Debug, Clone)]
+pub struct Paths<N, C> {
+    distances: HashMap<N, C>,
+    predecessors: HashMap<N, N>,
+}
+
+impl<N: Copy + Eq + Hash, C: Copy> Paths<N, C> {
+    fn new() -> Self {
+        Self {
+            distances: HashMap::new(),
+            predecessors: HashMap::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn distance(&self, node: N) -> Option<C> {
+        self.distances.get(&node).copied()
+    }
+
+    #[must_use]
+    pub const fn distances(&self) -> &HashMap<N, C> {
+        &self.distances
+    }
+
+    /// The nodes from a start node to `to`, both inclusive, or `None` if `to` was not reached
+    #[must_use]
+    pub fn path(&self, to: N) -> Option<Vec<N>> {
+        self.distances.contains_key(&to).then(|| {
+            let mut path: Vec<N> =
+                std::iter::successors(Some(to), |node| self.predecessors.get(node).copied())
+                    .collect();
+            path.reverse();
+            path
+        })
+    }
+}
+
+/// A node in the priority queue, ordered so that the [`BinaryHeap`] pops the lowest estimate first
+struct Queued<N, C> {
+    estimate: C,
+    cost: C,
+    node: N,
+}
+
+impl<N, C: Ord> Ord for Queued<N, C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimate.cmp(&self.estimate)
+    }
+}
+
+impl<N, C: Ord> PartialOrd for Queued<N, C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<N, C: Ord> PartialEq for Queued<N, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.estimate == other.estimate
+    }
+}
+
+impl<N, C: Ord> Eq for Queued<N, C> {}
+
+/// A* from all start nodes until a goal is settled, or until all reachable nodes are settled
+fn best_first<N, C, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut neighbours: impl FnMut(N) -> I,
+    mut heuristic: impl FnMut(N) -> C,
+    mut is_goal: impl FnMut(N) -> bool,
+) -> (Paths<N, C>, Option<N>)
+where
+    N: Copy + Eq + Hash,
+    C: Number,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let mut paths = Paths::new();
+    let mut queue = BinaryHeap::new();
+    let mut settled = HashSet::new();
+    for start in starts {
+        paths.distances.insert(start, C::ZERO);
+        queue.push(Queued {
+            estimate: heuristic(start),
+            cost: C::ZERO,
+            node: start,
+        });
+    }
+    while let Some(Queued { cost, node, .. }) = queue.pop() {
+        if !settled.insert(node) {
+            continue;
+        }
+        if is_goal(node) {
+            return (paths, Some(node));
+        }
+        for (next, step) in neighbours(node) {
+            let cost = cost + step;
+            if paths.distance(next).is_none_or(|known| cost < known) {
+                paths.distances.insert(next, cost);
+                paths.predecessors.insert(next, node);
+                queue.push(Queued {
+                    estimate: cost + heuristic(next),
+                    cost,
+                    node: next,
+                });
+            }
+        }
+    }
+    (paths, None)
+}
+
+/// The cheapest paths from any of the start nodes to every reachable node.
+/// `neighbours` returns the nodes reachable from a node together with the cost to get there.
+pub fn dijkstra<N, C, I>(
+    starts: impl IntoIterator<Item = N>,
+    neighbours: impl FnMut(N) -> I,
+) -> Paths<N, C>
+where
+    N: Copy + Eq + Hash,
+    C: Number,
+    I: IntoIterator<Item = (N, C)>,
+{
+    best_first(starts, neighbours, |_| C::ZERO, |_| false).0
+}
+
+/// The cheapest path from `start` to a goal and its cost.
+///
+/// Settled nodes are not visited again, so the `heuristic` has to be consistent: it must never
+/// overestimate the remaining cost to the nearest goal, nor drop by more than the cost of a step.
+pub fn astar<N, C, I>(
+    start: N,
+    neighbours: impl FnMut(N) -> I,
+    heuristic: impl FnMut(N) -> C,
+    is_goal: impl FnMut(N) -> bool,
+) -> Option<(Vec<N>, C)>
+where
+    N: Copy + Eq + Hash,
+    C: Number,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let (paths, goal) = best_first([start], neighbours, heuristic, is_goal);
+    let goal = goal?;
+    Some((paths.path(goal)?, paths.distance(goal)?))
+}
+
+/// Breadth first search from several start nodes at once, visiting the nodes in order of their
+/// distance to the nearest start node. As it is lazy, it also works on infinite graphs.
+pub struct Bfs<N, F> {
+    queue: VecDeque<N>,
+    paths: Paths<N, usize>,
+    neighbours: F,
+}
+
+/// See [`Bfs`]
+pub fn bfs<N, I, F>(starts: impl IntoIterator<Item = N>, neighbours: F) -> Bfs<N, F>
+where
+    N: Copy + Eq + Hash,
+    I: IntoIterator<Item = N>,
+    F: FnMut(N) -> I,
+{
+    let mut paths = Paths::new();
+    let mut queue = VecDeque::new();
+    for start in starts {
+        if paths.distances.insert(start, 0).is_none() {
+            queue.push_back(start);
+        }
+    }
+    Bfs {
+        queue,
+        paths,
+        neighbours,
+    }
+}
+
+impl<N: Copy + Eq + Hash, F> Bfs<N, F> {
+    /// The paths to the nodes visited so far (and their neighbours)
+    pub const fn paths(&self) -> &Paths<N, usize> {
+        &self.paths
+    }
+}
+
+impl<N, I, F> Iterator for Bfs<N, F>
+where
+    N: Copy + Eq + Hash,
+    I: IntoIterator<Item = N>,
+    F: FnMut(N) -> I,
+{
+    type Item = (N, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.queue.pop_front()?;
+        let distance = self.paths.distances[&node];
+        for next in (self.neighbours)(node) {
+            if let std::collections::hash_map::Entry::Vacant(entry) =
+                self.paths.distances.entry(next)
+            {
+                entry.insert(distance + 1);
+                self.paths.predecessors.insert(next, node);
+                self.queue.push_back(next);
+            }
+        }
+        Some((node, distance))
+    }
+}
+
+/// The shortest distances between all pairs of nodes (Floyd–Warshall)
+#[derive(Debug, Clone)]
+pub struct AllPairs<N, C> {
+    nodes: Vec<N>,
+    indices: HashMap<N, usize>,
+    distances: Vec<Option<C>>,
+    /// The first step on the shortest path from one node to another
+    next: Vec<Option<usize>>,
+}
+
+/// See [`AllPairs`]. Neighbours that are not in `nodes` are ignored.
+pub fn all_pairs<N, C, I>(
+    nodes: impl IntoIterator<Item = N>,
+    mut neighbours: impl FnMut(N) -> I,
+) -> AllPairs<N, C>
+where
+    N: Copy + Eq + Hash,
+    C: Number,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let nodes: Vec<N> = nodes.into_iter().collect();
+    let indices: HashMap<N, usize> = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, &node)| (node, i))
+        .collect();
+    let n = nodes.len();
+    let mut distances = vec![None; n * n];
+    let mut next = vec![None; n * n];
+    for (i, &node) in nodes.iter().enumerate() {
+        distances[i * n + i] = Some(C::ZERO);
+        next[i * n + i] = Some(i);
+        for (neighbour, cost) in neighbours(node) {
+            let Some(&j) = indices.get(&neighbour) else {
+                continue;
+            };
+            if distances[i * n + j].is_none_or(|known| cost < known) {
+                distances[i * n + j] = Some(cost);
+                next[i * n + j] = Some(j);
+            }
+        }
+    }
+    for k in 0..n {
+        for i in 0..n {
+            let Some(ik) = distances[i * n + k] else {
+                continue;
+            };
+            for j in 0..n {
+                let Some(kj) = distances[k * n + j] else {
+                    continue;
+                };
+                if distances[i * n + j].is_none_or(|ij| ik + kj < ij) {
+                    distances[i * n + j] = Some(ik + kj);
+                    next[i * n + j] = next[i * n + k];
+                }
+            }
+        }
+    }
+    AllPairs {
+        nodes,
+        indices,
+        distances,
+        next,
+    }
+}
+
+impl<N: Copy + Eq + Hash, C: Copy> AllPairs<N, C> {
+    fn index(&self, from: N, to: N) -> Option<usize> {
+        Some(self.indices.get(&from)? * self.nodes.len() + self.indices.get(&to)?)
+    }
+
+    /// `None` if `to` cannot be reached from `from`
+    #[must_use]
+    pub fn distance(&self, from: N, to: N) -> Option<C> {
+        self.distances[self.index(from, to)?]
+    }
+
+    /// The first node after `from` on the shortest path to `to`
+    #[must_use]
+    pub fn next_hop(&self, from: N, to: N) -> Option<N> {
+        self.next[self.index(from, to)?].map(|i| self.nodes[i])
+    }
+
+    /// The nodes on the shortest path from `from` to `to`, both inclusive
+    #[must_use]
+    pub fn path(&self, from: N, to: N) -> Option<Vec<N>> {
+        let mut path = vec![from];
+        let mut current = from;
+        while current != to {
+            current = self.next_hop(current, to)?;
+            path.push(current);
+        }
+        Some(path)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::util::{Grid, Vec2};
+
+    use super::*;
+
+    /// A small weighted graph:
+    /// ```text
+    /// a -1- b -1- c
+    ///  \          |
+    ///   ----5---- d -1- e
+    /// ```
+    fn graph(node: char) -> Vec<(char, u32)> {
+        let edges = [
+            ('a', 'b', 1),
+            ('b', 'c', 1),
+            ('a', 'd', 5),
+            ('c', 'd', 1),
+            ('d', 'e', 1),
+        ];
+        edges
+            .iter()
+            .filter_map(|&(from, to, cost)| match node {
+                n if n == from => Some((to, cost)),
+                n if n == to => Some((from, cost)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn it_finds_cheapest_paths() {
+        let paths = dijkstra(['a'], graph);
+        assert_eq!(Some(3), paths.distance('d'));
+        assert_eq!(Some(4), paths.distance('e'));
+        assert_eq!(Some(vec!['a', 'b', 'c', 'd', 'e']), paths.path('e'));
+        assert_eq!(Some(vec!['a']), paths.path('a'));
+        assert_eq!(None, paths.path('z'));
+        assert_eq!(5, paths.distances().len());
+
+        let paths = dijkstra(['a', 'e'], graph);
+        assert_eq!(Some(1), paths.distance('d'));
+    }
+
+    #[test]
+    fn it_finds_paths_with_heuristic() {
+        let maze = Grid::parse("S..#\n.#..\n.#.#\n...G", Ok).unwrap();
+        let goal = Vec2::new(3, 3);
+        let neighbours = |p: Vec2<usize>| {
+            p.neighbours()
+                .filter(|n| maze.get(n.x, n.y).is_some_and(|&c| c != '#'))
+                .map(|n| (n, 1))
+                .collect::<Vec<_>>()
+        };
+        let (path, cost) = astar(
+            Vec2::new(0, 0),
+            neighbours,
+            |p| p.manhattan_distance(goal),
+            |p| p == goal,
+        )
+        .unwrap();
+        assert_eq!(6, cost);
+        assert_eq!(7, path.len());
+        assert!(path.windows(2).all(|w| w[0].manhattan_distance(w[1]) == 1));
+        assert_eq!(
+            None,
+            astar(Vec2::new(0, 0), neighbours, |_| 0, |p| p == Vec2::new(3, 0))
+        );
+    }
+
+    #[test]
+    fn it_searches_breadth_first() {
+        let mut search = bfs([0], |n: i32| [n - 1, n + 1, n * 3]);
+        assert_eq!(Some((10, 4)), search.find(|&(n, _)| n == 10));
+        assert_eq!(Some(5), search.paths().path(10).map(|path| path.len()));
+
+        let mut search = bfs([0, 20], |n: i32| [n - 1, n + 1]);
+        assert_eq!(Some((15, 5)), search.find(|&(n, _)| n == 15));
+    }
+
+    #[test]
+    fn it_finds_all_pairs() {
+        let distances = all_pairs("abcde".chars(), graph);
+        assert_eq!(Some(3), distances.distance('a', 'd'));
+        assert_eq!(Some(0), distances.distance('c', 'c'));
+        assert_eq!(Some('b'), distances.next_hop('a', 'e'));
+        assert_eq!(
+            Some(vec!['e', 'd', 'c', 'b', 'a']),
+            distances.path('e', 'a')
+        );
+
+        let disconnected = all_pairs("ab".chars(), |_| Vec::<(char, u8)>::new());
+        assert_eq!(None, disconnected.distance('a', 'b'));
+        assert_eq!(None, disconnected.path('a', 'b'));
+    }
+}
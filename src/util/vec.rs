@@ -0,0 +1,383 @@
+use std::{
+    fmt::{Debug, Display},
+    hash::Hash,
+    ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign},
+};
+
+use super::{Compass, Direction};
+
+/// The integer types points and vectors can be made of
+pub trait Number:
+    Copy
+    + Ord
+    + Hash
+    + Default
+    + Debug
+    + Display
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + AddAssign
+    + SubAssign
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    #[must_use]
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+
+    #[must_use]
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+
+    /// The distance between two numbers, which cannot underflow for unsigned numbers
+    #[must_use]
+    fn distance(self, other: Self) -> Self {
+        self.max(other) - self.min(other)
+    }
+}
+
+macro_rules! number {
+    ($($t:ty),*) => {
+        $(impl Number for $t {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+
+            fn checked_add(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_add(self, rhs)
+            }
+
+            fn checked_sub(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_sub(self, rhs)
+            }
+        })*
+    };
+}
+
+number!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+/// A point or vector on a plane, with `y` growing downwards like in [`super::Grid`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Vec2<T> {
+    pub x: T,
+    pub y: T,
+}
+
+impl<T: Number> Vec2<T> {
+    #[must_use]
+    pub const fn new(x: T, y: T) -> Self {
+        Self { x, y }
+    }
+
+    #[must_use]
+    pub fn manhattan_distance(self, other: Self) -> T {
+        self.x.distance(other.x) + self.y.distance(other.y)
+    }
+
+    /// The number of king moves between two points
+    #[must_use]
+    pub fn chebyshev_distance(self, other: Self) -> T {
+        self.x.distance(other.x).max(self.y.distance(other.y))
+    }
+
+    /// The neighbours above, to the right, below and to the left, skipping those that do not fit into `T`
+    pub fn neighbours(self) -> impl Iterator<Item = Self> {
+        let Self { x, y } = self;
+        [
+            (Some(x), y.checked_sub(T::ONE)),
+            (x.checked_add(T::ONE), Some(y)),
+            (Some(x), y.checked_add(T::ONE)),
+            (x.checked_sub(T::ONE), Some(y)),
+        ]
+        .into_iter()
+        .filter_map(|(x, y)| Some(Self::new(x?, y?)))
+    }
+
+    /// Like [`Vec2::neighbours`], including the diagonal neighbours, clockwise starting above
+    pub fn neighbours_with_diagonals(self) -> impl Iterator<Item = Self> {
+        let Self { x, y } = self;
+        let (left, right) = (x.checked_sub(T::ONE), x.checked_add(T::ONE));
+        let (up, down) = (y.checked_sub(T::ONE), y.checked_add(T::ONE));
+        [
+            (Some(x), up),
+            (right, up),
+            (right, Some(y)),
+            (right, down),
+            (Some(x), down),
+            (left, down),
+            (left, Some(y)),
+            (left, up),
+        ]
+        .into_iter()
+        .filter_map(|(x, y)| Some(Self::new(x?, y?)))
+    }
+}
+
+impl<T: Number + Neg<Output = T>> Vec2<T> {
+    /// Rotates by 90° clockwise (on screen, where `y` grows downwards)
+    #[must_use]
+    pub fn rotate_right(self) -> Self {
+        Self::new(-self.y, self.x)
+    }
+
+    /// Rotates by 90° counterclockwise (on screen, where `y` grows downwards)
+    #[must_use]
+    pub fn rotate_left(self) -> Self {
+        Self::new(self.y, -self.x)
+    }
+
+    /// The sign of each component
+    #[must_use]
+    pub fn signum(self) -> Self {
+        let signum = |v: T| match v.cmp(&T::ZERO) {
+            std::cmp::Ordering::Less => -T::ONE,
+            std::cmp::Ordering::Equal => T::ZERO,
+            std::cmp::Ordering::Greater => T::ONE,
+        };
+        Self::new(signum(self.x), signum(self.y))
+    }
+}
+
+impl<T: Number + Neg<Output = T>> From<Direction> for Vec2<T> {
+    /// The unit step in a direction
+    fn from(direction: Direction) -> Self {
+        Compass::from(direction).into()
+    }
+}
+
+impl<T> From<(T, T)> for Vec2<T> {
+    fn from((x, y): (T, T)) -> Self {
+        Self { x, y }
+    }
+}
+
+impl<T> From<Vec2<T>> for (T, T) {
+    fn from(Vec2 { x, y }: Vec2<T>) -> Self {
+        (x, y)
+    }
+}
+
+impl<T: Number> Add for Vec2<T> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl<T: Number> AddAssign for Vec2<T> {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl<T: Number> Sub for Vec2<T> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl<T: Number> SubAssign for Vec2<T> {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl<T: Number> Mul<T> for Vec2<T> {
+    type Output = Self;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        Self::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+impl<T: Number + Neg<Output = T>> Neg for Vec2<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::new(-self.x, -self.y)
+    }
+}
+
+impl<T: Display> Display for Vec2<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+/// A point or vector in space
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Vec3<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+impl<T: Number> Vec3<T> {
+    #[must_use]
+    pub const fn new(x: T, y: T, z: T) -> Self {
+        Self { x, y, z }
+    }
+
+    #[must_use]
+    pub fn manhattan_distance(self, other: Self) -> T {
+        self.x.distance(other.x) + self.y.distance(other.y) + self.z.distance(other.z)
+    }
+
+    #[must_use]
+    pub fn chebyshev_distance(self, other: Self) -> T {
+        self.x
+            .distance(other.x)
+            .max(self.y.distance(other.y))
+            .max(self.z.distance(other.z))
+    }
+
+    /// The six neighbours sharing a face, skipping those that do not fit into `T`
+    pub fn neighbours(self) -> impl Iterator<Item = Self> {
+        let Self { x, y, z } = self;
+        [
+            (x.checked_sub(T::ONE), Some(y), Some(z)),
+            (x.checked_add(T::ONE), Some(y), Some(z)),
+            (Some(x), y.checked_sub(T::ONE), Some(z)),
+            (Some(x), y.checked_add(T::ONE), Some(z)),
+            (Some(x), Some(y), z.checked_sub(T::ONE)),
+            (Some(x), Some(y), z.checked_add(T::ONE)),
+        ]
+        .into_iter()
+        .filter_map(|(x, y, z)| Some(Self::new(x?, y?, z?)))
+    }
+}
+
+impl<T> From<(T, T, T)> for Vec3<T> {
+    fn from((x, y, z): (T, T, T)) -> Self {
+        Self { x, y, z }
+    }
+}
+
+impl<T: Number> Add for Vec3<T> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z)
+    }
+}
+
+impl<T: Number> AddAssign for Vec3<T> {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl<T: Number> Sub for Vec3<T> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z)
+    }
+}
+
+impl<T: Number> SubAssign for Vec3<T> {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl<T: Number> Mul<T> for Vec3<T> {
+    type Output = Self;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        Self::new(self.x * rhs, self.y * rhs, self.z * rhs)
+    }
+}
+
+impl<T: Number + Neg<Output = T>> Neg for Vec3<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::new(-self.x, -self.y, -self.z)
+    }
+}
+
+impl<T: Display> Display for Vec3<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({}, {}, {})", self.x, self.y, self.z)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn it_does_arithmetic() {
+        let a = Vec2::new(3, -2);
+        let b = Vec2::new(-1, 5);
+        assert_eq!(Vec2::new(2, 3), a + b);
+        assert_eq!(Vec2::new(4, -7), a - b);
+        assert_eq!(Vec2::new(9, -6), a * 3);
+        assert_eq!(Vec2::new(-3, 2), -a);
+        let mut c = a;
+        c += b;
+        c -= a;
+        assert_eq!(b, c);
+        assert_eq!(Vec3::new(2, 4, 6), Vec3::new(1, 2, 3) * 2 + Vec3::default());
+    }
+
+    #[test]
+    fn it_measures_distances() {
+        let a = Vec2::<isize>::new(2, 18);
+        let b = Vec2::new(-2, 15);
+        assert_eq!(7, a.manhattan_distance(b));
+        assert_eq!(4, a.chebyshev_distance(b));
+        assert_eq!(
+            3,
+            Vec2::<usize>::new(5, 1).manhattan_distance(Vec2::new(3, 2))
+        );
+        assert_eq!(
+            1,
+            Vec3::<u8>::new(1, 2, 3).manhattan_distance(Vec3::new(1, 2, 2))
+        );
+        assert_eq!(
+            3,
+            Vec3::<u8>::new(1, 2, 3).chebyshev_distance(Vec3::new(4, 2, 2))
+        );
+    }
+
+    #[test]
+    fn it_finds_neighbours() {
+        let origin = Vec2::<usize>::default();
+        assert_eq!(
+            vec![Vec2::new(1, 0), Vec2::new(0, 1)],
+            origin.neighbours().collect::<Vec<_>>()
+        );
+        assert_eq!(3, origin.neighbours_with_diagonals().count());
+        assert_eq!(
+            8,
+            Vec2::<isize>::default().neighbours_with_diagonals().count()
+        );
+        assert!(Vec2::new(1, 1)
+            .neighbours_with_diagonals()
+            .all(|neighbour| neighbour.chebyshev_distance(Vec2::new(1, 1)) == 1));
+        assert_eq!(6, Vec3::<u8>::new(1, 1, 1).neighbours().count());
+        assert_eq!(3, Vec3::<u8>::default().neighbours().count());
+    }
+
+    #[test]
+    fn it_rotates() {
+        let right = Vec2::<i32>::from(Direction::Right);
+        assert_eq!(Vec2::from(Direction::Down), right.rotate_right());
+        assert_eq!(Vec2::from(Direction::Up), right.rotate_left());
+        for direction in [
+            Direction::Up,
+            Direction::Right,
+            Direction::Down,
+            Direction::Left,
+        ] {
+            let delta = Vec2::<i32>::from(direction);
+            assert_eq!(Vec2::from(direction.turn(false)), delta.rotate_right());
+            assert_eq!(Vec2::from(direction.turn(true)), delta.rotate_left());
+        }
+        assert_eq!(Vec2::new(1, -1), Vec2::new(7, -3).signum());
+    }
+}
@@ -0,0 +1,257 @@
+use std::{collections::HashMap, fmt::Display, path::Path, str::FromStr};
+
+use aoc2022::util::{InputSource, MissingInput};
+use color_eyre::eyre::{eyre, WrapErr};
+
+use crate::runner::{solve_days, Answer, Part};
+
+pub const ANSWERS_FILE: &str = "answers.toml";
+
+/// The recorded answers, read from a TOML file like
+/// ```toml
+/// [day01]
+/// part1 = 24000
+/// part2 = "45000"
+/// ```
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Answers(HashMap<(u8, Part), String>);
+
+impl Answers {
+    /// # Errors
+    /// If the file cannot be read or is malformed
+    pub fn load(path: &Path) -> color_eyre::Result<Self> {
+        std::fs::read_to_string(path)
+            .wrap_err_with(|| format!("Cannot read answers '{}'", path.display()))?
+            .parse()
+            .wrap_err_with(|| format!("Cannot parse answers '{}'", path.display()))
+    }
+
+    #[must_use]
+    pub fn get(&self, day: u8, part: Part) -> Option<&str> {
+        self.0.get(&(day, part)).map(String::as_str)
+    }
+}
+
+impl FromStr for Answers {
+    type Err = color_eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let table: toml::Table = s.parse()?;
+        let mut answers = HashMap::new();
+        for (key, parts) in &table {
+            let day: u8 = key
+                .strip_prefix("day")
+                .and_then(|day| day.parse().ok())
+                .ok_or_else(|| eyre!("Expected a table named like 'day01', got '{key}'"))?;
+            let parts = parts
+                .as_table()
+                .ok_or_else(|| eyre!("'{key}' is not a table"))?;
+            for (name, value) in parts {
+                let part = match name.as_str() {
+                    "part1" => Part::One,
+                    "part2" => Part::Two,
+                    _ => {
+                        return Err(eyre!(
+                            "Expected 'part1' or 'part2' in '{key}', got '{name}'"
+                        ))
+                    }
+                };
+                let value = match value {
+                    toml::Value::String(s) => s.clone(),
+                    toml::Value::Integer(i) => i.to_string(),
+                    _ => return Err(eyre!("'{key}.{name}' must be a string or an integer")),
+                };
+                answers.insert((day, part), value);
+            }
+        }
+        Ok(Self(answers))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Verdict {
+    Pass,
+    Fail {
+        expected: String,
+        actual: String,
+    },
+    /// There is no recorded answer to compare against
+    Missing(String),
+    /// The part is not solved
+    Skipped,
+    /// There is no puzzle input to solve the day with
+    NoInput,
+    Error(String),
+}
+
+impl Verdict {
+    fn new(expected: Option<&str>, actual: Option<String>) -> Self {
+        match (expected, actual) {
+            (_, None) => Self::Skipped,
+            (None, Some(actual)) => Self::Missing(actual),
+            (Some(expected), Some(actual)) if expected.trim() == actual.trim() => Self::Pass,
+            (Some(expected), Some(actual)) => Self::Fail {
+                expected: expected.to_owned(),
+                actual,
+            },
+        }
+    }
+
+    #[must_use]
+    pub const fn is_regression(&self) -> bool {
+        matches!(self, Self::Fail { .. } | Self::Error(_))
+    }
+}
+
+impl Display for Verdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Pass => write!(f, "pass"),
+            Self::Fail { expected, actual } if expected.contains('\n') || actual.contains('\n') => {
+                write!(f, "FAIL\nexpected:\n{expected}\nactual:\n{actual}")
+            }
+            Self::Fail { expected, actual } => {
+                write!(f, "FAIL (expected {expected}, got {actual})")
+            }
+            Self::Missing(_) => write!(f, "missing (no recorded answer)"),
+            Self::Skipped => write!(f, "skipped (not solved)"),
+            Self::NoInput => write!(f, "missing (no puzzle input)"),
+            Self::Error(err) => write!(f, "ERROR ({err})"),
+        }
+    }
+}
+
+/// Runs the selected parts of the selected days and compares them against the recorded answers.
+/// Failing (or unregistered) days are reported as [`Verdict::Error`] instead of aborting,
+/// days without an input file as [`Verdict::NoInput`].
+pub fn verify(
+    days: &[u8],
+    parts: &[Part],
+    input: &InputSource,
+    answers: &Answers,
+) -> Vec<(u8, Part, Verdict)> {
+    let mut verdicts = Vec::with_capacity(days.len() * parts.len());
+    for (number, result) in solve_days(days, parts, input) {
+        match result {
+            Ok(report) => {
+                for Answer { part, value, .. } in report.answers {
+                    verdicts.push((number, part, Verdict::new(answers.get(number, part), value)));
+                }
+            }
+            Err(err) if err.is::<MissingInput>() => {
+                verdicts.extend(parts.iter().map(|&part| (number, part, Verdict::NoInput)));
+            }
+            Err(err) => {
+                for &part in parts {
+                    verdicts.push((number, part, Verdict::Error(format!("{err:#}"))));
+                }
+            }
+        }
+    }
+    verdicts
+}
+
+/// Verifies the selection, prints a line per part and a summary.
+/// # Errors
+/// If an answer does not match or a day fails
+pub fn run(
+    days: &[u8],
+    parts: &[Part],
+    input: &InputSource,
+    answers: &Path,
+) -> color_eyre::Result<()> {
+    let answers = Answers::load(answers)?;
+    let verdicts = verify(days, parts, input, &answers);
+    for (day, part, verdict) in &verdicts {
+        println!("Day {day:02} part {part}: {verdict}");
+    }
+    let count = |f: fn(&Verdict) -> bool| verdicts.iter().filter(|(_, _, v)| f(v)).count();
+    let regressions = count(Verdict::is_regression);
+    println!(
+        "{} passed, {regressions} failed, {} missing, {} skipped",
+        count(|v| *v == Verdict::Pass),
+        count(|v| matches!(v, Verdict::Missing(_) | Verdict::NoInput)),
+        count(|v| *v == Verdict::Skipped),
+    );
+    if regressions > 0 {
+        return Err(eyre!(
+            "{regressions} of {} parts failed verification",
+            verdicts.len()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    #[test]
+    fn it_parses_answers() {
+        let answers: Answers =
+            "[day01]\npart1 = 24000\npart2 = \"45000\"\n\n[day10]\npart2 = '''\n##..\n..##'''"
+                .parse()
+                .unwrap();
+        assert_eq!(Some("24000"), answers.get(1, Part::One));
+        assert_eq!(Some("45000"), answers.get(1, Part::Two));
+        assert_eq!(Some("##..\n..##"), answers.get(10, Part::Two));
+        assert_eq!(None, answers.get(10, Part::One));
+    }
+
+    #[test]
+    fn it_rejects_malformed_answers() {
+        assert!("[1]\npart1 = 3".parse::<Answers>().is_err());
+        assert!("[day01]\npart3 = 3".parse::<Answers>().is_err());
+        assert!("[day01]\npart1 = 3.5".parse::<Answers>().is_err());
+    }
+
+    #[test]
+    fn it_verifies_against_recorded_answers() {
+        let answers: Answers = "[day01]\npart1 = 24000\n[day04]\npart1 = 2\npart2 = 5"
+            .parse()
+            .unwrap();
+        let input = InputSource::Directory(PathBuf::from("examples/"));
+        let verdicts = verify(&[1, 4, 25], &[Part::One, Part::Two], &input, &answers);
+        assert_eq!(
+            vec![
+                (1, Part::One, Verdict::Pass),
+                (1, Part::Two, Verdict::Missing("45000".to_owned())),
+                (4, Part::One, Verdict::Pass),
+                (
+                    4,
+                    Part::Two,
+                    Verdict::Fail {
+                        expected: "5".to_owned(),
+                        actual: "4".to_owned()
+                    }
+                ),
+                (25, Part::One, Verdict::Missing("2=-1=0".to_owned())),
+                (25, Part::Two, Verdict::Skipped),
+            ],
+            verdicts
+        );
+    }
+
+    #[test]
+    fn it_reports_failing_days_as_errors() {
+        let input = InputSource::File(PathBuf::from("examples/"));
+        let verdicts = verify(&[1], &[Part::One], &input, &Answers::default());
+        assert!(verdicts[0].2.is_regression());
+    }
+
+    #[test]
+    fn it_reports_days_without_input_as_missing() {
+        let input = InputSource::File(PathBuf::from("does/not/exist.txt"));
+        let verdicts = verify(&[1], &[Part::One, Part::Two], &input, &Answers::default());
+        assert_eq!(
+            vec![
+                (1, Part::One, Verdict::NoInput),
+                (1, Part::Two, Verdict::NoInput)
+            ],
+            verdicts
+        );
+        assert!(!verdicts[0].2.is_regression());
+    }
+}